@@ -0,0 +1,48 @@
+//! Run with `cargo bench`. Budgets on a recent laptop, to be noticed when
+//! exceeded: a month under 100µs, a year under 1ms, a 50-year agenda under
+//! 5ms and a 100,000-line holiday file under 100ms.
+
+use std::{env, fmt::Write, fs, hint::black_box};
+
+use calp::bench::Holidays;
+use chrono::{Datelike, Days, NaiveDate};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn render(c: &mut Criterion) {
+    let holidays = Holidays::japanese(2000..=2050);
+    let today = NaiveDate::from_ymd_opt(2025, 5, 7).unwrap();
+    c.bench_function("single month", |b| {
+        b.iter(|| holidays.render_grid(black_box(&[(2025, 5)]), today))
+    });
+    let year = (1..=12).map(|m| (2025, m)).collect::<Vec<_>>();
+    c.bench_function("full year", |b| {
+        b.iter(|| holidays.render_grid(black_box(&year), today))
+    });
+}
+
+fn agenda(c: &mut Criterion) {
+    let holidays = Holidays::japanese(2000..=2050);
+    let from = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    c.bench_function("50-year agenda", |b| {
+        b.iter(|| holidays.agenda(black_box(from), 365 * 50))
+    });
+}
+
+fn load(c: &mut Criterion) {
+    let path = env::temp_dir().join(format!("calp-bench-{}.csv", std::process::id()));
+    let mut s = String::new();
+    let first = NaiveDate::from_ymd_opt(1800, 1, 1).unwrap();
+    for n in 0..100_000 {
+        let date = first + Days::new(n);
+        let category = ["national", "company", "personal"][date.weekday() as usize % 3];
+        writeln!(s, "{},event {n},{category}", date.format("%Y/%m/%d")).unwrap();
+    }
+    fs::write(&path, s).unwrap();
+    c.bench_function("100k-line holiday file", |b| {
+        b.iter(|| Holidays::load(black_box(&path)).unwrap())
+    });
+    fs::remove_file(path).unwrap();
+}
+
+criterion_group!(benches, render, agenda, load);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use std::{fs, path::Path};
+
+fn main() {
+    // Reported by `calp version --verbose`
+    println!(
+        "cargo:rustc-env=CALP_TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+    // Part of the holiday cache key, so a rebuild with changed holiday
+    // rules does not read entries cached by the old ones.
+    let mut files = vec![];
+    collect(Path::new("src"), &mut files);
+    files.sort();
+    let mut hash = 0xcbf29ce484222325_u64;
+    for file in files {
+        for b in fs::read(&file).unwrap() {
+            hash = (hash ^ u64::from(b)).wrapping_mul(0x100000001b3);
+        }
+    }
+    println!("cargo:rustc-env=CALP_BUILD_ID={hash:016x}");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src");
+}
+
+fn collect(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::fs;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    consts::{ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    filter::DayFilter,
+    holiday::{Holiday, HolidayInfo},
+    names::NameFit,
+    Lang, MAX_DATE,
+};
+
+/// Formats the next `count` holidays/events on or after `today`.
+pub fn format_upcoming(
+    holiday_info: &HolidayInfo,
+    today: NaiveDate,
+    count: usize,
+    day_filter: &DayFilter,
+    name_fit: &NameFit,
+    lang: Lang,
+) -> Vec<String> {
+    holiday_info
+        .iter_from(today)
+        .filter(|(date, holiday)| first_listed(*date, holiday, today))
+        .filter(|(date, _)| day_filter.matches(holiday_info, *date))
+        .take(count)
+        .map(|(date, holiday)| format_entry(date, holiday, Some(today), name_fit, lang))
+        .collect()
+}
+
+/// The last day of a window of `days` days after `today`, which stops at
+/// [`MAX_DATE`].
+pub fn window_end(today: NaiveDate, days: u32) -> NaiveDate {
+    today
+        .checked_add_days(Days::new(days.into()))
+        .map_or(MAX_DATE, |end| end.min(MAX_DATE))
+}
+
+/// Formats all holidays/events from `today` through the following `days` days.
+pub fn format_agenda(
+    holiday_info: &HolidayInfo,
+    today: NaiveDate,
+    days: u32,
+    day_filter: &DayFilter,
+    name_fit: &NameFit,
+    lang: Lang,
+) -> Vec<String> {
+    let end = window_end(today, days);
+    holiday_info
+        .iter_from(today)
+        .take_while(|(date, _)| *date <= end)
+        .filter(|(date, holiday)| first_listed(*date, holiday, today))
+        .filter(|(date, _)| day_filter.matches(holiday_info, *date))
+        .map(|(date, holiday)| format_entry(date, holiday, Some(today), name_fit, lang))
+        .collect()
+}
+
+/// Formats every holiday/event in `year`, each followed by its distance from
+/// `relative_to` when given.
+pub fn format_holidays(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    day_filter: &DayFilter,
+    name_fit: &NameFit,
+    relative_to: Option<NaiveDate>,
+    lang: Lang,
+) -> Vec<String> {
+    let Some(start) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+        return vec![];
+    };
+    holiday_info
+        .iter_from(start)
+        .take_while(|(date, _)| date.year() == year)
+        .filter(|(date, holiday)| first_listed(*date, holiday, start))
+        .filter(|(date, _)| day_filter.matches(holiday_info, *date))
+        .map(|(date, holiday)| format_entry(date, holiday, relative_to, name_fit, lang))
+        .collect()
+}
+
+/// Formats every holiday/event whose name contains `pattern` (case-insensitive),
+/// with its distance from today and its source file.
+pub fn format_search(
+    holiday_info: &HolidayInfo,
+    pattern: &str,
+    year: Option<i32>,
+    day_filter: &DayFilter,
+    name_fit: &NameFit,
+    today: NaiveDate,
+    lang: Lang,
+) -> Vec<String> {
+    let start = year
+        .and_then(|y| NaiveDate::from_ymd_opt(y, 1, 1))
+        .unwrap_or(NaiveDate::MIN);
+    holiday_info
+        .iter_from(NaiveDate::MIN)
+        .filter(|(date, _)| year.is_none_or(|y| date.year() == y))
+        .filter(|(date, holiday)| first_listed(*date, holiday, start))
+        .filter(|(_, holiday)| holiday.name_contains(pattern))
+        .filter(|(date, _)| day_filter.matches(holiday_info, *date))
+        .map(|(date, holiday)| {
+            let entry = format_entry(date, holiday, Some(today), name_fit, lang);
+            if holiday.source.is_empty() {
+                entry
+            } else {
+                format!("{entry}  [{}]", holiday.source)
+            }
+        })
+        .collect()
+}
+
+/// Formats each conflicting day with its entries and their sources, e.g.
+/// `2024/08/12 (月) 山の日 [holidays.csv] / 夏休み [pto.csv]`.
+pub fn format_conflicts(conflicts: &[(NaiveDate, &[Holiday])], lang: Lang) -> Vec<String> {
+    conflicts
+        .iter()
+        .map(|(date, holidays)| {
+            let weekday = date.weekday().num_days_from_sunday() as usize;
+            let entries = holidays
+                .iter()
+                .map(|h| format!("{} [{}]", h.name, h.source))
+                .collect::<Vec<_>>()
+                .join(" / ");
+            match lang {
+                Lang::Japanese => format!(
+                    "{} ({}) {entries}",
+                    date.format("%Y/%m/%d"),
+                    JAPANESE_WEEK_NAMES[weekday]
+                ),
+                Lang::English => format!(
+                    "{} ({}) {entries}",
+                    date.format("%Y-%m-%d"),
+                    ENGLISH_WEEK_NAMES[weekday]
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Formats each loaded source with its entry count, year coverage and, for
+/// files, when they were last modified, e.g.
+/// `/home/me/.calp_shuku  1050 entries  1955-2026  modified 2025-02-01 10:00`.
+pub fn format_sources(holiday_info: &HolidayInfo, lang: Lang) -> Vec<String> {
+    let sources = holiday_info.sources();
+    if sources.is_empty() {
+        return vec![match lang {
+            Lang::Japanese => "祝日データが読み込まれていません".to_string(),
+            Lang::English => "no holiday data loaded".to_string(),
+        }];
+    }
+    sources
+        .iter()
+        .map(|s| {
+            let modified = fs::metadata(&s.source)
+                .and_then(|m| m.modified())
+                .map(|t| DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M"));
+            let (first, last) = (s.first.year(), s.last.year());
+            match (lang, modified) {
+                (Lang::Japanese, Ok(t)) => {
+                    format!("{}  {}件  {first}〜{last}年  更新 {t}", s.source, s.entries)
+                }
+                (Lang::Japanese, Err(_)) => {
+                    format!("{}  {}件  {first}〜{last}年  (計算)", s.source, s.entries)
+                }
+                (Lang::English, Ok(t)) => format!(
+                    "{}  {} entries  {first}-{last}  modified {t}",
+                    s.source, s.entries
+                ),
+                (Lang::English, Err(_)) => format!(
+                    "{}  {} entries  {first}-{last}  (computed)",
+                    s.source, s.entries
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Multi-day entries are stored on every day they cover but listed once: on
+/// their first day, or on `start` when the listing begins inside the span.
+fn first_listed(date: NaiveDate, holiday: &Holiday, start: NaiveDate) -> bool {
+    holiday
+        .span
+        .is_none_or(|(first, _)| date == first || date == start)
+}
+
+fn format_entry(
+    date: NaiveDate,
+    holiday: &Holiday,
+    relative_to: Option<NaiveDate>,
+    name_fit: &NameFit,
+    lang: Lang,
+) -> String {
+    let weekday = date.weekday().num_days_from_sunday() as usize;
+    let time = holiday.time.map_or(String::new(), |t| format!("{t} "));
+    let until = match (holiday.span, lang) {
+        (Some((_, last)), Lang::Japanese) => format!(" (〜{})", last.format("%m/%d")),
+        (Some((_, last)), Lang::English) => format!(" (until {})", last.format("%m-%d")),
+        (None, _) => String::new(),
+    };
+    let when = match lang {
+        Lang::Japanese => format!(
+            "{} ({}) {time}",
+            date.format("%Y/%m/%d"),
+            JAPANESE_WEEK_NAMES[weekday]
+        ),
+        Lang::English => format!(
+            "{} ({}) {time}",
+            date.format("%Y-%m-%d"),
+            ENGLISH_WEEK_NAMES[weekday]
+        ),
+    };
+    let name = name_fit.fit(&holiday.name, when.width());
+    let entry = format!("{when}{name}{until}");
+    match relative_to {
+        Some(today) => format!(
+            "{entry}  {}",
+            format_relative((date - today).num_days(), lang)
+        ),
+        None => entry,
+    }
+}
+
+fn format_relative(days: i64, lang: Lang) -> String {
+    match (lang, days) {
+        (Lang::Japanese, 0) => "今日".to_string(),
+        (Lang::Japanese, ..0) => format!("{}日前", -days),
+        (Lang::Japanese, _) => format!("あと{days}日"),
+        (Lang::English, 0) => "today".to_string(),
+        (Lang::English, 1) => "in 1 day".to_string(),
+        (Lang::English, -1) => "1 day ago".to_string(),
+        (Lang::English, ..0) => format!("{} days ago", -days),
+        (Lang::English, _) => format!("in {days} days"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        agenda::{
+            format_agenda, format_conflicts, format_relative, format_search, format_upcoming,
+            window_end,
+        },
+        filter::DayFilter,
+        holiday::{holidays_of, parse_holiday_text, Category, DateFormat, HolidayInfo},
+        names::NameFit,
+        Lang, MAX_DATE,
+    };
+
+    /// Entries of holiday-file `text` read from the file `source`.
+    fn read_from(hi: &mut HolidayInfo, text: &str, source: &str) {
+        hi.extend(parse_holiday_text(text, DateFormat::Ymd, Category::National, source).unwrap());
+    }
+
+    #[test]
+    fn test_format_upcoming() {
+        let hi = holidays_of(&[
+            "2025/04/29,昭和の日",
+            "2025/05/01..2025/05/07,GW,personal",
+            "2025/05/03,憲法記念日",
+            "2025/05/04,みどりの日",
+        ]);
+        let today = NaiveDate::from_ymd_opt(2025, 5, 2).unwrap();
+        let upcoming = |count| {
+            format_upcoming(
+                &hi,
+                today,
+                count,
+                &DayFilter::default(),
+                &NameFit::default(),
+                Lang::English,
+            )
+        };
+        // A span under way is listed once, on today
+        assert_eq!(
+            upcoming(2),
+            [
+                "2025-05-02 (Fr) GW (until 05-07)  today",
+                "2025-05-03 (Sa) 憲法記念日  in 1 day",
+            ]
+        );
+        assert_eq!(upcoming(10).len(), 3);
+    }
+
+    #[test]
+    fn test_format_agenda() {
+        let hi = holidays_of(&[
+            "2025/07/21,海の日",
+            "2025/07/22 10:00-11:00,review,company",
+            "2025/08/11,山の日",
+            "2025/08/12,夏休み,personal",
+        ]);
+        let today = NaiveDate::from_ymd_opt(2025, 7, 22).unwrap();
+        let agenda = |days| {
+            format_agenda(
+                &hi,
+                today,
+                days,
+                &DayFilter::default(),
+                &NameFit::default(),
+                Lang::Japanese,
+            )
+        };
+        assert_eq!(agenda(0), ["2025/07/22 (火) 10:00-11:00 review  今日"]);
+        // The window includes its last day
+        assert_eq!(
+            agenda(20).last().unwrap(),
+            "2025/08/11 (月) 山の日  あと20日"
+        );
+        assert_eq!(agenda(20).len(), 2);
+    }
+
+    #[test]
+    fn test_format_conflicts() {
+        let mut hi = HolidayInfo::new();
+        read_from(
+            &mut hi,
+            "2024/08/11,山の日\n2024/08/12,振替休日",
+            "holidays.csv",
+        );
+        read_from(
+            &mut hi,
+            "2024/08/12..2024/08/13,夏休み,personal\n2024/08/13,お盆,company",
+            "pto.csv",
+        );
+        let date = |d| NaiveDate::from_ymd_opt(2024, 8, d).unwrap();
+        // Entries from a single source do not conflict.
+        let conflicts = hi.conflicts(date(1)..=date(31)).collect::<Vec<_>>();
+        assert_eq!(
+            format_conflicts(&conflicts, Lang::Japanese),
+            ["2024/08/12 (月) 振替休日 [holidays.csv] / 夏休み [pto.csv]"]
+        );
+        assert_eq!(hi.conflicts(date(13)..=date(31)).count(), 0);
+    }
+
+    #[test]
+    fn test_format_search() {
+        let mut hi = holidays_of(&[
+            "2024/06/03,健康診断,company",
+            "2025/06/02..2025/06/03,Health check,personal",
+            "2025/06/10,健康診断 (再検査),company",
+            "2024/12/28..2025/01/05,年末年始休暇,company",
+        ]);
+        read_from(&mut hi, "2025/06/06,健康診断,company", "team.csv");
+        let today = NaiveDate::from_ymd_opt(2025, 6, 6).unwrap();
+        let search = |pattern, year| {
+            format_search(
+                &hi,
+                pattern,
+                year,
+                &DayFilter::default(),
+                &NameFit::default(),
+                today,
+                Lang::English,
+            )
+        };
+        assert_eq!(
+            search("健康診断", Some(2025)),
+            [
+                "2025-06-06 (Fr) 健康診断  today  [team.csv]",
+                "2025-06-10 (Tu) 健康診断 (再検査)  in 4 days",
+            ]
+        );
+        assert_eq!(search("健康診断", None).len(), 3);
+        // Case is ignored, and a span is listed once.
+        assert_eq!(
+            search("HEALTH", None),
+            ["2025-06-02 (Mo) Health check (until 06-03)  4 days ago"]
+        );
+        // A span from the year before is listed from January 1st.
+        assert_eq!(
+            search("年末年始", Some(2025)),
+            ["2025-01-01 (We) 年末年始休暇 (until 01-05)  156 days ago"]
+        );
+        assert_eq!(search("年末年始", Some(2024)).len(), 1);
+        assert!(search("歯医者", None).is_empty());
+    }
+
+    #[test]
+    fn test_format_relative() {
+        assert_eq!(format_relative(0, Lang::English), "today");
+        assert_eq!(format_relative(1, Lang::English), "in 1 day");
+        assert_eq!(format_relative(-1, Lang::English), "1 day ago");
+        assert_eq!(format_relative(-12, Lang::English), "12 days ago");
+        assert_eq!(format_relative(3, Lang::Japanese), "あと3日");
+        assert_eq!(format_relative(-3, Lang::Japanese), "3日前");
+    }
+
+    #[test]
+    fn test_window_end() {
+        let today = NaiveDate::from_ymd_opt(2025, 12, 30).unwrap();
+        assert_eq!(window_end(today, 2).to_string(), "2026-01-01");
+        assert_eq!(window_end(today, u32::MAX), MAX_DATE);
+    }
+}
@@ -0,0 +1,49 @@
+//! Entry points for `benches/`, which only see the public API. Not part of
+//! the supported interface.
+
+use std::{ops::RangeInclusive, path::Path};
+
+use chrono::{NaiveDate, Weekday};
+
+use crate::{
+    agenda::format_agenda, filter::DayFilter, holiday::HolidayInfo, names::NameFit, print_months,
+    theme::Theme, Encoding, FormatConfig, Lang, LibResult,
+};
+
+/// Loaded holidays/events.
+pub struct Holidays(HolidayInfo);
+
+impl Holidays {
+    /// Japanese national holidays of `years`, with substitute holidays.
+    pub fn japanese(years: RangeInclusive<i32>) -> Holidays {
+        Holidays(HolidayInfo::japanese(years))
+    }
+
+    /// A UTF-8 holiday file.
+    pub fn load(path: &Path) -> LibResult<Holidays> {
+        HolidayInfo::from_file(path, Encoding::Utf8).map(Holidays)
+    }
+
+    /// The grid of `months` as `calp` prints it, colors included.
+    pub fn render_grid(&self, months: &[(i32, u32)], today: NaiveDate) -> Vec<u8> {
+        let format_config = FormatConfig {
+            show_year: months.len() == 1,
+            ..FormatConfig::plain(Lang::Japanese, Weekday::Sun, Theme::Default.palette())
+        };
+        let mut out = Vec::new();
+        print_months(&mut out, months, &format_config, today, &self.0).unwrap();
+        out
+    }
+
+    /// `calp upcoming --days days` as of `today`.
+    pub fn agenda(&self, today: NaiveDate, days: u32) -> Vec<String> {
+        format_agenda(
+            &self.0,
+            today,
+            days,
+            &DayFilter::default(),
+            &NameFit::default(),
+            Lang::Japanese,
+        )
+    }
+}
@@ -0,0 +1,245 @@
+use std::ops::RangeInclusive;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use clap::Args;
+
+use crate::{date_parser::parse_weekday, holiday::HolidayInfo, MAX_DATE, MIN_DATE};
+
+// Which weekdays count as working days, for all business-day calculations.
+// Not a doc comment: clap would take it as calp's about text.
+#[derive(Debug, Clone, Args)]
+pub struct WorkWeek {
+    /// Weekdays that are workdays e.g. mon,tue,wed,thu
+    #[arg(long, value_name = "WEEKDAYS", value_delimiter = ',', value_parser = parse_weekday, default_value = "mon,tue,wed,thu,fri", global = true)]
+    work_week: Vec<Weekday>,
+
+    /// Weekdays that count as half a business day e.g. sat
+    #[arg(long, value_name = "WEEKDAYS", value_delimiter = ',', value_parser = parse_weekday, global = true)]
+    half_days: Vec<Weekday>,
+}
+
+impl Default for WorkWeek {
+    fn default() -> WorkWeek {
+        WorkWeek {
+            work_week: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            half_days: vec![],
+        }
+    }
+}
+
+impl WorkWeek {
+    /// Workdays `work_week` and `half_days`, counted as half a business day.
+    pub fn new(work_week: Vec<Weekday>, half_days: Vec<Weekday>) -> WorkWeek {
+        WorkWeek {
+            work_week,
+            half_days,
+        }
+    }
+
+    /// Share of a business day a `weekday` is worth: 1, 0.5 for half days, or 0.
+    pub fn weight(&self, weekday: Weekday) -> f64 {
+        if self.work_week.contains(&weekday) {
+            1.0
+        } else if self.half_days.contains(&weekday) {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether any weekday counts, so that business days exist at all.
+    fn any(&self) -> bool {
+        !self.work_week.is_empty() || !self.half_days.is_empty()
+    }
+}
+
+/// How [`roll`] moves a date that is not a business day.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RollConvention {
+    /// To the next business day
+    Following,
+    /// To the next business day, unless that is in the next month; then to
+    /// the previous one
+    ModifiedFollowing,
+}
+
+/// Share of a business day `date` is worth; 0 on days off.
+pub fn business_day_weight(holiday_info: &HolidayInfo, date: NaiveDate) -> f64 {
+    if holiday_info.is_day_off(date) {
+        0.0
+    } else {
+        holiday_info.work_week().weight(date.weekday())
+    }
+}
+
+/// Whether `date` is a business day: a workday, or half day, that is not a day off.
+pub fn is_business_day(holiday_info: &HolidayInfo, date: NaiveDate) -> bool {
+    business_day_weight(holiday_info, date) > 0.0
+}
+
+/// The first business day after `date`, or `None` if there is none up to
+/// [`MAX_DATE`](crate::MAX_DATE).
+pub fn next_business_day(holiday_info: &HolidayInfo, date: NaiveDate) -> Option<NaiveDate> {
+    if !holiday_info.work_week().any() {
+        return None;
+    }
+    std::iter::successors(date.succ_opt(), NaiveDate::succ_opt)
+        .take_while(|d| *d <= MAX_DATE)
+        .find(|d| is_business_day(holiday_info, *d))
+}
+
+/// The last business day before `date`, or `None` if there is none from
+/// [`MIN_DATE`](crate::MIN_DATE).
+pub fn prev_business_day(holiday_info: &HolidayInfo, date: NaiveDate) -> Option<NaiveDate> {
+    if !holiday_info.work_week().any() {
+        return None;
+    }
+    std::iter::successors(date.pred_opt(), NaiveDate::pred_opt)
+        .take_while(|d| *d >= MIN_DATE)
+        .find(|d| is_business_day(holiday_info, *d))
+}
+
+/// `date` if it is a business day, or the business day `convention` moves it to.
+pub fn roll(
+    holiday_info: &HolidayInfo,
+    date: NaiveDate,
+    convention: RollConvention,
+) -> Option<NaiveDate> {
+    if is_business_day(holiday_info, date) {
+        return Some(date);
+    }
+    let following = next_business_day(holiday_info, date);
+    match convention {
+        RollConvention::Following => following,
+        RollConvention::ModifiedFollowing => match following {
+            Some(d) if d.month() == date.month() => Some(d),
+            _ => prev_business_day(holiday_info, date).or(following),
+        },
+    }
+}
+
+/// The day `n` business days after `date`, or before it when `n` is
+/// negative, with half days counted as 0.5: a half day and the next
+/// business day together make one. `None` past the supported range or
+/// without any business days.
+pub fn add_business_days(holiday_info: &HolidayInfo, date: NaiveDate, n: i64) -> Option<NaiveDate> {
+    if n == 0 {
+        return Some(date);
+    }
+    if !holiday_info.work_week().any() {
+        return None;
+    }
+    let step = if n > 0 {
+        NaiveDate::succ_opt
+    } else {
+        NaiveDate::pred_opt
+    };
+    let mut total = 0.0;
+    std::iter::successors(step(&date), step)
+        .take_while(|d| (MIN_DATE..=MAX_DATE).contains(d))
+        .find(|d| {
+            total += business_day_weight(holiday_info, *d);
+            total >= n.unsigned_abs() as f64
+        })
+}
+
+/// Number of business days in `days`, with half days counted as 0.5.
+pub fn count_business_days(holiday_info: &HolidayInfo, days: RangeInclusive<NaiveDate>) -> f64 {
+    days.start()
+        .iter_days()
+        .take_while(|d| d <= days.end())
+        .map(|d| business_day_weight(holiday_info, d))
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{
+        business::{
+            add_business_days, count_business_days, next_business_day, prev_business_day, roll,
+            RollConvention, WorkWeek,
+        },
+        holiday::{holidays_of, HolidayInfo},
+        MAX_DATE,
+    };
+
+    #[test]
+    fn test_count_business_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut hi = HolidayInfo::new();
+        // Mon 1 through Sun 14
+        assert_eq!(count_business_days(&hi, date(1)..=date(14)), 10.0);
+        // Fri 31 Dec 9999 is the last supported day
+        assert_eq!(count_business_days(&hi, MAX_DATE..=MAX_DATE), 1.0);
+
+        hi.set_work_week(WorkWeek {
+            work_week: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu],
+            half_days: vec![Weekday::Sat],
+        });
+        assert_eq!(count_business_days(&hi, date(1)..=date(14)), 9.0);
+    }
+
+    #[test]
+    fn test_roll() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let mut hi = holidays_of(&["2025/05/05,こどもの日", "2025/05/06,振替休日"]);
+        // Sat 3 and Sun 4 May, then two holidays
+        assert_eq!(next_business_day(&hi, date(5, 2)), Some(date(5, 7)));
+        assert_eq!(prev_business_day(&hi, date(5, 7)), Some(date(5, 2)));
+        assert_eq!(
+            roll(&hi, date(5, 7), RollConvention::Following),
+            Some(date(5, 7))
+        );
+        assert_eq!(
+            roll(&hi, date(5, 4), RollConvention::Following),
+            Some(date(5, 7))
+        );
+        // Sat 31 May rolls into June unless modified
+        assert_eq!(
+            roll(&hi, date(5, 31), RollConvention::Following),
+            Some(date(6, 2))
+        );
+        assert_eq!(
+            roll(&hi, date(5, 31), RollConvention::ModifiedFollowing),
+            Some(date(5, 30))
+        );
+
+        assert_eq!(add_business_days(&hi, date(5, 2), 1), Some(date(5, 7)));
+        assert_eq!(add_business_days(&hi, date(5, 7), -1), Some(date(5, 2)));
+        assert_eq!(add_business_days(&hi, date(5, 1), 3), Some(date(5, 8)));
+        assert_eq!(add_business_days(&hi, date(5, 4), 0), Some(date(5, 4)));
+        assert_eq!(add_business_days(&hi, date(5, 8), 7), Some(date(5, 19)));
+
+        // Half-day Saturdays: Sat 10 and Sat 17 May add up to the seventh day
+        hi.set_work_week(WorkWeek {
+            work_week: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            half_days: vec![Weekday::Sat],
+        });
+        assert_eq!(add_business_days(&hi, date(5, 8), 7), Some(date(5, 17)));
+
+        hi.set_work_week(WorkWeek {
+            work_week: vec![],
+            half_days: vec![],
+        });
+        assert_eq!(next_business_day(&hi, date(5, 2)), None);
+        assert_eq!(add_business_days(&hi, date(5, 2), 3), None);
+        assert_eq!(
+            roll(&hi, date(5, 2), RollConvention::ModifiedFollowing),
+            None
+        );
+    }
+}
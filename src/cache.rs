@@ -0,0 +1,304 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
+
+use crate::{
+    holiday::{Category, Holiday, HolidayInfo, TimeSpan},
+    paths::cache_dir,
+    source::{merge_sources, DateSource},
+    trace::Tracer,
+    LibResult,
+};
+
+const MAGIC: &[u8] = b"CALPHC2\n";
+
+const CATEGORIES: [Category; 4] = [
+    Category::National,
+    Category::Override,
+    Category::Company,
+    Category::Personal,
+];
+
+/// Merges `sources` as [`load_sources`](crate::source::load_sources) does,
+/// from the cache directory when it was written by the same build for the
+/// same sources and none of the files they read has changed since.
+/// Otherwise the cache is rewritten; failing to write it is not an error.
+/// Each set of sources has its own file, so alternating between profiles
+/// does not rewrite it every time.
+pub fn load_cached(sources: &[Box<dyn DateSource>], tracer: &Tracer) -> LibResult<HolidayInfo> {
+    match cache_dir() {
+        Some(dir) => load_with(&dir, sources, tracer),
+        None => merge_sources(sources, tracer).map(|(holiday_info, _)| holiday_info),
+    }
+}
+
+fn load_with(
+    dir: &Path,
+    sources: &[Box<dyn DateSource>],
+    tracer: &Tracer,
+) -> LibResult<HolidayInfo> {
+    let Some(key) = cache_key(sources) else {
+        return merge_sources(sources, tracer).map(|(holiday_info, _)| holiday_info);
+    };
+    let path = &dir.join(format!("holidays-{:016x}.bin", fnv1a(key.as_bytes())));
+    {
+        let _span = tracer.span("cache");
+        if let Some(holiday_info) = fs::read(path).ok().and_then(|b| decode(&b, &key)) {
+            return Ok(holiday_info);
+        }
+    }
+    let (holiday_info, inputs) = merge_sources(sources, tracer)?;
+    let stamped = inputs
+        .into_iter()
+        .map(|input| stamp(&input).map(|(len, hash)| (input, len, hash)))
+        .collect::<Option<Vec<_>>>();
+    if let Some(stamped) = stamped {
+        let _ = write(path, &encode(&key, &stamped, &holiday_info));
+    }
+    Ok(holiday_info)
+}
+
+/// The build and every source's key, or `None` if one cannot be cached.
+fn cache_key(sources: &[Box<dyn DateSource>]) -> Option<String> {
+    let mut key = format!(
+        "calp {} {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("CALP_BUILD_ID")
+    );
+    for source in sources {
+        key.push('\n');
+        key.push_str(&source.cache_key()?);
+    }
+    Some(key)
+}
+
+/// Length and hash of the contents of the file at `path`. Hashing is far
+/// cheaper than parsing, and unlike the modification time it also catches
+/// a change within the timestamp granularity or a restored backup.
+fn stamp(path: &Path) -> Option<(u64, u64)> {
+    let bytes = fs::read(path).ok()?;
+    Some((bytes.len() as u64, fnv1a(&bytes)))
+}
+
+/// 64-bit FNV-1a.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Writes through a temporary file, so a concurrent run never reads half a cache.
+fn write(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
+
+fn encode(key: &str, inputs: &[(PathBuf, u64, u64)], holiday_info: &HolidayInfo) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    put_str(&mut out, key);
+    put_u64(&mut out, inputs.len() as u64);
+    for (path, len, hash) in inputs {
+        put_str(&mut out, &path.to_string_lossy());
+        put_u64(&mut out, *len);
+        put_u64(&mut out, *hash);
+    }
+    let entries = holiday_info.iter_from(NaiveDate::MIN).collect::<Vec<_>>();
+    put_u64(&mut out, entries.len() as u64);
+    for (date, holiday) in entries {
+        put_date(&mut out, date);
+        put_str(&mut out, &holiday.name);
+        out.push(holiday.category as u8);
+        match holiday.time {
+            None => out.push(0),
+            Some(time) => {
+                out.push(if time.end.is_some() { 2 } else { 1 });
+                put_u64(&mut out, time.start.num_seconds_from_midnight().into());
+                if let Some(end) = time.end {
+                    put_u64(&mut out, end.num_seconds_from_midnight().into());
+                }
+            }
+        }
+        match holiday.span {
+            None => out.push(0),
+            Some((first, last)) => {
+                out.push(1);
+                put_date(&mut out, first);
+                put_date(&mut out, last);
+            }
+        }
+        put_str(&mut out, &holiday.source);
+    }
+    out
+}
+
+/// The cached calendar, if `bytes` were written for `key` and every input
+/// file is unchanged.
+fn decode(bytes: &[u8], key: &str) -> Option<HolidayInfo> {
+    let mut r = Reader(bytes.strip_prefix(MAGIC)?);
+    if r.str()? != key {
+        return None;
+    }
+    for _ in 0..r.u64()? {
+        let path = PathBuf::from(r.str()?);
+        if stamp(&path)? != (r.u64()?, r.u64()?) {
+            return None;
+        }
+    }
+    let mut entries = vec![];
+    for _ in 0..r.u64()? {
+        let date = r.date()?;
+        let name = r.str()?;
+        let byte = r.byte()?;
+        let category = CATEGORIES.into_iter().find(|c| *c as u8 == byte)?;
+        let time = match r.byte()? {
+            0 => None,
+            flag => Some(TimeSpan {
+                start: r.time()?,
+                end: if flag == 2 { Some(r.time()?) } else { None },
+            }),
+        };
+        let span = match r.byte()? {
+            0 => None,
+            _ => Some((r.date()?, r.date()?)),
+        };
+        let source = r.str()?;
+        entries.push((
+            date,
+            Holiday {
+                name,
+                category,
+                time,
+                span,
+                source,
+            },
+        ));
+    }
+    let mut holiday_info = HolidayInfo::new();
+    holiday_info.extend(entries);
+    Some(holiday_info)
+}
+
+fn put_u64(out: &mut Vec<u8>, n: u64) {
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    put_u64(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn put_date(out: &mut Vec<u8>, date: NaiveDate) {
+    out.extend_from_slice(&date.num_days_from_ce().to_le_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u64()?.try_into().ok()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn date(&mut self) -> Option<NaiveDate> {
+        let days = i32::from_le_bytes(self.take(4)?.try_into().ok()?);
+        NaiveDate::from_num_days_from_ce_opt(days)
+    }
+
+    fn time(&mut self) -> Option<NaiveTime> {
+        NaiveTime::from_num_seconds_from_midnight_opt(self.u64()?.try_into().ok()?, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        cache::{decode, encode, load_with},
+        holiday::{holidays_of, Category, DateFormat, HolidayInfo},
+        source::{DateSource, HolidayFile},
+        trace::Tracer,
+        Encoding,
+    };
+
+    #[test]
+    fn test_cache() {
+        let hi = holidays_of(&[
+            "2025/01/01,元日",
+            "2025/03/10 09:00-10:00,standup,company",
+            "2025/03/10 08:30,early,personal",
+            "2025/08/12..2025/08/14,夏休み,personal",
+        ]);
+        let bytes = encode("key", &[], &hi);
+        let decoded = decode(&bytes, "key").unwrap();
+        let all = |hi: &HolidayInfo| {
+            hi.iter_from(NaiveDate::MIN)
+                .map(|(date, h)| (date, h.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(all(&decoded), all(&hi));
+        assert!(decode(&bytes, "other").is_none());
+        assert!(decode(&bytes[..bytes.len() - 1], "key").is_none());
+
+        let dir = env::temp_dir().join(format!("calp-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("holidays.csv");
+        fs::write(&file, "2025/01/01,元日\n").unwrap();
+        let sources: Vec<Box<dyn DateSource>> = vec![Box::new(HolidayFile {
+            path: file.clone(),
+            encoding: Encoding::Utf8,
+            date_format: DateFormat::Ymd,
+            category: Category::National,
+        })];
+        let cache = dir.join("cache");
+        let tracer = Tracer::new(false);
+        let load = || all(&load_with(&cache, &sources, &tracer).unwrap());
+        let slots = || fs::read_dir(&cache).map_or(0, |d| d.count());
+        assert_eq!(load().len(), 1);
+        assert_eq!(slots(), 1);
+        assert_eq!(load().len(), 1);
+        // Same length, and likely the same modification time
+        fs::write(&file, "2025/01/02,元日\n").unwrap();
+        assert_eq!(load()[0].0, NaiveDate::from_ymd_opt(2025, 1, 2).unwrap());
+        fs::write(&file, "2025/01/01,元日\n2025/01/13,成人の日\n").unwrap();
+        assert_eq!(load().len(), 2);
+        assert_eq!(slots(), 1);
+
+        // Other sources get a slot of their own.
+        let other: Vec<Box<dyn DateSource>> = vec![Box::new(HolidayFile {
+            path: file.clone(),
+            encoding: Encoding::Utf8,
+            date_format: DateFormat::Ymd,
+            category: Category::Company,
+        })];
+        load_with(&cache, &other, &tracer).unwrap();
+        assert_eq!(slots(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
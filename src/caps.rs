@@ -0,0 +1,467 @@
+use std::{
+    borrow::Cow,
+    env,
+    fs::File,
+    io::{self, IsTerminal},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+use clap::{builder::PossibleValue, ValueEnum};
+use regex::{Captures, Regex};
+use unicode_width::UnicodeWidthChar;
+
+use crate::footnote::{is_marker, plain_letter};
+
+/// Colors the terminal can show.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ColorDepth {
+    None,
+    /// The 8 standard colors
+    Basic,
+    /// The 256-color palette, used by the themes and heatmaps
+    Ansi256,
+    /// 24-bit colors, for `#RRGGBB` custom colors
+    TrueColor,
+}
+
+/// `--color`: whether to color output at all.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColorChoice {
+    /// Only when stdout is a terminal or `CLICOLOR_FORCE` is set, and
+    /// `NO_COLOR` is not
+    Auto,
+    Always,
+    Never,
+}
+
+impl ValueEnum for ColorChoice {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Auto, Self::Always, Self::Never]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            ColorChoice::Auto => PossibleValue::new("auto"),
+            ColorChoice::Always => PossibleValue::new("always"),
+            ColorChoice::Never => PossibleValue::new("never"),
+        })
+    }
+}
+
+/// What the terminal supports. Renderers write full-featured output and
+/// [`Output`](crate::output::Output) runs each line through [`Caps::degrade`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Caps {
+    pub color: ColorDepth,
+    /// Box drawing, superscripts and other symbols; without it they become ASCII
+    pub unicode: bool,
+    /// OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    /// East Asian ambiguous-width characters take two columns, as in many
+    /// CJK terminal setups; those with an ASCII fallback then get it
+    pub ambiguous_wide: bool,
+    /// Terminal columns, when known
+    pub width: Option<usize>,
+}
+
+/// A `--caps KEY=VALUE` setting.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CapOverride {
+    Color(ColorDepth),
+    Unicode(bool),
+    Hyperlinks(bool),
+    AmbiguousWide(bool),
+    Width(usize),
+}
+
+/// Parses `color=none|8|256|truecolor`, `unicode=BOOL`, `hyperlinks=BOOL`,
+/// `ambiguous=narrow|wide` or `width=N`.
+pub fn parse_cap(s: &str) -> Result<CapOverride, String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE: \"{s}\""))?;
+    let flag = |v: &str| {
+        v.parse::<bool>()
+            .map_err(|_| format!("expected true or false: \"{v}\""))
+    };
+    match key.trim() {
+        "color" => match value.trim() {
+            "none" | "false" => Ok(CapOverride::Color(ColorDepth::None)),
+            "8" | "basic" => Ok(CapOverride::Color(ColorDepth::Basic)),
+            "256" | "true" => Ok(CapOverride::Color(ColorDepth::Ansi256)),
+            "truecolor" | "24bit" => Ok(CapOverride::Color(ColorDepth::TrueColor)),
+            v => Err(format!("expected none, 8, 256 or truecolor: \"{v}\"")),
+        },
+        "unicode" => flag(value.trim()).map(CapOverride::Unicode),
+        "hyperlinks" => flag(value.trim()).map(CapOverride::Hyperlinks),
+        "ambiguous" => match value.trim() {
+            "narrow" => Ok(CapOverride::AmbiguousWide(false)),
+            "wide" => Ok(CapOverride::AmbiguousWide(true)),
+            v => Err(format!("expected narrow or wide: \"{v}\"")),
+        },
+        "width" => value
+            .trim()
+            .parse()
+            .map(CapOverride::Width)
+            .map_err(|_| format!("invalid width: \"{value}\"")),
+        k => Err(format!(
+            "unknown capability: \"{k}\" (expected color, unicode, hyperlinks, ambiguous or width)"
+        )),
+    }
+}
+
+/// SGR color sequences and OSC 8 hyperlink brackets, which take no columns.
+pub fn escapes() -> &'static Regex {
+    static ESCAPES: OnceLock<Regex> = OnceLock::new();
+    ESCAPES.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*m|\x1b\]8;[^\x1b]*\x1b\\").unwrap())
+}
+
+/// Whether to color output under `choice`, given the environment `var`
+/// and whether stdout is a terminal. `NO_COLOR` wins over `CLICOLOR_FORCE`,
+/// and both over `TERM=dumb`; `--color always|never` over all of them.
+fn colored(choice: ColorChoice, var: impl Fn(&str) -> Option<String>, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Auto if var("NO_COLOR").is_some() => false,
+        ColorChoice::Auto if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") => true,
+        ColorChoice::Auto => terminal && var("TERM").is_none_or(|t| t != "dumb"),
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// Whether the console interprets escape codes, after asking it to.
+#[cfg(windows)]
+fn enable_vt_processing() -> bool {
+    nu_ansi_term::enable_ansi_support().is_ok()
+}
+
+#[cfg(not(windows))]
+fn enable_vt_processing() -> bool {
+    true
+}
+
+/// Columns of the terminal stdout is on, from `stty size`, which shells
+/// keep current unlike `COLUMNS`.
+fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(File::open("/dev/tty").ok()?)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let size = String::from_utf8_lossy(&output.stdout);
+    size.split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+        .filter(|w| *w > 0)
+}
+
+impl Caps {
+    /// Everything supported, width unknown.
+    pub const FULL: Caps = Caps {
+        color: ColorDepth::TrueColor,
+        unicode: true,
+        hyperlinks: true,
+        ambiguous_wide: false,
+        width: None,
+    };
+
+    /// Guesses from `choice`, whether stdout is a terminal, `NO_COLOR`,
+    /// `CLICOLOR_FORCE`, `TERM`, `COLORTERM`, the locale, `RUNEWIDTH_EASTASIAN`
+    /// and `COLUMNS` or the terminal's size, then applies `overrides`. On Windows it also turns on the
+    /// console's escape code processing.
+    pub fn detect(choice: ColorChoice, overrides: &[CapOverride]) -> Caps {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let term = var("TERM").unwrap_or_default();
+        // Consoles before Windows 10 print escape codes as text
+        let vt = enable_vt_processing();
+        let color = if !colored(choice, var, io::stdout().is_terminal())
+            || (!vt && choice == ColorChoice::Auto)
+        {
+            ColorDepth::None
+        } else if ["linux", "vt100", "ansi"].contains(&term.as_str()) {
+            ColorDepth::Basic
+        } else if var("COLORTERM").is_some_and(|c| c == "truecolor" || c == "24bit") {
+            ColorDepth::TrueColor
+        } else {
+            ColorDepth::Ansi256
+        };
+        let locale = var("LC_ALL")
+            .or_else(|| var("LC_CTYPE"))
+            .or_else(|| var("LANG"));
+        let unicode = locale.is_none_or(|l| {
+            let l = l.to_lowercase();
+            l.contains("utf-8") || l.contains("utf8")
+        });
+        let mut caps = Caps {
+            color,
+            unicode,
+            hyperlinks: vt && !["dumb", "linux"].contains(&term.as_str()),
+            // The variable go-runewidth and the tools built on it read
+            ambiguous_wide: var("RUNEWIDTH_EASTASIAN").is_some_and(|v| v == "1"),
+            width: var("COLUMNS")
+                .and_then(|c| c.parse().ok())
+                .or_else(terminal_width),
+        };
+        for o in overrides {
+            match *o {
+                CapOverride::Color(color) => caps.color = color,
+                CapOverride::Unicode(unicode) => caps.unicode = unicode,
+                CapOverride::Hyperlinks(hyperlinks) => caps.hyperlinks = hyperlinks,
+                CapOverride::AmbiguousWide(wide) => caps.ambiguous_wide = wide,
+                CapOverride::Width(width) => caps.width = Some(width),
+            }
+        }
+        caps
+    }
+
+    /// Whether [`Caps::degrade`] changes anything.
+    pub fn degrades(&self) -> bool {
+        Caps {
+            width: None,
+            ..*self
+        } != Caps::FULL
+    }
+
+    /// Rewrites one line of full-featured output for these capabilities.
+    pub fn degrade<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        static HYPERLINK: OnceLock<Regex> = OnceLock::new();
+        static SGR: OnceLock<Regex> = OnceLock::new();
+        if !self.hyperlinks {
+            let re = HYPERLINK.get_or_init(|| Regex::new(r"\x1b\]8;[^\x1b]*\x1b\\").unwrap());
+            line = Cow::Owned(re.replace_all(&line, "").into_owned());
+        }
+        let sgr = SGR.get_or_init(|| Regex::new(r"\x1b\[([0-9;]*)m").unwrap());
+        match self.color {
+            ColorDepth::None => line = Cow::Owned(sgr.replace_all(&line, "").into_owned()),
+            ColorDepth::Basic | ColorDepth::Ansi256 => {
+                line = Cow::Owned(
+                    sgr.replace_all(&line, |c: &Captures| {
+                        format!("\x1b[{}m", reduce_params(&c[1], self.color))
+                    })
+                    .into_owned(),
+                )
+            }
+            ColorDepth::TrueColor => (),
+        }
+        if !self.unicode && !line.is_ascii() {
+            line = Cow::Owned(line.chars().map(ascii_fallback).collect());
+        } else if self.ambiguous_wide && !line.is_ascii() {
+            // Footnote markers all become ASCII, so that they stay alike
+            // although only some superscripts are ambiguous.
+            let narrow = |c: char| match c.width_cjk() != c.width() || is_marker(c) {
+                true => ascii_fallback(c),
+                false => c,
+            };
+            line = Cow::Owned(line.chars().map(narrow).collect());
+        }
+        line
+    }
+}
+
+/// SGR parameters with 24-bit foregrounds/backgrounds (`38;2;R;G;B`,
+/// `48;2;R;G;B`) replaced by the nearest 256-color index, and for
+/// [`ColorDepth::Basic`] those (`38;5;N`, `48;5;N`) in turn by the nearest of
+/// the 8 standard colors.
+fn reduce_params(params: &str, color: ColorDepth) -> String {
+    let params = params.split(';').collect::<Vec<_>>();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < params.len() {
+        let base = params[i];
+        let index = match (base, params.get(i + 1)) {
+            ("38" | "48", Some(&"5")) if i + 2 < params.len() => {
+                i += 3;
+                params[i - 1].parse().unwrap_or(7)
+            }
+            ("38" | "48", Some(&"2")) if i + 4 < params.len() => {
+                let channel = |n: usize| params[i + n].parse().unwrap_or(0);
+                let index = cube_index(channel(2), channel(3), channel(4));
+                i += 5;
+                index
+            }
+            (p, _) => {
+                out.push(p.to_string());
+                i += 1;
+                continue;
+            }
+        };
+        if color == ColorDepth::Basic {
+            let offset = if base == "38" { 30 } else { 40 };
+            out.push((offset + basic_colour(index)).to_string());
+        } else {
+            out.push(format!("{base};5;{index}"));
+        }
+    }
+    out.join(";")
+}
+
+/// Nearest 256-color palette index of a 24-bit color, in the 6×6×6 cube.
+fn cube_index(r: u8, g: u8, b: u8) -> u8 {
+    // The cube's levels are 0, 95, 135, 175, 215 and 255.
+    let level = |c: u8| match c {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (c - 35) / 40,
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Nearest standard color (0-7) of a 256-color palette index.
+fn basic_colour(n: u8) -> u8 {
+    match n {
+        0..=15 => n % 8,
+        16..=231 => {
+            let n = n - 16;
+            let (r, g, b) = (n / 36, n / 6 % 6, n % 6);
+            // Keep the channels at least half as bright as the brightest one.
+            let max = r.max(g).max(b);
+            let on = |level: u8| max > 0 && level * 2 >= max;
+            u8::from(on(r)) | u8::from(on(g)) << 1 | u8::from(on(b)) << 2
+        }
+        // Grayscale ramp
+        _ if n < 244 => 0,
+        _ => 7,
+    }
+}
+
+/// One-column ASCII stand-in for the symbols the renderers draw.
+fn ascii_fallback(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '┄' | '┈' => '-',
+        '│' | '┃' | '║' => '|',
+        '┆' | '┊' => ':',
+        '\u{2500}'..='\u{257f}' => '+',
+        '¹' => '1',
+        '²' => '2',
+        '³' => '3',
+        '⁰' | '⁴'..='⁹' => char::from_digit(c as u32 - 0x2070, 10).unwrap_or('?'),
+        '⁺' => '+',
+        '…' => '~',
+        '→' => '>',
+        '−' => '-',
+        '█' | '■' => '#',
+        '□' => 'o',
+        '▲' => '^',
+        '·' => '.',
+        c => plain_letter(c).unwrap_or(c),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::caps::{colored, parse_cap, CapOverride, Caps, ColorChoice, ColorDepth};
+
+    #[test]
+    fn test_degrade() {
+        let line = "\x1b[31m 1\x1b[0m\x1b[38;5;214;48;5;22m 2\x1b[0m ┌─┐³⁺…";
+        let caps = |color, unicode| Caps {
+            color,
+            unicode,
+            ..Caps::FULL
+        };
+        assert_eq!(caps(ColorDepth::TrueColor, true).degrade(line), line);
+        assert_eq!(caps(ColorDepth::Ansi256, true).degrade(line), line);
+        assert_eq!(
+            caps(ColorDepth::Basic, true).degrade(line),
+            "\x1b[31m 1\x1b[0m\x1b[33;42m 2\x1b[0m ┌─┐³⁺…"
+        );
+        assert_eq!(caps(ColorDepth::None, false).degrade(line), " 1 2 +-+3+~");
+
+        let rgb = "\x1b[1;38;2;255;135;0m 3\x1b[0m";
+        assert_eq!(caps(ColorDepth::TrueColor, true).degrade(rgb), rgb);
+        assert_eq!(
+            caps(ColorDepth::Ansi256, true).degrade(rgb),
+            "\x1b[1;38;5;208m 3\x1b[0m"
+        );
+        assert_eq!(
+            caps(ColorDepth::Basic, true).degrade(rgb),
+            "\x1b[1;31m 3\x1b[0m"
+        );
+
+        let link = "\x1b]8;;https://example.com\x1b\\May\x1b]8;;\x1b\\";
+        let caps = Caps {
+            hyperlinks: false,
+            ..Caps::FULL
+        };
+        assert_eq!(caps.degrade(link), "May");
+
+        let caps = Caps {
+            ambiguous_wide: true,
+            ..Caps::FULL
+        };
+        assert_eq!(caps.degrade(" 3¹ 4⁵ 5ᵃ ─ 元日"), " 31 45 5a - 元日");
+        // The year heatmap's cells
+        assert_eq!(caps.degrade("·□■▲ 祝日"), ".o#^ 祝日");
+    }
+
+    #[test]
+    fn test_parse_cap() {
+        assert_eq!(parse_cap("unicode=false"), Ok(CapOverride::Unicode(false)));
+        assert_eq!(
+            parse_cap("color=8"),
+            Ok(CapOverride::Color(ColorDepth::Basic))
+        );
+        assert_eq!(parse_cap("width=70"), Ok(CapOverride::Width(70)));
+        assert_eq!(
+            parse_cap("ambiguous=wide"),
+            Ok(CapOverride::AmbiguousWide(true))
+        );
+        assert!(parse_cap("unicode").is_err());
+        assert!(parse_cap("sixel=true").is_err());
+    }
+
+    #[test]
+    fn test_detect_color() {
+        assert_eq!(
+            Caps::detect(ColorChoice::Never, &[]).color,
+            ColorDepth::None
+        );
+        assert_ne!(
+            Caps::detect(ColorChoice::Always, &[]).color,
+            ColorDepth::None
+        );
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(colored(ColorChoice::Auto, env(&[]), true));
+        assert!(!colored(ColorChoice::Auto, env(&[]), false));
+        assert!(colored(
+            ColorChoice::Auto,
+            env(&[("CLICOLOR_FORCE", "1")]),
+            false
+        ));
+        assert!(!colored(
+            ColorChoice::Auto,
+            env(&[("CLICOLOR_FORCE", "0")]),
+            false
+        ));
+        assert!(!colored(
+            ColorChoice::Auto,
+            env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]),
+            true
+        ));
+        assert!(!colored(ColorChoice::Auto, env(&[("TERM", "dumb")]), true));
+        assert!(colored(
+            ColorChoice::Always,
+            env(&[("NO_COLOR", "1")]),
+            false
+        ));
+        // --caps still picks the depth
+        let basic = [CapOverride::Color(ColorDepth::Basic)];
+        assert_eq!(
+            Caps::detect(ColorChoice::Never, &basic).color,
+            ColorDepth::Basic
+        );
+    }
+}
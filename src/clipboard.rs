@@ -0,0 +1,85 @@
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Stdio},
+};
+
+use crate::{caps::escapes, LibResult};
+
+/// Clipboard commands tried in order; the first one that can be spawned wins.
+const BACKENDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+/// Places `text` on the system clipboard.
+pub fn copy(text: &str) -> LibResult<()> {
+    copy_with(BACKENDS, text)
+}
+
+/// [`copy`] through the first of `backends` that takes all of `text`. One
+/// that exits without reading it, the pipe breaking, is skipped like one
+/// that is not installed.
+fn copy_with(backends: &[&[&str]], text: &str) -> LibResult<()> {
+    for backend in backends {
+        let Ok(mut child) = Command::new(backend[0])
+            .args(&backend[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(text.as_bytes()) {
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                    drop(stdin);
+                    child.wait()?;
+                    continue;
+                }
+                result => result?,
+            }
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err("no clipboard backend available (tried pbcopy, wl-copy, xclip, xsel, clip.exe)".into())
+}
+
+/// Removes SGR color sequences and OSC 8 hyperlinks, keeping the link text.
+pub fn strip_ansi(s: &str) -> String {
+    escapes().replace_all(s, "").into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::clipboard::{copy_with, strip_ansi};
+
+    #[test]
+    fn test_copy_broken_pipe() {
+        let file = env::temp_dir().join(format!("calp-clipboard-{}", std::process::id()));
+        let cat = format!("cat > '{}'", file.display());
+        // More than a pipe holds, so `true` exiting unread breaks the pipe
+        let text = "1月\n".repeat(1 << 16);
+        copy_with(&[&["true"], &["sh", "-c", &cat]], &text).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), text);
+        fs::remove_file(&file).unwrap();
+        assert!(copy_with(&[&["true"]], &text).is_err());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[31m 1\x1b[0m  2"), " 1  2");
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://example.com\x1b\\1月\x1b]8;;\x1b\\"),
+            "1月"
+        );
+        assert_eq!(strip_ansi("\x1b[1;4;48;5;22m3\x1b[0m"), "3");
+    }
+}
@@ -0,0 +1,287 @@
+use std::{fs, path::PathBuf};
+
+use clap::{
+    builder::PossibleValue, ArgAction, ArgMatches, CommandFactory, FromArgMatches, Subcommand,
+    ValueEnum,
+};
+use toml::{Table, Value};
+
+use crate::{
+    paths::{config_file, home_dir},
+    Config, LibResult,
+};
+
+/// Options that are never read from, or written to, a config file.
+const EXCLUDED: [&str; 4] = ["config", "profile", "help", "version"];
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective configuration, with every default filled in
+    Export {
+        /// Output format
+        #[arg(long, value_parser=clap::value_parser!(ExportFormat), default_value="toml")]
+        format: ExportFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExportFormat {
+    Toml,
+    Json,
+}
+
+impl ValueEnum for ExportFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Toml, Self::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            ExportFormat::Toml => PossibleValue::new("toml"),
+            ExportFormat::Json => PossibleValue::new("json"),
+        })
+    }
+}
+
+/// `path` with a leading `~` replaced by `$HOME`, as the shell does on the
+/// command line but not in config files.
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{rest}", home.display())
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Parses a file path, expanding `~`.
+pub fn parse_path(s: &str) -> Result<String, String> {
+    Ok(expand_home(s))
+}
+
+/// Parses the command line on top of defaults from the config file.
+///
+/// Top-level keys apply to every invocation; `[profiles.NAME]` tables are
+/// layered over them when selected with `--profile NAME` or `CALP_PROFILE`.
+/// Keys are long option names, e.g. `first-weekday = "mon"`. Files ending in
+/// `.json` are read as JSON with the same keys.
+pub fn parse_config() -> LibResult<Config> {
+    let pre = Config::command().ignore_errors(true).get_matches();
+    let path = match pre.get_one::<String>("config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => config_file(),
+    };
+    let profile = pre.get_one::<String>("profile");
+    let defaults = match &path {
+        Some(path) => {
+            let s = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let table: Table = if path.extension().is_some_and(|e| e == "json") {
+                serde_json::from_str(&s).map_err(|e| format!("{}: {e}", path.display()))?
+            } else {
+                s.parse().map_err(|e| format!("{}: {e}", path.display()))?
+            };
+            resolve_profile(table, profile.map(String::as_str))
+                .map_err(|e| format!("{}: {e}", path.display()))?
+        }
+        None if profile.is_some() => return Err("--profile requires a config file".into()),
+        None => Table::new(),
+    };
+
+    let cmd = apply_defaults(defaults).map_err(|e| match &path {
+        Some(path) => format!("{}: {e}", path.display()),
+        None => e,
+    })?;
+    let matches = cmd.clone().get_matches();
+    let mut config = Config::from_arg_matches(&matches)?;
+    config.effective = effective_config(&cmd, &matches);
+    Ok(config)
+}
+
+/// Every option value in effect, keyed by long name, in the form the config
+/// file accepts. Options without a value or default are left out.
+fn effective_config(cmd: &clap::Command, matches: &ArgMatches) -> Table {
+    cmd.get_arguments()
+        .filter(|a| !a.is_positional() && !EXCLUDED.contains(&a.get_id().as_str()))
+        .filter_map(|a| {
+            let raw = matches.get_raw(a.get_id().as_str())?;
+            let mut values = raw.map(|v| v.to_string_lossy().into_owned());
+            let key = a.get_long().unwrap_or(a.get_id().as_str()).to_string();
+            let value = match a.get_action() {
+                ArgAction::SetTrue | ArgAction::SetFalse => {
+                    Value::Boolean(values.next()? == "true")
+                }
+                ArgAction::Append => Value::Array(values.map(Value::String).collect()),
+                _ if a.get_value_delimiter().is_some() => {
+                    Value::Array(values.map(Value::String).collect())
+                }
+                _ => Value::String(values.next()?),
+            };
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Serializes an [`effective_config`] table.
+pub fn export_config(table: &Table, format: ExportFormat) -> LibResult<String> {
+    Ok(match format {
+        ExportFormat::Toml => toml::to_string(table)?,
+        ExportFormat::Json => serde_json::to_string_pretty(table)? + "\n",
+    })
+}
+
+/// Merges the selected profile over the top-level keys. Every profile's
+/// keys are checked, so a typo shows before the profile is first used.
+fn resolve_profile(mut table: Table, profile: Option<&str>) -> Result<Table, String> {
+    let profiles = match table.remove("profiles") {
+        Some(Value::Table(t)) => t,
+        Some(_) => return Err("\"profiles\" must be a table".to_string()),
+        None => Table::new(),
+    };
+    let cmd = Config::command();
+    for (name, keys) in profiles
+        .iter()
+        .filter_map(|(n, p)| Some((n, p.as_table()?)))
+    {
+        if let Some(key) = keys.keys().find(|key| config_arg(&cmd, key).is_none()) {
+            return Err(format!("unknown config key: \"profiles.{name}.{key}\""));
+        }
+    }
+    if let Some(name) = profile {
+        match profiles.get(name) {
+            Some(Value::Table(t)) => table.extend(t.clone()),
+            Some(_) => return Err(format!("profile \"{name}\" must be a table")),
+            None => return Err(format!("unknown profile: \"{name}\"")),
+        }
+    }
+    Ok(table)
+}
+
+/// Turns each config key into the default value of the matching option.
+fn apply_defaults(defaults: Table) -> Result<clap::Command, String> {
+    let mut cmd = Config::command();
+    for (key, value) in defaults {
+        let id = config_arg(&cmd, &key).ok_or_else(|| format!("unknown config key: \"{key}\""))?;
+        let values = match value {
+            Value::Array(values) => values.iter().map(scalar).collect::<Result<Vec<_>, _>>(),
+            v => scalar(&v).map(|s| vec![s]),
+        }
+        .map_err(|e| format!("{key}: {e}"))?;
+        cmd = cmd.mut_arg(id, |a| a.default_values(values));
+    }
+    Ok(cmd)
+}
+
+/// The option set by config key `key`.
+fn config_arg(cmd: &clap::Command, key: &str) -> Option<clap::Id> {
+    cmd.get_arguments()
+        .find(|a| {
+            !a.is_positional()
+                && (a.get_long() == Some(key) || a.get_id() == &key.replace('-', "_"))
+        })
+        .filter(|a| !EXCLUDED.contains(&a.get_id().as_str()))
+        .map(|a| a.get_id().clone())
+}
+
+fn scalar(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(n) => Ok(n.to_string()),
+        Value::Float(n) => Ok(n.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        v => Err(format!("unsupported value: {v}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use toml::Table;
+
+    use crate::config::{apply_defaults, effective_config, expand_home, resolve_profile};
+
+    #[test]
+    fn test_profiles() {
+        let table: Table = r#"
+            lang = "en"
+            heatmap = true
+            [profiles.work]
+            first-weekday = "mon"
+            show-categories = ["national", "company"]
+        "#
+        .parse()
+        .unwrap();
+
+        let work = resolve_profile(table.clone(), Some("work")).unwrap();
+        let m = apply_defaults(work)
+            .unwrap()
+            .try_get_matches_from(["calp"])
+            .unwrap();
+        assert_eq!(m.get_one::<bool>("heatmap"), Some(&true));
+        assert_eq!(
+            m.get_one::<chrono::Weekday>("first_weekday"),
+            Some(&chrono::Weekday::Mon)
+        );
+        assert_eq!(
+            m.get_many::<crate::Category>("show_categories")
+                .unwrap()
+                .count(),
+            2
+        );
+
+        // The command line still wins over the profile.
+        let work = resolve_profile(table.clone(), Some("work")).unwrap();
+        let m = apply_defaults(work)
+            .unwrap()
+            .try_get_matches_from(["calp", "--first-weekday", "sun"])
+            .unwrap();
+        assert_eq!(
+            m.get_one::<chrono::Weekday>("first_weekday"),
+            Some(&chrono::Weekday::Sun)
+        );
+
+        let base = resolve_profile(table.clone(), None).unwrap();
+        assert!(!base.contains_key("first-weekday"));
+        assert!(resolve_profile(table, Some("home")).is_err());
+
+        let unknown: Table = "colour = true".parse().unwrap();
+        assert!(apply_defaults(unknown).is_err());
+        // A typo in a profile that is not selected
+        let typo: Table = "[profiles.home]\nfirst-weekdy = \"sun\"".parse().unwrap();
+        assert_eq!(
+            resolve_profile(typo, None),
+            Err("unknown config key: \"profiles.home.first-weekdy\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_home() {
+        if let Ok(home) = std::env::var("HOME") {
+            assert_eq!(expand_home("~/work.csv"), format!("{home}/work.csv"));
+            assert_eq!(expand_home("~"), home);
+        }
+        assert_eq!(expand_home("~user/work.csv"), "~user/work.csv");
+        assert_eq!(expand_home("work~.csv"), "work~.csv");
+    }
+
+    #[test]
+    fn test_effective_config_round_trip() {
+        let cmd = apply_defaults(Table::new()).unwrap();
+        let m = cmd
+            .clone()
+            .try_get_matches_from(["calp", "--heatmap", "-m", "1-3", "--lang", "en"])
+            .unwrap();
+        let table = effective_config(&cmd, &m);
+        assert_eq!(table["heatmap"].as_bool(), Some(true));
+        assert_eq!(table["lang"].as_str(), Some("en"));
+        assert!(!table.contains_key("first-weekday"));
+        assert!(table["work-week"].is_array());
+        assert!(!table.contains_key("config"));
+
+        let reloaded = apply_defaults(table.clone()).unwrap();
+        let m = reloaded.clone().try_get_matches_from(["calp"]).unwrap();
+        assert_eq!(effective_config(&reloaded, &m), table);
+    }
+}
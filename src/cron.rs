@@ -0,0 +1,147 @@
+use chrono::{Datelike, NaiveDate};
+
+/// The day-level part of a crontab schedule (minute and hour are validated but not used).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    months: Vec<bool>,
+    days: Vec<bool>,
+    weekdays: Vec<bool>,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Whether the schedule fires at least once on `date`.
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+        let day = self.days[date.day() as usize];
+        let weekday = self.weekdays[date.weekday().num_days_from_sunday() as usize];
+        // cron(5): when both day fields are restricted (neither starts with `*`),
+        // either one matching is enough.
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Parses a crontab expression such as `0 3 * * 1,15` or `@weekly`.
+/// Anything after the five time fields (the command) is ignored.
+pub fn parse_cron(s: &str) -> Result<CronSchedule, String> {
+    let expanded = match s.trim() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        s => s,
+    };
+    let fields = expanded.split_whitespace().collect::<Vec<_>>();
+    let [minute, hour, day, month, weekday, ..] = fields[..] else {
+        return Err(format!("expected 5 cron fields: \"{s}\""));
+    };
+    parse_field(minute, 0, 59, &[])?;
+    parse_field(hour, 0, 23, &[])?;
+    let days = parse_field(day, 1, 31, &[])?;
+    let months = parse_field(month, 1, 12, &MONTH_NAMES)?;
+    let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES)?;
+    // Both 0 and 7 are Sunday.
+    weekdays[0] |= weekdays[7];
+    weekdays.truncate(7);
+    Ok(CronSchedule {
+        months,
+        days,
+        weekdays,
+        days_restricted: !day.starts_with('*'),
+        weekdays_restricted: !weekday.starts_with('*'),
+    })
+}
+
+/// Parses one field into a table indexed by value (`0..=max`).
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<Vec<bool>, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let n = match names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            Some(i) => i as u32 + min,
+            None => s
+                .parse()
+                .map_err(|_| format!("invalid cron value: \"{s}\""))?,
+        };
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(format!("cron value out of range ({min}-{max}): \"{s}\""))
+        }
+    };
+
+    let mut table = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid cron step: \"{part}\""))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            r => match r.split_once('-') {
+                Some((s, e)) => (value(s)?, value(e)?),
+                // `n/step` runs from n to the end of the range.
+                None if step > 1 => (value(r)?, max),
+                None => (value(r)?, value(r)?),
+            },
+        };
+        if start > end {
+            return Err(format!("invalid cron range: \"{part}\""));
+        }
+        for n in (start..=end).step_by(step) {
+            table[n as usize] = true;
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::cron::parse_cron;
+
+    #[test]
+    fn test_parse_cron() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // 1st and 15th, or any Monday
+        let cron = parse_cron("0 3 1,15 * mon /usr/bin/backup").unwrap();
+        assert!(cron.matches(ymd(2024, 1, 1)));
+        assert!(cron.matches(ymd(2024, 1, 8)));
+        assert!(cron.matches(ymd(2024, 1, 15)));
+        assert!(!cron.matches(ymd(2024, 1, 9)));
+
+        // every other day in the first quarter
+        let cron = parse_cron("*/5 * */2 jan-mar *").unwrap();
+        assert!(cron.matches(ymd(2024, 3, 3)));
+        assert!(!cron.matches(ymd(2024, 3, 4)));
+        assert!(!cron.matches(ymd(2024, 4, 3)));
+
+        // Sunday as 7
+        let cron = parse_cron("0 0 * * 7").unwrap();
+        assert!(cron.matches(ymd(2024, 1, 7)));
+        assert_eq!(cron, parse_cron("@weekly").unwrap());
+
+        assert!(parse_cron("0 3 * *").is_err());
+        assert!(parse_cron("0 3 32 * *").is_err());
+        assert!(parse_cron("0 3 * * */0").is_err());
+    }
+}
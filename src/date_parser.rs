@@ -0,0 +1,101 @@
+use std::ops::RangeInclusive;
+
+use chrono::{format::StrftimeItems, NaiveDate, Weekday};
+
+use crate::{MAX_DATE, MAX_YEAR, MIN_DATE, MIN_YEAR};
+
+/// Parses `YYYY-MM-DD` or `YYYY/MM/DD`, within [`MIN_DATE`]-[`MAX_DATE`].
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y/%m/%d"))
+        .map_err(|_| format!("invalid date: \"{s}\""))?;
+    if !(MIN_DATE..=MAX_DATE).contains(&date) {
+        return Err(format!(
+            "invalid date: \"{s}\" (expected years {MIN_YEAR}-{MAX_YEAR})"
+        ));
+    }
+    Ok(date)
+}
+
+/// Parses a month as `YYYY-MM` or `YYYY/MM`, returning `(year, month)`.
+pub fn parse_year_month(s: &str) -> Result<(i32, u32), String> {
+    let err = || format!("invalid month: \"{s}\" (expected YYYY-MM)");
+    let (year, month) = s.split_once(['-', '/']).ok_or_else(err)?;
+    let year = year
+        .parse()
+        .ok()
+        .filter(|y| (MIN_YEAR..=MAX_YEAR).contains(y));
+    let month = month.parse().ok().filter(|m| (1..=12).contains(m));
+    year.zip(month).ok_or_else(err)
+}
+
+/// Parses a year or an inclusive `START-END` range of years, within
+/// [`MIN_YEAR`]-[`MAX_YEAR`].
+pub fn parse_years(s: &str) -> Result<RangeInclusive<i32>, String> {
+    let year = |y: &str| {
+        y.trim()
+            .parse()
+            .ok()
+            .filter(|y| (MIN_YEAR..=MAX_YEAR).contains(y))
+            .ok_or_else(|| format!("invalid year: \"{y}\" (expected {MIN_YEAR}-{MAX_YEAR})"))
+    };
+    let (start, end) = match s.split_once('-') {
+        Some((start, end)) => (year(start)?, year(end)?),
+        None => (year(s)?, year(s)?),
+    };
+    if end < start {
+        return Err(format!("range ends before it starts: \"{s}\""));
+    }
+    Ok(start..=end)
+}
+
+/// Parses a weekday name such as `mon` or `Monday`.
+pub fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    s.parse().map_err(|_| format!("invalid weekday: \"{s}\""))
+}
+
+/// Validates a strftime format string.
+pub fn parse_strftime(s: &str) -> Result<String, String> {
+    StrftimeItems::new(s)
+        .parse()
+        .map(|_| s.to_string())
+        .map_err(|_| format!("invalid date format: \"{s}\""))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Weekday;
+
+    use crate::date_parser::{parse_strftime, parse_weekday, parse_year_month, parse_years};
+
+    #[test]
+    fn test_parse_years() {
+        assert_eq!(parse_years("2025"), Ok(2025..=2025));
+        assert_eq!(parse_years("2024-2026"), Ok(2024..=2026));
+        assert!(parse_years("2026-2024").is_err());
+        assert!(parse_years("2024-").is_err());
+        assert!(parse_years("0-2024").is_err());
+    }
+
+    #[test]
+    fn test_parse_year_month() {
+        assert_eq!(parse_year_month("2024-11"), Ok((2024, 11)));
+        assert_eq!(parse_year_month("2025/3"), Ok((2025, 3)));
+        assert!(parse_year_month("2025-13").is_err());
+        assert!(parse_year_month("2025").is_err());
+        assert!(parse_year_month("10000-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_weekday() {
+        assert_eq!(parse_weekday("fri"), Ok(Weekday::Fri));
+        assert_eq!(parse_weekday("Monday"), Ok(Weekday::Mon));
+        assert!(parse_weekday("fr1").is_err());
+    }
+
+    #[test]
+    fn test_parse_strftime() {
+        assert_eq!(parse_strftime("%Y-%m-%d"), Ok("%Y-%m-%d".to_string()));
+        assert!(parse_strftime("%Y-%Q").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+
+use chrono::NaiveDate;
+
+use crate::{
+    business::count_business_days, date_parser::parse_date, holiday::HolidayInfo, Lang, LibResult,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deadline {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Reads a deadlines file of `date,name` lines, sorted by date. Lines starting with `#` are comments.
+pub fn load_deadlines(path: &str) -> LibResult<Vec<Deadline>> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let mut deadlines = s
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|(n, line)| {
+            let (date, name) = line.split_once(',').unwrap_or((line, ""));
+            let date = parse_date(date.trim()).map_err(|e| format!("{path}:{}: {e}", n + 1))?;
+            Ok(Deadline {
+                date,
+                name: name.trim().to_string(),
+            })
+        })
+        .collect::<LibResult<Vec<_>>>()?;
+    deadlines.sort_by_key(|d| d.date);
+    Ok(deadlines)
+}
+
+/// Formats the countdown table, e.g. `リリース: 2025-09-30, あと47日 / 営業日32日`.
+pub fn format_countdown(
+    deadlines: &[Deadline],
+    holiday_info: &HolidayInfo,
+    today: NaiveDate,
+    lang: Lang,
+) -> Vec<String> {
+    deadlines
+        .iter()
+        .map(|d| {
+            let days = (d.date - today).num_days();
+            let business = d
+                .date
+                .pred_opt()
+                .map_or(0.0, |last| count_business_days(holiday_info, today..=last));
+            let date = d.date.format("%Y-%m-%d");
+            match (lang, days) {
+                (Lang::Japanese, 0) => format!("{}: {date}, 今日", d.name),
+                (Lang::Japanese, ..0) => format!("{}: {date}, {}日超過", d.name, -days),
+                (Lang::Japanese, _) => {
+                    format!("{}: {date}, あと{days}日 / 営業日{business}日", d.name)
+                }
+                (Lang::English, 0) => format!("{}: {date}, today", d.name),
+                (Lang::English, ..0) => format!("{}: {date}, {} days overdue", d.name, -days),
+                (Lang::English, _) => format!(
+                    "{}: {date}, {days} days left / {business} business days",
+                    d.name
+                ),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        deadline::{format_countdown, load_deadlines},
+        holiday::holidays_of,
+        Lang,
+    };
+
+    #[test]
+    fn test_deadlines() {
+        let dir = env::temp_dir().join(format!("calp-deadline-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("deadlines.csv");
+        fs::write(
+            &file,
+            "# due\n2025-09-30,リリース\n\n2025/09/01,締切\n2025-09-05, 申請 \n",
+        )
+        .unwrap();
+        let deadlines = load_deadlines(file.to_str().unwrap()).unwrap();
+        let names = deadlines
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["締切", "申請", "リリース"]);
+
+        let hi = holidays_of(&["2025/09/15,敬老の日"]);
+        let today = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        assert_eq!(
+            format_countdown(&deadlines, &hi, today, Lang::Japanese),
+            [
+                "締切: 2025-09-01, 今日",
+                "申請: 2025-09-05, あと4日 / 営業日4日",
+                "リリース: 2025-09-30, あと29日 / 営業日20日",
+            ]
+        );
+        let today = NaiveDate::from_ymd_opt(2025, 9, 3).unwrap();
+        assert_eq!(
+            format_countdown(&deadlines[..1], &hi, today, Lang::English),
+            ["締切: 2025-09-01, 2 days overdue"]
+        );
+
+        fs::write(&file, "2025-09-30,リリース\nsoon,締切\n").unwrap();
+        let err = load_deadlines(file.to_str().unwrap()).unwrap_err();
+        assert!(
+            err.to_string().ends_with(":2: invalid date: \"soon\""),
+            "{err}"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
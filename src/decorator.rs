@@ -0,0 +1,82 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use chrono::NaiveDate;
+
+use crate::{date_parser::parse_date, LibResult};
+
+/// Runs `command` through the shell once with every displayed date on stdin,
+/// one `YYYY-MM-DD` per line.
+///
+/// Each output line is `date[<TAB>annotation]`; every listed date is marked in
+/// the grid and non-empty annotations are listed below it.
+pub fn run_decorator(command: &str, dates: &[NaiveDate]) -> LibResult<BTreeMap<NaiveDate, String>> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{command}: {e}"))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let input = dates
+        .iter()
+        .map(|d| format!("{}\n", d.format("%Y-%m-%d")))
+        .collect::<String>();
+    // Write from a separate thread so a command that answers while still
+    // reading cannot deadlock on a full pipe.
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    // The command may legitimately exit without reading all of stdin.
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(format!("{command}: exited with {}", output.status).into());
+    }
+    parse_output(&String::from_utf8_lossy(&output.stdout))
+        .map_err(|e| format!("{command}: {e}").into())
+}
+
+/// `command` run by the platform's shell.
+#[cfg(windows)]
+pub fn shell(command: &str) -> Command {
+    let mut c = Command::new("cmd");
+    c.args(["/C", command]);
+    c
+}
+
+#[cfg(not(windows))]
+pub fn shell(command: &str) -> Command {
+    let mut c = Command::new("sh");
+    c.args(["-c", command]);
+    c
+}
+
+fn parse_output(s: &str) -> Result<BTreeMap<NaiveDate, String>, String> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            let (date, annotation) = line.split_once('\t').unwrap_or((line, ""));
+            let date = parse_date(date.trim()).map_err(|e| format!("line {}: {e}", n + 1))?;
+            Ok((date, annotation.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::decorator::parse_output;
+
+    #[test]
+    fn test_parse_output() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let m = parse_output("2024-01-05\t3 commits\n2024-01-09\n\n").unwrap();
+        assert_eq!(m.len(), 2);
+        assert_eq!(m[&date(5)], "3 commits");
+        assert_eq!(m[&date(9)], "");
+        assert!(parse_output("soon\tx").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use clap::ValueEnum;
+use unicode_width::UnicodeWidthStr;
+
+use crate::holiday::Category;
+
+/// Columns a wide cell reserves for its marker.
+pub const MARKER_WIDTH: usize = 2;
+
+/// Parses a `CATEGORY=EMOJI` mapping e.g. `national=🎌`.
+pub fn parse_emoji(s: &str) -> Result<(Category, String), String> {
+    let (category, emoji) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected CATEGORY=EMOJI: \"{s}\""))?;
+    let category = Category::from_str(category.trim(), true)
+        .map_err(|_| format!("unknown category: \"{category}\""))?;
+    let emoji = emoji.trim().to_string();
+    if marker_width(&emoji) > MARKER_WIDTH {
+        return Err(format!(
+            "marker wider than {MARKER_WIDTH} columns: \"{emoji}\""
+        ));
+    }
+    Ok((category, emoji))
+}
+
+/// Display width of a marker. Terminals draw a character followed by the
+/// emoji presentation selector (U+FE0F), e.g. ✈️, two columns wide even though
+/// its base character is narrow.
+pub fn marker_width(s: &str) -> usize {
+    if s.contains('\u{fe0f}') {
+        s.width().max(2)
+    } else {
+        s.width()
+    }
+}
+
+/// `marker` padded with spaces to [`MARKER_WIDTH`] columns.
+pub fn pad_marker(marker: &str) -> String {
+    let pad = MARKER_WIDTH.saturating_sub(marker_width(marker));
+    format!("{marker}{}", " ".repeat(pad))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        emoji::{marker_width, pad_marker, parse_emoji},
+        holiday::Category,
+    };
+
+    #[test]
+    fn test_emoji() {
+        assert_eq!(
+            parse_emoji("national=🎌"),
+            Ok((Category::National, "🎌".to_string()))
+        );
+        assert_eq!(
+            parse_emoji("Personal = ✈\u{fe0f}"),
+            Ok((Category::Personal, "✈\u{fe0f}".to_string()))
+        );
+        assert!(parse_emoji("🎌").is_err());
+        assert!(parse_emoji("trips=✈").is_err());
+        assert!(parse_emoji("national=🎌🎌").is_err());
+
+        assert_eq!(marker_width("✈\u{fe0f}"), 2);
+        assert_eq!(marker_width("祝"), 2);
+        assert_eq!(pad_marker("*"), "* ");
+        assert_eq!(pad_marker(""), "  ");
+    }
+}
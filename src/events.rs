@@ -0,0 +1,142 @@
+use std::{fs, path::PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::{
+    holiday::{parse_line, read_holiday_file, Category, DateFormat, Holiday},
+    Encoding, LibResult,
+};
+
+/// A UTF-8 holiday file of personal events, edited in place by `calp pick`.
+/// New events are appended as `date,name,personal` lines; other lines,
+/// comments and `#include`s are kept as they are.
+pub struct EventsFile {
+    pub path: PathBuf,
+}
+
+impl EventsFile {
+    /// The file's entries; none if it does not exist yet.
+    pub fn load(&self) -> LibResult<Vec<(NaiveDate, Holiday)>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        read_holiday_file(
+            &self.path,
+            Encoding::Utf8,
+            DateFormat::Ymd,
+            Category::National,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+    }
+
+    pub fn add(&self, date: NaiveDate, name: &str) -> LibResult<()> {
+        let name = check_name(name)?;
+        let mut s = fs::read_to_string(&self.path).unwrap_or_default();
+        if !s.is_empty() && !s.ends_with('\n') {
+            s.push('\n');
+        }
+        s.push_str(&format!("{},{name},personal\n", date.format("%Y/%m/%d")));
+        self.write(&s)
+    }
+
+    /// Renames the first single-day event called `name` on `date`.
+    pub fn rename(&self, date: NaiveDate, name: &str, new_name: &str) -> LibResult<()> {
+        let new_name = check_name(new_name)?;
+        self.rewrite(date, name, |line| {
+            let mut cols = line.split(',').collect::<Vec<_>>();
+            cols[1] = new_name;
+            Some(cols.join(","))
+        })
+    }
+
+    /// Deletes the line of the first single-day event called `name` on `date`.
+    pub fn remove(&self, date: NaiveDate, name: &str) -> LibResult<()> {
+        self.rewrite(date, name, |_| None)
+    }
+
+    fn rewrite(
+        &self,
+        date: NaiveDate,
+        name: &str,
+        f: impl FnOnce(&str) -> Option<String>,
+    ) -> LibResult<()> {
+        let s =
+            fs::read_to_string(&self.path).map_err(|e| format!("{}: {e}", self.path.display()))?;
+        let mut lines = s.lines().map(str::to_string).collect::<Vec<_>>();
+        let index = lines
+            .iter()
+            .position(|line| {
+                matches!(parse_line(line), Ok(Some((d, h)))
+                    if d == date && h.name == name && h.span.is_none())
+            })
+            .ok_or_else(|| {
+                format!(
+                    "{}: no event \"{name}\" on {}",
+                    self.path.display(),
+                    date.format("%Y/%m/%d")
+                )
+            })?;
+        match f(&lines[index]) {
+            Some(line) => lines[index] = line,
+            None => {
+                lines.remove(index);
+            }
+        }
+        let mut s = lines.join("\n");
+        if !lines.is_empty() {
+            s.push('\n');
+        }
+        self.write(&s)
+    }
+
+    fn write(&self, s: &str) -> LibResult<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, s).map_err(|e| format!("{}: {e}", self.path.display()).into())
+    }
+}
+
+/// An event name that fits in one CSV column.
+fn check_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err("event name is empty".to_string())
+    } else if name.contains([',', '\n', '\r']) {
+        Err(format!("event names cannot contain commas: \"{name}\""))
+    } else {
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use chrono::NaiveDate;
+
+    use crate::events::EventsFile;
+
+    #[test]
+    fn test_events_file() {
+        let path = env::temp_dir().join(format!("calp-events-{}.csv", std::process::id()));
+        fs::write(&path, "# events\n2025/03/01 10:00,歯医者,personal").unwrap();
+        let events = EventsFile { path: path.clone() };
+        let date = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+
+        events.add(date(2), "締切").unwrap();
+        events.rename(date(1), "歯医者", "眼科").unwrap();
+        assert!(events.add(date(3), "a,b").is_err());
+        assert!(events.remove(date(3), "締切").is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# events\n2025/03/01 10:00,眼科,personal\n2025/03/02,締切,personal\n"
+        );
+        events.remove(date(2), "締切").unwrap();
+        let loaded = events.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].1.name, "眼科");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use clap::{builder::PossibleValue, Args, ValueEnum};
+
+use crate::{business::is_business_day, date_parser::parse_weekday, holiday::HolidayInfo};
+
+#[derive(Debug, Default, Args)]
+pub struct DayFilter {
+    /// Only list the given weekdays e.g. mon,wed,fri
+    #[arg(long, value_name = "WEEKDAYS", value_delimiter = ',', value_parser = parse_weekday, global = true)]
+    only_weekdays: Vec<Weekday>,
+
+    /// Only list days of the given kind
+    #[arg(long, value_name = "KIND", value_parser = clap::value_parser!(DayKind), global = true)]
+    only: Option<DayKind>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum DayKind {
+    Weekends,
+    Holidays,
+    Workdays,
+}
+
+impl ValueEnum for DayKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Weekends, Self::Holidays, Self::Workdays]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            DayKind::Weekends => PossibleValue::new("weekends"),
+            DayKind::Holidays => PossibleValue::new("holidays"),
+            DayKind::Workdays => PossibleValue::new("workdays"),
+        })
+    }
+}
+
+impl DayFilter {
+    pub fn matches(&self, holiday_info: &HolidayInfo, date: NaiveDate) -> bool {
+        if !self.only_weekdays.is_empty() && !self.only_weekdays.contains(&date.weekday()) {
+            return false;
+        }
+        match self.only {
+            Some(DayKind::Weekends) => matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Some(DayKind::Holidays) => holiday_info.is_day_off(date),
+            Some(DayKind::Workdays) => is_business_day(holiday_info, date),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Datelike, NaiveDate, Weekday};
+
+    use crate::{
+        filter::{DayFilter, DayKind},
+        holiday::holidays_of,
+    };
+
+    #[test]
+    fn test_matches() {
+        let hi = holidays_of(&["2025/01/13,成人の日"]);
+        let week = |filter: DayFilter| {
+            NaiveDate::from_ymd_opt(2025, 1, 11)
+                .unwrap()
+                .iter_days()
+                .take(7)
+                .filter(|d| filter.matches(&hi, *d))
+                .map(|d| d.day())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(week(DayFilter::default()), [11, 12, 13, 14, 15, 16, 17]);
+        let only = |kind| DayFilter {
+            only: Some(kind),
+            ..DayFilter::default()
+        };
+        assert_eq!(week(only(DayKind::Weekends)), [11, 12]);
+        assert_eq!(week(only(DayKind::Holidays)), [13]);
+        assert_eq!(week(only(DayKind::Workdays)), [14, 15, 16, 17]);
+        let mon_wed = DayFilter {
+            only_weekdays: vec![Weekday::Mon, Weekday::Wed],
+            only: Some(DayKind::Workdays),
+        };
+        assert_eq!(week(mon_wed), [15]);
+    }
+}
@@ -0,0 +1,120 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::names::NameFit;
+
+/// Numbered notes referenced from day cells. Identical texts share a number,
+/// so the same holiday gets the same marker wherever it appears.
+#[derive(Debug, Default)]
+pub struct Footnotes {
+    notes: Vec<String>,
+}
+
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// Cell markers for notes 10 and up; there is no superscript q.
+const SUPERSCRIPT_LETTERS: [char; 25] = [
+    'ᵃ', 'ᵇ', 'ᶜ', 'ᵈ', 'ᵉ', 'ᶠ', 'ᵍ', 'ʰ', 'ⁱ', 'ʲ', 'ᵏ', 'ˡ', 'ᵐ', 'ⁿ', 'ᵒ', 'ᵖ', 'ʳ', 'ˢ', 'ᵗ',
+    'ᵘ', 'ᵛ', 'ʷ', 'ˣ', 'ʸ', 'ᶻ',
+];
+
+impl Footnotes {
+    /// Registers `text` and returns its 1-based number.
+    pub fn add(&mut self, text: String) -> usize {
+        match self.notes.iter().position(|n| *n == text) {
+            Some(i) => i + 1,
+            None => {
+                self.notes.push(text);
+                self.notes.len()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// The note texts in number order.
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// The notes in number order, each prefixed with the marker its days
+    /// carry e.g. `¹ 元日`, or past those its number e.g. `⁺³⁵ 元日`.
+    pub fn lines(&self, name_fit: &NameFit) -> Vec<String> {
+        self.notes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| {
+                let marker = match cell_marker(i + 1) {
+                    '⁺' => format!("⁺{}", superscript(i + 1)),
+                    c => c.to_string(),
+                };
+                let note = name_fit.fit(note, marker.width() + 1);
+                format!("{marker} {note}")
+            })
+            .collect()
+    }
+}
+
+pub fn superscript(n: usize) -> String {
+    n.to_string()
+        .chars()
+        .map(|c| SUPERSCRIPT_DIGITS[c.to_digit(10).unwrap() as usize])
+        .collect()
+}
+
+/// Single-column marker for the gap after a day number: the superscript
+/// digit, a superscript letter from 10 on (`ᵃ` is 10), or `⁺` once those
+/// run out.
+pub fn cell_marker(n: usize) -> char {
+    match n {
+        0..10 => SUPERSCRIPT_DIGITS[n],
+        _ => SUPERSCRIPT_LETTERS.get(n - 10).copied().unwrap_or('⁺'),
+    }
+}
+
+/// Whether `c` is one of the markers [`cell_marker`] returns.
+pub fn is_marker(c: char) -> bool {
+    SUPERSCRIPT_DIGITS.contains(&c) || SUPERSCRIPT_LETTERS.contains(&c) || c == '⁺'
+}
+
+/// The letter a superscript letter marker stands for, e.g. `a` for `ᵃ`.
+pub fn plain_letter(c: char) -> Option<char> {
+    let i = SUPERSCRIPT_LETTERS.iter().position(|l| *l == c)?;
+    ('a'..='z').filter(|l| *l != 'q').nth(i)
+}
+
+#[cfg(test)]
+mod test {
+    use unicode_width::UnicodeWidthChar;
+
+    use crate::{
+        footnote::{cell_marker, plain_letter, superscript, Footnotes},
+        names::NameFit,
+    };
+
+    #[test]
+    fn test_footnotes() {
+        let mut notes = Footnotes::default();
+        assert_eq!(notes.add("元日".to_string()), 1);
+        assert_eq!(notes.add("成人の日".to_string()), 2);
+        assert_eq!(notes.add("元日".to_string()), 1);
+        assert_eq!(notes.lines(&NameFit::default()), ["¹ 元日", "² 成人の日"]);
+        assert_eq!(superscript(12), "¹²");
+        assert_eq!(cell_marker(9), '⁹');
+        assert_eq!(cell_marker(10), 'ᵃ');
+        assert_eq!(cell_marker(12), 'ᶜ');
+        assert_eq!(cell_marker(34), 'ᶻ');
+        assert_eq!(cell_marker(35), '⁺');
+        assert!((1..=35).all(|n| cell_marker(n).width() == Some(1)));
+        assert_eq!(plain_letter('ᵖ'), Some('p'));
+        assert_eq!(plain_letter('ʳ'), Some('r'));
+
+        for i in 3..=35 {
+            notes.add(i.to_string());
+        }
+        let lines = notes.lines(&NameFit::default());
+        assert_eq!(lines[9], "ᵃ 10");
+        assert_eq!(lines[34], "⁺³⁵ 35");
+    }
+}
@@ -0,0 +1,60 @@
+use std::{collections::HashMap, process::Command};
+
+use chrono::NaiveDate;
+
+use crate::LibResult;
+
+/// Number of commits per day in `from..=to` in the repository at `path`, by
+/// committer date, which is also the date `--since` and `--until` compare.
+pub fn commit_counts(
+    path: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> LibResult<HashMap<NaiveDate, usize>> {
+    let output = Command::new("git")
+        .args(["-C", path, "log", "--all", "--date=short", "--format=%cd"])
+        .arg(format!("--since={} 00:00", from.format("%Y-%m-%d")))
+        .arg(format!("--until={} 23:59:59", to.format("%Y-%m-%d")))
+        .output()
+        .map_err(|e| format!("git: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("git: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(count_dates(
+        &String::from_utf8_lossy(&output.stdout),
+        from,
+        to,
+    ))
+}
+
+fn count_dates(log: &str, from: NaiveDate, to: NaiveDate) -> HashMap<NaiveDate, usize> {
+    let mut counts = HashMap::new();
+    for date in log
+        .lines()
+        .filter_map(|l| NaiveDate::parse_from_str(l.trim(), "%Y-%m-%d").ok())
+        .filter(|d| from <= *d && *d <= to)
+    {
+        *counts.entry(date).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::gitlog::count_dates;
+
+    #[test]
+    fn test_count_dates() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let counts = count_dates(
+            "2024-01-02\n2024-01-02\n2024-01-05\n2023-12-31\n",
+            date(1),
+            date(31),
+        );
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&date(2)], 2);
+        assert_eq!(counts[&date(5)], 1);
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    clipboard::strip_ansi, column_weekday, filter::DayFilter, format_month, holiday::HolidayInfo,
+    month_layout, render::Calendar, theme::Theme, weekday_names, FormatConfig, Lang,
+};
+
+/// Checks the invariants every layout of `year`/`month` keeps with weeks
+/// starting on `first_weekday`: February has 29 days exactly in leap years,
+/// each day appears once and in order under its own weekday, and the
+/// horizontal, vertical and wide grids and the structured [`Calendar`] show
+/// the same days, in lines of equal width.
+pub fn validate_grid(year: i32, month: u32, first_weekday: Weekday) -> Result<(), String> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| format!("{year}-{month:02}: no such month"))?;
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let fail = |what: String| Err(format!("{year}-{month:02} from {first_weekday}: {what}"));
+
+    let weeks = month_layout(year, month, first_weekday);
+    let placed = weeks
+        .iter()
+        .flatten()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    if placed != (1..=days).collect::<Vec<_>>() {
+        return fail(format!("layout has days {placed:?}"));
+    }
+    if !weeks[0].contains(&Some(1)) {
+        return fail("day 1 is not in the first week".to_string());
+    }
+    for week in &weeks {
+        for (i, day) in week.iter().enumerate() {
+            let Some(date) = day.and_then(|d| first.with_day(d)) else {
+                continue;
+            };
+            if column_weekday(first_weekday, i) != date.weekday() {
+                return fail(format!(
+                    "{date} is in the column for {}",
+                    column_weekday(first_weekday, i)
+                ));
+            }
+        }
+    }
+
+    let holiday_info = HolidayInfo::new();
+    let calendar = Calendar::new(
+        &holiday_info,
+        [(year, month, String::new())],
+        first_weekday,
+        weekday_names(Lang::English, first_weekday),
+        first,
+        &DayFilter::default(),
+    );
+    let calendar_days = calendar.months[0]
+        .weeks
+        .each_ref()
+        .map(|week| week.each_ref().map(|d| d.as_ref().map(|d| d.date.day())));
+    if calendar_days != weeks {
+        return fail("the structured calendar differs from the layout".to_string());
+    }
+
+    let plain = || FormatConfig::plain(Lang::English, first_weekday, Theme::Default.palette());
+    let modes = [
+        ("horizontal", plain()),
+        (
+            "vertical",
+            FormatConfig {
+                vertical: true,
+                ..plain()
+            },
+        ),
+        (
+            "wide",
+            FormatConfig {
+                wide: true,
+                ..plain()
+            },
+        ),
+    ];
+    for (mode, format_config) in modes {
+        let lines = format_month(year, month, &format_config, first, &holiday_info)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect::<Vec<_>>();
+        if lines.iter().any(|line| line.width() != lines[0].width()) {
+            return fail(format!("{mode} lines differ in width: {lines:#?}"));
+        }
+        // Below the header, and the weekday names of horizontal grids
+        let body = &lines[if format_config.vertical { 1 } else { 2 }..];
+        let mut shown = body
+            .iter()
+            .flat_map(|line| line.split_whitespace())
+            .filter_map(|word| word.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+        if !format_config.vertical && shown != placed {
+            return fail(format!("{mode} grid shows days {shown:?}"));
+        }
+        shown.sort_unstable();
+        if shown != placed {
+            return fail(format!("{mode} grid shows days {shown:?}"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Weekday;
+    use proptest::prelude::*;
+
+    use crate::grid::validate_grid;
+
+    #[test]
+    fn test_validate_grid_february() {
+        for year in [1, 1900, 2000, 2023, 2024, 2100, 9999] {
+            for first_weekday in [Weekday::Sun, Weekday::Mon, Weekday::Sat] {
+                assert_eq!(validate_grid(year, 2, first_weekday), Ok(()));
+            }
+        }
+        assert!(validate_grid(2024, 13, Weekday::Sun).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_validate_grid(year in 1..=9999i32, month in 1..=12u32, first in 0..7u8) {
+            let first_weekday = Weekday::try_from(first).unwrap();
+            prop_assert_eq!(validate_grid(year, month, first_weekday), Ok(()));
+        }
+    }
+}
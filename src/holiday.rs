@@ -0,0 +1,1158 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, Weekday};
+use clap::{builder::PossibleValue, ValueEnum};
+use encoding_rs::SHIFT_JIS;
+
+use crate::{
+    business::WorkWeek,
+    observance::Observance,
+    paths::holiday_file_candidates,
+    source::{load_sources, Computed, DateSource, HolidayFile, SubstituteHolidays},
+    trace::Tracer,
+    Encoding, Lang, LibResult,
+};
+
+/// Names of days off that follow from other holidays rather than being
+/// holidays themselves; the Cabinet Office CSV calls both kinds 休日.
+const DERIVED_NAMES: [&str; 5] = [
+    "休日",
+    "振替休日",
+    "国民の休日",
+    "Substitute Holiday",
+    "Citizens' Holiday",
+];
+
+/// In order of significance. The discriminants are stored in the holiday
+/// cache.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Category {
+    National = 0,
+    /// One-off additions from override files, e.g. an Imperial ceremony holiday
+    Override = 1,
+    Company = 2,
+    Personal = 3,
+}
+
+impl ValueEnum for Category {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::National,
+            Self::Company,
+            Self::Personal,
+            Self::Override,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Category::National => PossibleValue::new("national"),
+            Category::Company => PossibleValue::new("company"),
+            Category::Personal => PossibleValue::new("personal"),
+            Category::Override => PossibleValue::new("override"),
+        })
+    }
+}
+
+/// How the date column of holiday files is written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DateFormat {
+    /// `2025/01/01`
+    Ymd,
+    /// `2025/01/01`, or an Excel serial number such as `45658`
+    Excel,
+}
+
+impl ValueEnum for DateFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Ymd, Self::Excel]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            DateFormat::Ymd => PossibleValue::new("ymd"),
+            DateFormat::Excel => PossibleValue::new("excel"),
+        })
+    }
+}
+
+/// Time of day an event starts and, optionally, ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSpan {
+    pub start: NaiveTime,
+    pub end: Option<NaiveTime>,
+}
+
+impl TimeSpan {
+    /// Parses `HH:MM` or `HH:MM-HH:MM`.
+    pub fn parse(s: &str) -> Result<TimeSpan, String> {
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time: \"{t}\""))
+        };
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (time(start)?, time(end)?);
+                if end < start {
+                    return Err(format!("end before start: \"{s}\""));
+                }
+                Ok(TimeSpan {
+                    start,
+                    end: Some(end),
+                })
+            }
+            None => Ok(TimeSpan {
+                start: time(s)?,
+                end: None,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for TimeSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start.format("%H:%M"))?;
+        if let Some(end) = self.end {
+            write!(f, "-{}", end.format("%H:%M"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holiday {
+    pub name: String,
+    pub category: Category,
+    /// `None` for all-day entries
+    pub time: Option<TimeSpan>,
+    /// First and last day of a multi-day entry, which is stored on every day it covers
+    pub span: Option<(NaiveDate, NaiveDate)>,
+    /// File the entry was read from, or the observance that computed it
+    pub source: String,
+}
+
+impl Holiday {
+    /// Whether the name contains `pattern`, ignoring case.
+    pub fn name_contains(&self, pattern: &str) -> bool {
+        self.name.to_lowercase().contains(&pattern.to_lowercase())
+    }
+
+    /// Whether this entry makes its day a day off. Personal events and timed
+    /// events do not.
+    pub fn is_day_off(&self) -> bool {
+        self.category != Category::Personal && self.time.is_none()
+    }
+}
+
+/// What was loaded from one file or observance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceStats {
+    pub source: String,
+    /// Multi-day entries count once
+    pub entries: usize,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+#[derive(Default)]
+pub struct HolidayInfo {
+    entries: BTreeMap<NaiveDate, Vec<Holiday>>,
+    work_week: WorkWeek,
+}
+
+impl HolidayInfo {
+    pub fn new() -> HolidayInfo {
+        HolidayInfo::default()
+    }
+
+    /// Japanese national holidays of `years`, computed rather than read from
+    /// a file, with substitute holidays and citizens' holidays.
+    ///
+    /// ```
+    /// use calp::{add_business_days, is_business_day, roll, HolidayInfo, RollConvention};
+    /// use chrono::NaiveDate;
+    ///
+    /// let holidays = HolidayInfo::japanese(2025..=2025);
+    /// let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+    /// assert!(!is_business_day(&holidays, date(5, 6)));
+    /// assert_eq!(roll(&holidays, date(5, 3), RollConvention::Following), Some(date(5, 7)));
+    /// assert_eq!(add_business_days(&holidays, date(5, 2), 1), Some(date(5, 7)));
+    /// ```
+    pub fn japanese(years: RangeInclusive<i32>) -> HolidayInfo {
+        let sources: [Box<dyn DateSource>; 2] = [
+            Box::new(Computed {
+                observances: vec![Observance::Japan],
+                years,
+                lang: Lang::Japanese,
+            }),
+            Box::new(SubstituteHolidays {
+                lang: Lang::Japanese,
+            }),
+        ];
+        load_sources(&sources, &Tracer::new(false)).expect("computed sources cannot fail")
+    }
+
+    /// The entries of a holiday file and the files it `#include`s, such as
+    /// the Cabinet Office's `syukujitsu.csv` in [`Encoding::ShiftJis`].
+    pub fn from_file(path: &Path, encoding: Encoding) -> LibResult<HolidayInfo> {
+        let source: [Box<dyn DateSource>; 1] = [Box::new(HolidayFile {
+            path: path.to_path_buf(),
+            encoding,
+            date_format: DateFormat::Ymd,
+            category: Category::National,
+        })];
+        load_sources(&source, &Tracer::new(false))
+    }
+
+    pub fn work_week(&self) -> &WorkWeek {
+        &self.work_week
+    }
+
+    pub fn set_work_week(&mut self, work_week: WorkWeek) {
+        self.work_week = work_week;
+    }
+
+    /// Returns the most significant category among the entries on the given day.
+    pub fn category(&self, year: i32, month: u32, day: u32) -> Option<Category> {
+        let date = NaiveDate::from_ymd_opt(year, month, day)?;
+        self.entries.get(&date)?.iter().map(|h| h.category).min()
+    }
+
+    pub fn holidays_on(&self, date: NaiveDate) -> &[Holiday] {
+        self.entries.get(&date).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Whether `date` is a day off. Personal events and timed events do not make a day off.
+    pub fn is_day_off(&self, date: NaiveDate) -> bool {
+        self.holidays_on(date).iter().any(Holiday::is_day_off)
+    }
+
+    /// Holidays within `range` that make a day off, a span counting once.
+    pub fn holiday_count(&self, range: RangeInclusive<NaiveDate>) -> usize {
+        let first = *range.start();
+        self.entries
+            .range(range)
+            .flat_map(|(date, holidays)| holidays.iter().map(move |h| (*date, h)))
+            .filter(|(date, h)| {
+                h.is_day_off()
+                    && h.span
+                        .is_none_or(|(start, _)| start == *date || first == *date)
+            })
+            .count()
+    }
+
+    /// Number of entries on each day that has any.
+    pub fn entry_counts(&self) -> HashMap<NaiveDate, usize> {
+        self.entries
+            .iter()
+            .map(|(date, holidays)| (*date, holidays.len()))
+            .collect()
+    }
+
+    /// Days within `range` whose entries come from more than one source.
+    pub fn conflicts(
+        &self,
+        range: RangeInclusive<NaiveDate>,
+    ) -> impl Iterator<Item = (NaiveDate, &[Holiday])> {
+        self.entries
+            .range(range)
+            .filter(|(_, holidays)| holidays.iter().any(|h| h.source != holidays[0].source))
+            .map(|(date, holidays)| (*date, holidays.as_slice()))
+    }
+
+    /// Whether any entry falls in `year`.
+    pub fn covers_year(&self, year: i32) -> bool {
+        let (Some(first), Some(last)) = (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year, 12, 31),
+        ) else {
+            return false;
+        };
+        self.entries.range(first..=last).next().is_some()
+    }
+
+    /// Entry count and date coverage of each source, sorted by source.
+    pub fn sources(&self) -> Vec<SourceStats> {
+        let mut stats: BTreeMap<&str, SourceStats> = BTreeMap::new();
+        for (date, holiday) in self.iter_from(NaiveDate::MIN) {
+            let s = stats
+                .entry(holiday.source.as_str())
+                .or_insert_with(|| SourceStats {
+                    source: holiday.source.clone(),
+                    entries: 0,
+                    first: date,
+                    last: date,
+                });
+            if holiday.span.is_none_or(|(first, _)| first == date) {
+                s.entries += 1;
+            }
+            s.last = date;
+        }
+        stats.into_values().collect()
+    }
+
+    /// Iterates over all entries on or after `date` in chronological order.
+    pub fn iter_from(&self, date: NaiveDate) -> impl Iterator<Item = (NaiveDate, &Holiday)> {
+        self.entries
+            .range(date..)
+            .flat_map(|(date, holidays)| holidays.iter().map(|h| (*date, h)))
+    }
+
+    /// The first day after `date` with an entry whose name contains
+    /// `pattern`, wrapping around to the earliest such day.
+    pub fn find_next(&self, date: NaiveDate, pattern: &str) -> Option<NaiveDate> {
+        let after = date.succ_opt().map(|d| self.iter_from(d));
+        after
+            .into_iter()
+            .flatten()
+            .chain(self.iter_from(NaiveDate::MIN))
+            .find(|(_, holiday)| holiday.name_contains(pattern))
+            .map(|(date, _)| date)
+    }
+
+    /// Drops every entry whose category is not in `categories`.
+    pub fn retain_categories(&mut self, categories: &[Category]) {
+        let entries = std::mem::take(&mut self.entries);
+        for (date, holidays) in entries {
+            for holiday in holidays {
+                if categories.contains(&holiday.category) {
+                    self.add(date, holiday);
+                }
+            }
+        }
+    }
+
+    /// Adds the days off the Act on National Holidays derives from national
+    /// holidays: a substitute holiday (振替休日) after one falling on Sunday,
+    /// and a citizens' holiday (国民の休日) on a day between two of them.
+    /// Only the computed Japanese holidays count: files, the Cabinet
+    /// Office CSV among them, list such days themselves if they have them,
+    /// and other countries' holidays follow other rules. Days that already
+    /// have a national entry, e.g. 休日 from the CSV, are left alone.
+    pub(crate) fn add_substitute_holidays(&mut self, lang: Lang) {
+        let is_national = |h: &Holiday| {
+            matches!(h.category, Category::National | Category::Override) && h.time.is_none()
+        };
+        let japan = Observance::Japan
+            .to_possible_value()
+            .map(|v| v.get_name().to_string());
+        let base = self
+            .entries
+            .iter()
+            .filter(|(_, holidays)| {
+                holidays.iter().any(|h| {
+                    is_national(h)
+                        && Some(&h.source) == japan.as_ref()
+                        && !DERIVED_NAMES.contains(&h.name.as_str())
+                })
+            })
+            .map(|(date, _)| *date)
+            .collect::<BTreeSet<_>>();
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let mut derived = vec![];
+        for date in &base {
+            let Some(next) = date.succ_opt() else {
+                continue;
+            };
+            // Substitute holidays since 1973-04-12; since 2007 they move on
+            // to the first day that is not a holiday.
+            if date.weekday() == Weekday::Sun && *date >= ymd(1973, 4, 12) {
+                let mut day = next;
+                while *date >= ymd(2007, 1, 1) && base.contains(&day) {
+                    day = day + Days::new(1);
+                }
+                if !base.contains(&day) {
+                    derived.push((day, "振替休日", "Substitute Holiday"));
+                }
+            }
+            // Citizens' holidays since 1985-12-27.
+            if *date >= ymd(1985, 12, 27)
+                && !base.contains(&next)
+                && next.weekday() != Weekday::Sun
+                && next.succ_opt().is_some_and(|d| base.contains(&d))
+            {
+                derived.push((next, "国民の休日", "Citizens' Holiday"));
+            }
+        }
+        for (date, ja, en) in derived {
+            if self.holidays_on(date).iter().any(is_national) {
+                continue;
+            }
+            self.add(
+                date,
+                Holiday {
+                    name: match lang {
+                        Lang::Japanese => ja,
+                        Lang::English => en,
+                    }
+                    .to_string(),
+                    category: Category::National,
+                    time: None,
+                    span: None,
+                    source: "substitute".to_string(),
+                },
+            );
+        }
+    }
+
+    /// Removes the entries on `date`, or only those called `name` when given.
+    pub fn remove(&mut self, date: NaiveDate, name: Option<&str>) {
+        self.retain_on(date, |h| name.is_some_and(|n| h.name != n));
+    }
+
+    /// Removes the entries on `date` called `name` that were read from `source`.
+    pub fn remove_from(&mut self, date: NaiveDate, name: &str, source: &str) {
+        self.retain_on(date, |h| h.name != name || h.source != source);
+    }
+
+    fn retain_on(&mut self, date: NaiveDate, keep: impl FnMut(&Holiday) -> bool) {
+        let Some(holidays) = self.entries.get_mut(&date) else {
+            return;
+        };
+        holidays.retain(keep);
+        if holidays.is_empty() {
+            self.entries.remove(&date);
+        }
+    }
+
+    /// Adds many entries at once, as [`add`](Self::add) would one by one.
+    /// Into an empty calendar, e.g. from the first holiday file, the map is
+    /// built in one pass instead of searched for each entry.
+    pub fn extend(&mut self, mut entries: Vec<(NaiveDate, Holiday)>) {
+        if !self.entries.is_empty() {
+            for (date, holiday) in entries {
+                self.add(date, holiday);
+            }
+            return;
+        }
+        // Stable sorts keep the order of same-day entries, as add does.
+        entries.sort_by_key(|(date, _)| *date);
+        let mut days: Vec<(NaiveDate, Vec<Holiday>)> = vec![];
+        for (date, holiday) in entries {
+            match days.last_mut() {
+                Some((day, holidays)) if *day == date => holidays.push(holiday),
+                _ => days.push((date, vec![holiday])),
+            }
+        }
+        for (_, holidays) in &mut days {
+            holidays.sort_by_key(|h| h.time.map(|t| t.start));
+        }
+        self.entries = days.into_iter().collect();
+    }
+
+    pub fn add(&mut self, date: NaiveDate, holiday: Holiday) {
+        let holidays = self.entries.entry(date).or_default();
+        holidays.push(holiday);
+        // All-day entries first, then by start time.
+        holidays.sort_by_key(|h| h.time.map(|t| t.start));
+    }
+}
+
+/// Set once [`default_holiday_file`] finds no file.
+static DEFAULT_FILE_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// The first of the [`holiday_file_candidates`] that exists, e.g.
+/// `~/.config/calp/shuku.csv` or the legacy `~/.calp_shuku`. A missing file
+/// is remembered until [`forget_missing_holiday_file`], so `--watch` and
+/// `pick` don't look for it on every redraw.
+pub fn default_holiday_file() -> Option<PathBuf> {
+    first_existing(holiday_file_candidates(), &DEFAULT_FILE_MISSING)
+}
+
+/// Makes the next [`default_holiday_file`] look again, once one of the
+/// candidates may have been created.
+pub fn forget_missing_holiday_file() {
+    DEFAULT_FILE_MISSING.store(false, Ordering::Relaxed);
+}
+
+fn first_existing(candidates: Vec<PathBuf>, missing: &AtomicBool) -> Option<PathBuf> {
+    if missing.load(Ordering::Relaxed) {
+        return None;
+    }
+    let path = candidates.into_iter().find(|p| p.exists());
+    if path.is_none() {
+        missing.store(true, Ordering::Relaxed);
+    }
+    path
+}
+
+/// Reads a holiday file, following `#include path` directives.
+/// Relative include paths are resolved against the directory of the including file.
+///
+/// Each line is `date[ time],name[,category]`. Lines starting with `#` are comments.
+/// Lines without a category get `category`, except timed ones in a national
+/// file, which are personal (see [`default_category`]).
+/// Every file opened, included ones too, is added to `read`.
+pub fn read_holiday_file(
+    path: &Path,
+    encoding: Encoding,
+    date_format: DateFormat,
+    category: Category,
+    stack: &mut Vec<PathBuf>,
+    read: &mut Vec<PathBuf>,
+) -> LibResult<Vec<(NaiveDate, Holiday)>> {
+    let f = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    read.push(path.to_path_buf());
+    let canonical = path.canonicalize()?;
+    if stack.contains(&canonical) {
+        return Err(format!("include cycle detected: {}", path.display()).into());
+    }
+    stack.push(canonical);
+
+    // Decoded a line at a time, so only the entries are held in memory. Line
+    // feeds and carriage returns never occur inside a Shift_JIS or UTF-8
+    // character, and the decoder carries its BOM handling over from the
+    // first line.
+    let mut file = BufReader::with_capacity(1 << 16, f);
+    let mut decoder = SHIFT_JIS.new_decoder();
+    let mut buf = Vec::new();
+    let mut decoded = String::new();
+    let source = path.display().to_string();
+    let mut holidays = Vec::new();
+    let mut n = 0;
+    loop {
+        buf.clear();
+        let Some(ended) = read_line(&mut file, &mut buf)? else {
+            break;
+        };
+        n += 1;
+        let line = match encoding {
+            Encoding::ShiftJis => {
+                decoded.clear();
+                decoded.reserve(
+                    decoder
+                        .max_utf8_buffer_length(buf.len())
+                        .unwrap_or(buf.len() * 3),
+                );
+                let _ = decoder.decode_to_string(&buf, &mut decoded, !ended);
+                Cow::Borrowed(decoded.as_str())
+            }
+            Encoding::Utf8 => String::from_utf8_lossy(&buf), // UTF-8 is the default encoding in Rust.
+        };
+        let line = match n {
+            1 => line.strip_prefix('\u{feff}').unwrap_or(&line),
+            _ => &line,
+        };
+        let trimmed = line.trim();
+        // `#include` and a path; `#includes ...` is an ordinary comment
+        let include = trimmed
+            .strip_prefix("#include")
+            .filter(|rest| rest.starts_with(char::is_whitespace));
+        if let Some(include) = include {
+            let include = include.trim().trim_matches('"');
+            let include = path
+                .parent()
+                .map_or_else(|| PathBuf::from(include), |dir| dir.join(include));
+            holidays.extend(read_holiday_file(
+                &include,
+                encoding,
+                date_format,
+                category,
+                stack,
+                read,
+            )?);
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some((date, mut holiday)) =
+            parse_entry(line, date_format, category).map_err(|e| format!("{source}:{n}: {e}"))?
+        {
+            holiday.source = source.clone();
+            holidays.extend(expand_span(date, holiday));
+        }
+    }
+
+    stack.pop();
+    Ok(holidays)
+}
+
+/// Reads a line into `buf` without its ending, which may be a line feed,
+/// CRLF as on Windows, or a lone CR as on classic Mac OS. `None` at the end
+/// of input, otherwise whether the line had an ending.
+fn read_line(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> io::Result<Option<bool>> {
+    let mut read = false;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(read.then_some(false));
+        }
+        read = true;
+        let Some(i) = available.iter().position(|b| *b == b'\n' || *b == b'\r') else {
+            let len = available.len();
+            buf.extend_from_slice(available);
+            reader.consume(len);
+            continue;
+        };
+        let cr = available[i] == b'\r';
+        buf.extend_from_slice(&available[..i]);
+        reader.consume(i + 1);
+        if cr && reader.fill_buf()?.first() == Some(&b'\n') {
+            reader.consume(1);
+        }
+        return Ok(Some(true));
+    }
+}
+
+/// Entries of holiday-file text that has no `#include`s, such as a
+/// command's output, with `source` as their source.
+pub fn parse_holiday_text(
+    text: &str,
+    date_format: DateFormat,
+    category: Category,
+    source: &str,
+) -> LibResult<Vec<(NaiveDate, Holiday)>> {
+    let mut holidays = Vec::new();
+    for (n, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        if line.trim().starts_with('#') {
+            continue;
+        }
+        if let Some((date, mut holiday)) = parse_entry(line, date_format, category)
+            .map_err(|e| format!("{source}:{}: {e}", n + 1))?
+        {
+            holiday.source = source.to_string();
+            holidays.extend(expand_span(date, holiday));
+        }
+    }
+    Ok(holidays)
+}
+
+/// A calendar of holiday-file `lines`, read as [`parse_holiday_text`] reads them.
+#[cfg(test)]
+pub fn holidays_of(lines: &[&str]) -> HolidayInfo {
+    let text = lines.join("\n");
+    let mut holiday_info = HolidayInfo::new();
+    holiday_info
+        .extend(parse_holiday_text(&text, DateFormat::Ymd, Category::National, "").unwrap());
+    holiday_info
+}
+
+/// `holiday` on every day of its span, or on `date` alone.
+pub fn expand_span(date: NaiveDate, holiday: Holiday) -> Vec<(NaiveDate, Holiday)> {
+    match holiday.span {
+        Some((first, last)) => first
+            .iter_days()
+            .take_while(|d| *d <= last)
+            .map(|d| (d, holiday.clone()))
+            .collect(),
+        None => vec![(date, holiday)],
+    }
+}
+
+/// The category of an entry that names none, read from a source whose
+/// entries are `category`. Timed entries are appointments, not days off, so
+/// in a national holiday file they are personal instead.
+pub fn default_category(category: Category, timed: bool) -> Category {
+    match category {
+        Category::National if timed => Category::Personal,
+        category => category,
+    }
+}
+
+/// Parses a `date[..date][ time],name[,category]` line, where `time` is `HH:MM`
+/// or `HH:MM-HH:MM`. Lines without a valid date yield `None`.
+pub fn parse_line(line: &str) -> Result<Option<(NaiveDate, Holiday)>, String> {
+    parse_entry(line, DateFormat::Ymd, Category::National)
+}
+
+/// [`parse_line`], with dates in `date_format` and `category` for lines
+/// that don't name one.
+fn parse_entry(
+    line: &str,
+    date_format: DateFormat,
+    category: Category,
+) -> Result<Option<(NaiveDate, Holiday)>, String> {
+    let parse_date = |s: &str| match date_format {
+        DateFormat::Ymd => parse_ymd(s),
+        DateFormat::Excel => parse_ymd(s).or_else(|| parse_excel_serial(s)),
+    };
+    let mut cols = line.split(",");
+    let mut when = cols.next().unwrap_or_default().split_whitespace();
+    let dates = when.next().unwrap_or_default();
+    let (first, last) = dates.split_once("..").unwrap_or((dates, ""));
+    let Some(date) = parse_date(first) else {
+        return Ok(None);
+    };
+    let span = match last {
+        "" => None,
+        last => {
+            let last = parse_date(last).ok_or_else(|| format!("invalid date: \"{last}\""))?;
+            if last < date {
+                return Err(format!("range ends before it starts: \"{dates}\""));
+            }
+            Some((date, last))
+        }
+    };
+    let time = when.next().map(TimeSpan::parse).transpose()?;
+    let name = cols.next().unwrap_or_default().trim().to_string();
+    let category = match cols.next().map(str::trim) {
+        None | Some("") => default_category(category, time.is_some()),
+        Some(c) => Category::from_str(c, true).map_err(|_| format!("unknown category: \"{c}\""))?,
+    };
+    Ok(Some((
+        date,
+        Holiday {
+            name,
+            category,
+            time,
+            span,
+            source: String::new(),
+        },
+    )))
+}
+
+/// A `%Y/%m/%d` date. Holiday files are read line by line, and chrono's
+/// format parser is the slowest step of that.
+fn parse_ymd(s: &str) -> Option<NaiveDate> {
+    let mut parts = s.split('/');
+    let year = parts.next()?.parse().ok()?;
+    let mut number = || {
+        let part = parts.next()?;
+        let digits = (1..=2).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit());
+        digits.then(|| part.parse().ok())?
+    };
+    let (month, day) = (number()?, number()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// A day number of Excel's 1900 date system, where 1 is 1900-01-01. Excel
+/// counts a 29 February 1900 as serial 60, so later days are one off.
+fn parse_excel_serial(s: &str) -> Option<NaiveDate> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let serial: u64 = s.parse().ok()?;
+    let epoch = match serial {
+        0 | 60 => return None,
+        1..60 => NaiveDate::from_ymd_opt(1899, 12, 31)?,
+        _ => NaiveDate::from_ymd_opt(1899, 12, 30)?,
+    };
+    epoch.checked_add_days(Days::new(serial))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        env, fs,
+        io::BufReader,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use chrono::NaiveDate;
+
+    use chrono::NaiveTime;
+    use encoding_rs::SHIFT_JIS;
+
+    use crate::{
+        holiday::{
+            first_existing, holidays_of, parse_entry, parse_excel_serial, parse_line, parse_ymd,
+            read_holiday_file, read_line, Category, DateFormat, Holiday, HolidayInfo, TimeSpan,
+        },
+        jp_holidays::national_holidays,
+        Encoding, Lang,
+    };
+
+    #[test]
+    fn test_add_substitute_holidays() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let entry = |name: &str, source: &str| Holiday {
+            name: name.to_string(),
+            category: Category::National,
+            time: None,
+            span: None,
+            source: source.to_string(),
+        };
+        let mut hi = HolidayInfo::new();
+        for year in [2019, 2024] {
+            for (d, name) in national_holidays(year, Lang::Japanese) {
+                hi.add(d, entry(name, "japan"));
+            }
+        }
+        // Already listed, as in the Cabinet Office CSV
+        hi.add(date(2024, 2, 12), entry("休日", "holidays.csv"));
+        // Other holidays on a Sunday, and between two days off
+        hi.add(date(2024, 3, 31), entry("Easter Sunday", "christian"));
+        hi.add(date(2024, 6, 2), entry("trip", "trips.ics"));
+        hi.add(date(2024, 6, 4), entry("trip", "trips.ics"));
+        hi.add_substitute_holidays(Lang::Japanese);
+
+        let names = |d| {
+            hi.holidays_on(d)
+                .iter()
+                .map(|h| h.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(date(2019, 4, 30)), ["国民の休日"]);
+        assert_eq!(names(date(2019, 5, 2)), ["国民の休日"]);
+        assert_eq!(names(date(2019, 5, 6)), ["振替休日"]);
+        assert_eq!(names(date(2024, 2, 12)), ["休日"]);
+        assert_eq!(names(date(2024, 5, 6)), ["振替休日"]);
+        assert_eq!(names(date(2024, 11, 4)), ["振替休日"]);
+        assert!(names(date(2024, 9, 17)).is_empty());
+        assert!(names(date(2024, 4, 1)).is_empty());
+        assert!(names(date(2024, 6, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_sources() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let entry = |source: &str, span| Holiday {
+            name: String::new(),
+            category: Category::National,
+            time: None,
+            span,
+            source: source.to_string(),
+        };
+        let mut hi = HolidayInfo::new();
+        hi.add(date(2023, 1, 1), entry("a.csv", None));
+        hi.add(date(2025, 5, 5), entry("a.csv", None));
+        let trip = Some((date(2024, 8, 1), date(2024, 8, 2)));
+        hi.add(date(2024, 8, 1), entry("b.csv", trip));
+        hi.add(date(2024, 8, 2), entry("b.csv", trip));
+
+        let sources = hi.sources();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].entries, 2);
+        assert_eq!(
+            (sources[0].first, sources[0].last),
+            (date(2023, 1, 1), date(2025, 5, 5))
+        );
+        assert_eq!(sources[1].source, "b.csv");
+        assert_eq!(sources[1].entries, 1);
+    }
+
+    #[test]
+    fn test_holiday_count() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let hi = holidays_of(&[
+            "2025/04/29,昭和の日",
+            "2025/04/30..2025/05/02,社休,company",
+            "2025/04/30,dentist,personal",
+            "2025/05/01 10:00,call,company",
+        ]);
+        assert_eq!(hi.holiday_count(date(2025, 4, 1)..=date(2025, 4, 30)), 2);
+        // A span running into the month counts there too, once.
+        assert_eq!(hi.holiday_count(date(2025, 5, 1)..=date(2025, 5, 31)), 1);
+        assert_eq!(hi.holiday_count(date(2025, 4, 1)..=date(2025, 5, 31)), 2);
+    }
+
+    #[test]
+    fn test_entry_counts() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let hi = holidays_of(&[
+            "2025/05/05,こどもの日",
+            "2025/05/05 10:00,call,company",
+            "2025/05/05,dentist,personal",
+            "2025/05/06..2025/05/07,社休,company",
+        ]);
+        let counts = hi.entry_counts();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&date(2025, 5, 5)], 3);
+        assert_eq!(counts[&date(2025, 5, 7)], 1);
+        assert!(!counts.contains_key(&date(2025, 5, 8)));
+    }
+
+    #[test]
+    fn test_covers_year() {
+        let hi = holidays_of(&["2024/12/31,大晦日,personal", "2026/01/01,元日"]);
+        assert!(hi.covers_year(2024) && hi.covers_year(2026));
+        assert!(!hi.covers_year(2025));
+        assert!(!hi.covers_year(i32::MAX));
+    }
+
+    #[test]
+    fn test_remove_from() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let entry = |source: &str| Holiday {
+            name: "review".to_string(),
+            category: Category::Personal,
+            time: None,
+            span: None,
+            source: source.to_string(),
+        };
+        let mut hi = HolidayInfo::new();
+        hi.add(date, entry("team.csv"));
+        hi.add(date, entry("events.csv"));
+        hi.remove_from(date, "review", "events.csv");
+        let left = hi.holidays_on(date);
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].source, "team.csv");
+        hi.remove_from(date, "review", "team.csv");
+        assert!(hi.iter_from(NaiveDate::MIN).next().is_none());
+    }
+
+    #[test]
+    fn test_first_existing() {
+        let dir = env::temp_dir().join(format!("calp-default-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let candidates = vec![dir.join("shuku.csv"), dir.join(".calp_shuku")];
+        let missing = AtomicBool::new(false);
+        assert_eq!(first_existing(candidates.clone(), &missing), None);
+        // Remembered: a file created since is not seen...
+        fs::write(&candidates[1], "").unwrap();
+        assert_eq!(first_existing(candidates.clone(), &missing), None);
+        // ...until the miss is forgotten.
+        missing.store(false, Ordering::Relaxed);
+        assert_eq!(
+            first_existing(candidates.clone(), &missing).as_ref(),
+            Some(&candidates[1])
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_line() {
+        // A buffer smaller than a line, and a CRLF split across refills
+        let text = b"2025/01/01,a\r\n2025/01/02,b\r2025/01/03,c\n\n2025/01/04,d";
+        let mut reader = BufReader::with_capacity(13, &text[..]);
+        let mut lines = vec![];
+        let mut buf = vec![];
+        while let Some(ended) = read_line(&mut reader, &mut buf).unwrap() {
+            lines.push((String::from_utf8(buf.clone()).unwrap(), ended));
+            buf.clear();
+        }
+        let expected = [
+            ("2025/01/01,a", true),
+            ("2025/01/02,b", true),
+            ("2025/01/03,c", true),
+            ("", true),
+            ("2025/01/04,d", false),
+        ];
+        assert_eq!(lines, expected.map(|(l, e)| (l.to_string(), e)));
+    }
+
+    #[test]
+    fn test_extend() {
+        let entries = [
+            "2024/1/10 14:00,dentist,personal",
+            "2024/1/2,書き初め",
+            "2024/1/10,standup,company",
+            "2024/1/10 09:00,call,company",
+        ]
+        .map(|line| parse_line(line).unwrap().unwrap());
+        let listed = |hi: &HolidayInfo| {
+            hi.iter_from(NaiveDate::MIN)
+                .map(|(date, h)| (date, h.name.clone()))
+                .collect::<Vec<_>>()
+        };
+        let mut added = HolidayInfo::new();
+        for (date, holiday) in entries.clone() {
+            added.add(date, holiday);
+        }
+        let mut extended = HolidayInfo::new();
+        extended.extend(entries[..2].to_vec());
+        extended.extend(entries[2..].to_vec());
+        assert_eq!(listed(&extended), listed(&added));
+        let mut extended = HolidayInfo::new();
+        extended.extend(entries.to_vec());
+        assert_eq!(listed(&extended), listed(&added));
+    }
+
+    #[test]
+    fn test_read_holiday_file() {
+        let dir = env::temp_dir().join(format!("calp-read-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (sjis, _, _) = SHIFT_JIS.encode(
+            "# 祝日\r\n#includes follow\r\n2025/01/01,元日\r\n#include \"more.csv\"\r\n#include\r\n2025/02/11,建国記念の日",
+        );
+        fs::write(dir.join("holidays.csv"), sjis).unwrap();
+        fs::write(
+            dir.join("more.csv"),
+            SHIFT_JIS.encode("2025/01/13,成人の日\n").0,
+        )
+        .unwrap();
+        let names = |encoding| {
+            read_holiday_file(
+                &dir.join("holidays.csv"),
+                encoding,
+                DateFormat::Ymd,
+                Category::National,
+                &mut Vec::new(),
+                &mut Vec::new(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(date, h)| (date.to_string(), h.name))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(Encoding::ShiftJis),
+            [
+                ("2025-01-01", "元日"),
+                ("2025-01-13", "成人の日"),
+                ("2025-02-11", "建国記念の日"),
+            ]
+            .map(|(d, n)| (d.to_string(), n.to_string()))
+        );
+        fs::write(
+            dir.join("holidays.csv"),
+            "2025/01/01,元日\r\n2025/01/02,初夢\r\n",
+        )
+        .unwrap();
+        assert_eq!(names(Encoding::Utf8)[1].1, "初夢");
+        // Saved from Excel: a BOM, padded fields and classic Mac line endings
+        fs::write(
+            dir.join("holidays.csv"),
+            "\u{feff}2025/01/01 , 元日 \r2025/01/02,初夢, personal \r",
+        )
+        .unwrap();
+        for encoding in [Encoding::Utf8, Encoding::ShiftJis] {
+            let names = names(encoding);
+            assert_eq!(names.len(), 2);
+            assert_eq!((names[0].1.as_str(), names[1].1.as_str()), ("元日", "初夢"));
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_ymd() {
+        for s in [
+            "2024/01/10",
+            "2024/1/9",
+            "824/12/31",
+            "2024/02/30",
+            "2024/001/10",
+            "2024/01",
+            "2024/01/10/1",
+            "2024-01-10",
+            "2024/+1/10",
+            "",
+        ] {
+            let chrono = NaiveDate::parse_from_str(s, "%Y/%m/%d").ok();
+            assert_eq!(parse_ymd(s), chrono, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_parse_excel_serial() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(parse_excel_serial("1"), ymd(1900, 1, 1));
+        assert_eq!(parse_excel_serial("59"), ymd(1900, 2, 28));
+        assert_eq!(parse_excel_serial("60"), None);
+        assert_eq!(parse_excel_serial("61"), ymd(1900, 3, 1));
+        assert_eq!(parse_excel_serial("45658"), ymd(2025, 1, 1));
+        assert_eq!(parse_excel_serial("45870"), ymd(2025, 8, 1));
+        for s in ["0", "", "+45658", "45658.5", "日付"] {
+            assert_eq!(parse_excel_serial(s), None, "{s}");
+        }
+
+        let entry = |line, date_format| {
+            parse_entry(line, date_format, Category::National)
+                .unwrap()
+                .map(|(date, h)| (date, h.span))
+        };
+        assert_eq!(
+            entry("45658,元日", DateFormat::Excel),
+            Some((ymd(2025, 1, 1).unwrap(), None))
+        );
+        assert_eq!(entry("45658,元日", DateFormat::Ymd), None);
+        assert_eq!(
+            entry("45658..2025/01/03,正月", DateFormat::Excel),
+            Some((
+                ymd(2025, 1, 1).unwrap(),
+                Some((ymd(2025, 1, 1).unwrap(), ymd(2025, 1, 3).unwrap()))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let holiday = |name: &str, category| Holiday {
+            name: name.to_string(),
+            category,
+            time: None,
+            span: None,
+            source: String::new(),
+        };
+
+        assert_eq!(
+            parse_line("2024/1/10,元日"),
+            Ok(Some((date, holiday("元日", Category::National))))
+        );
+        assert_eq!(
+            parse_line("2024/1/10,team,company"),
+            Ok(Some((date, holiday("team", Category::Company))))
+        );
+        assert_eq!(
+            parse_line("2024/1/10,birthday, Personal"),
+            Ok(Some((date, holiday("birthday", Category::Personal))))
+        );
+        assert_eq!(
+            parse_line("国民の祝日・休日月日,国民の祝日・休日名称"),
+            Ok(None)
+        );
+        assert!(parse_line("2024/1/10,x,unknown").is_err());
+
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(
+            parse_line("2024/1/10 09:00-10:00,standup,company").map(|e| e.unwrap().1.time),
+            Ok(Some(TimeSpan {
+                start: time(9),
+                end: Some(time(10)),
+            }))
+        );
+        assert_eq!(
+            parse_line("2024/1/10 14:00,dentist").map(|e| e.unwrap().1.time),
+            Ok(Some(TimeSpan {
+                start: time(14),
+                end: None,
+            }))
+        );
+        assert!(parse_line("2024/1/10 10:00-09:00,x").is_err());
+        let category = |line| parse_line(line).map(|e| e.unwrap().1.category);
+        assert_eq!(category("2024/1/10 14:00,dentist"), Ok(Category::Personal));
+        assert_eq!(
+            category("2024/1/10 14:00,vote,national"),
+            Ok(Category::National)
+        );
+        assert_eq!(
+            parse_entry("2024/1/10 14:00,review", DateFormat::Ymd, Category::Company)
+                .map(|e| e.unwrap().1.category),
+            Ok(Category::Company)
+        );
+
+        assert_eq!(
+            parse_line("2024/1/10..2024/1/14,trip,personal").map(|e| e.unwrap().1.span),
+            Ok(Some((date, NaiveDate::from_ymd_opt(2024, 1, 14).unwrap())))
+        );
+        assert!(parse_line("2024/1/10..2024/1/9,trip").is_err());
+    }
+
+    #[test]
+    fn test_find_next() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let mut hi = HolidayInfo::new();
+        for (d, name) in national_holidays(2024, Lang::English) {
+            hi.add(
+                d,
+                Holiday {
+                    name: name.to_string(),
+                    category: Category::National,
+                    time: None,
+                    span: None,
+                    source: String::new(),
+                },
+            );
+        }
+        assert_eq!(hi.find_next(date(1, 1), "day"), Some(date(1, 8)));
+        assert_eq!(hi.find_next(date(9, 1), "EQUINOX"), Some(date(9, 22)));
+        // Wraps around past the last match
+        assert_eq!(hi.find_next(date(9, 22), "equinox"), Some(date(3, 20)));
+        assert_eq!(hi.find_next(date(1, 1), "easter"), None);
+    }
+}
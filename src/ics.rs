@@ -0,0 +1,315 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::{
+    cache::fnv1a,
+    filter::DayFilter,
+    holiday::{default_category, expand_span, Category, Holiday, HolidayInfo, TimeSpan},
+};
+
+/// An iCalendar (RFC 5545) calendar of every holiday/event in `year`, for
+/// import into Google Calendar or Outlook. All-day entries become date
+/// events, shown as free time; timed ones keep their local (floating) times.
+/// `stamp` is the UTC creation time every event carries.
+pub fn format_ics(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    day_filter: &DayFilter,
+    stamp: NaiveDateTime,
+) -> String {
+    let Some(start) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+        return String::new();
+    };
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//calp//calp {}//JA", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let entries = holiday_info
+        .iter_from(start)
+        .take_while(|(date, _)| date.year() == year)
+        // Multi-day entries are stored on each day they cover
+        .filter(|(date, h)| {
+            h.span
+                .is_none_or(|(first, _)| *date == first || *date == start)
+        })
+        .filter(|(date, _)| day_filter.matches(holiday_info, *date));
+    for (date, holiday) in entries {
+        lines.extend(format_event(date, holiday, stamp));
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+fn format_event(date: NaiveDate, holiday: &Holiday, stamp: NaiveDateTime) -> Vec<String> {
+    let (first, last) = holiday.span.unwrap_or((date, date));
+    let ymd = |d: NaiveDate| d.format("%Y%m%d").to_string();
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}-{:016x}@calp", ymd(first), uid_hash(holiday)),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+    ];
+    match holiday.time {
+        Some(time) => {
+            let at = |t: NaiveTime| format!("{}T{}", ymd(first), t.format("%H%M%S"));
+            lines.push(format!("DTSTART:{}", at(time.start)));
+            if let Some(end) = time.end {
+                lines.push(format!("DTEND:{}", at(end)));
+            }
+        }
+        None => {
+            // DTEND is exclusive for dates
+            let end = last.succ_opt().unwrap_or(last);
+            lines.push(format!("DTSTART;VALUE=DATE:{}", ymd(first)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", ymd(end)));
+            lines.push("TRANSP:TRANSPARENT".to_string());
+        }
+    }
+    lines.push(format!("SUMMARY:{}", escape(&holiday.name)));
+    let category = match holiday.category {
+        Category::National => "NATIONAL",
+        Category::Override => "OVERRIDE",
+        Category::Company => "COMPANY",
+        Category::Personal => "PERSONAL",
+    };
+    lines.push(format!("CATEGORIES:{category}"));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// Escapes a TEXT value.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits `line` into lines of at most 75 octets, continued with a leading space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+/// The events of an iCalendar calendar, each on every day it covers, with
+/// `source` as their source. `CATEGORIES` picks the category when it names
+/// one of calp's, as exported calendars do; otherwise it is `category`, or
+/// personal for timed events when that is national.
+/// Times are taken as written, whatever their zone, and recurrence rules
+/// are not expanded.
+pub fn parse_ics(
+    text: &str,
+    category: Category,
+    source: &str,
+) -> Result<Vec<(NaiveDate, Holiday)>, String> {
+    let mut entries = vec![];
+    let mut event: Option<Vec<(String, String)>> = None;
+    for line in unfold(text) {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = head.split(';').next().unwrap_or_default().to_uppercase();
+        match (name.as_str(), &mut event) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => event = Some(vec![]),
+            ("END", Some(props)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let (date, holiday) = parse_event(props, category, source)?;
+                entries.extend(expand_span(date, holiday));
+                event = None;
+            }
+            (_, Some(props)) => props.push((name, value.to_string())),
+            _ => (),
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_event(
+    props: &[(String, String)],
+    category: Category,
+    source: &str,
+) -> Result<(NaiveDate, Holiday), String> {
+    let prop = |name: &str| props.iter().find(|(n, _)| n == name).map(|(_, v)| v.trim());
+    let Some(dtstart) = prop("DTSTART") else {
+        return Err(format!("{source}: VEVENT without DTSTART"));
+    };
+    let parse = |v: &str| -> Result<(NaiveDate, Option<NaiveTime>), String> {
+        let invalid = || format!("{source}: invalid date: \"{v}\"");
+        let date = NaiveDate::parse_from_str(v.get(..8).ok_or_else(invalid)?, "%Y%m%d")
+            .map_err(|_| invalid())?;
+        match v.get(8..) {
+            Some("") | None => Ok((date, None)),
+            Some(time) => {
+                let time = time
+                    .strip_prefix('T')
+                    .and_then(|t| t.get(..6))
+                    .ok_or_else(invalid)?;
+                let time = NaiveTime::parse_from_str(time, "%H%M%S").map_err(|_| invalid())?;
+                Ok((date, Some(time)))
+            }
+        }
+    };
+    let (first, start) = parse(dtstart)?;
+    let end = prop("DTEND").map(parse).transpose()?;
+    let (time, span) = match start {
+        Some(start) => {
+            let end = end.filter(|(d, _)| *d == first).and_then(|(_, t)| t);
+            (Some(TimeSpan { start, end }), None)
+        }
+        // DTEND is exclusive for dates
+        None => {
+            let last = end
+                .and_then(|(d, _)| d.pred_opt())
+                .filter(|last| *last > first);
+            (None, last.map(|last| (first, last)))
+        }
+    };
+    let category = match prop("CATEGORIES").map(|c| c.to_uppercase()).as_deref() {
+        Some("NATIONAL") => Category::National,
+        Some("OVERRIDE") => Category::Override,
+        Some("COMPANY") => Category::Company,
+        Some("PERSONAL") => Category::Personal,
+        _ => default_category(category, time.is_some()),
+    };
+    Ok((
+        first,
+        Holiday {
+            name: unescape(prop("SUMMARY").unwrap_or_default()),
+            category,
+            time,
+            span,
+            source: source.to_string(),
+        },
+    ))
+}
+
+/// Content lines with folded continuations joined back on.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Reverses [`escape`]; line breaks become spaces, as names are one line.
+fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push(' '),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A UID part that stays the same across exports of the same entry.
+fn uid_hash(holiday: &Holiday) -> u64 {
+    let key = format!("{}\0{}\0{:?}", holiday.source, holiday.name, holiday.time);
+    fnv1a(key.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        filter::DayFilter,
+        holiday::{holidays_of, Category, TimeSpan},
+        ics::{fold, format_ics, parse_ics},
+    };
+
+    #[test]
+    fn test_format_ics() {
+        let hi = holidays_of(&[
+            "2025/01/01,元日",
+            "2025/03/10 09:00-10:00,standup; weekly,company",
+            "2025/08/12..2025/08/14,夏休み,personal",
+        ]);
+        let stamp = NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let ics = format_ics(&hi, 2025, &DayFilter::default(), stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250101\r\nDTEND;VALUE=DATE:20250102\r\n"));
+        assert!(ics.contains("DTSTART:20250310T090000\r\nDTEND:20250310T100000\r\n"));
+        assert!(ics.contains("SUMMARY:standup\\; weekly\r\nCATEGORIES:COMPANY\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250812\r\nDTEND;VALUE=DATE:20250815\r\n"));
+
+        let long = format!("SUMMARY:{}", "祝".repeat(30));
+        let folded = fold(&long);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), long);
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20250812\r\n\
+            DTEND;VALUE=DATE:20250815\r\n\
+            SUMMARY:夏休み\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;TZID=Asia/Tokyo:20250310T090000\r\n\
+            DTEND;TZID=Asia/Tokyo:20250310T100000\r\n\
+            SUMMARY:standup\\; week\r\n ly\r\n\
+            CATEGORIES:COMPANY\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let entries = parse_ics(ics, Category::Personal, "cal.ics").unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        assert_eq!(
+            entries.iter().map(|(d, _)| *d).collect::<Vec<_>>(),
+            [date(8, 12), date(8, 13), date(8, 14), date(3, 10)]
+        );
+        let (_, summer) = &entries[0];
+        assert_eq!(
+            (summer.name.as_str(), summer.category, summer.span),
+            (
+                "夏休み",
+                Category::Personal,
+                Some((date(8, 12), date(8, 14)))
+            )
+        );
+        let (_, standup) = &entries[3];
+        assert_eq!(standup.name, "standup; weekly");
+        assert_eq!(standup.category, Category::Company);
+        assert_eq!(standup.time, Some(TimeSpan::parse("09:00-10:00").unwrap()));
+        assert_eq!(standup.source, "cal.ics");
+
+        let hi = holidays_of(&["2025/01/01,元日"]);
+        let stamp = NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let exported = format_ics(&hi, 2025, &DayFilter::default(), stamp);
+        let entries = parse_ics(&exported, Category::Personal, "").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.category, Category::National);
+
+        let missing = "BEGIN:VEVENT\nSUMMARY:x\nEND:VEVENT\n";
+        assert!(parse_ics(missing, Category::Personal, "").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    business::is_business_day,
+    consts::{ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    holiday::HolidayInfo,
+    sun::{format_sun_times, sun_times, SunConfig},
+    tz::Zone,
+    Lang,
+};
+
+/// Describes a single day: weekday, holidays/events, business-day flag and sun times.
+pub fn format_info(
+    holiday_info: &HolidayInfo,
+    date: NaiveDate,
+    zone: Zone,
+    sun: &SunConfig,
+    lang: Lang,
+) -> Vec<String> {
+    let weekday = date.weekday().num_days_from_sunday() as usize;
+    let japanese = lang == Lang::Japanese;
+    let mut lines = vec![match lang {
+        Lang::Japanese => format!(
+            "{} ({})",
+            date.format("%Y/%m/%d"),
+            JAPANESE_WEEK_NAMES[weekday]
+        ),
+        Lang::English => format!(
+            "{} ({})",
+            date.format("%Y-%m-%d"),
+            ENGLISH_WEEK_NAMES[weekday]
+        ),
+    }];
+    lines.extend(holiday_info.holidays_on(date).iter().map(|h| match h.time {
+        Some(time) => format!("  {time} {}", h.name),
+        None => format!("  {}", h.name),
+    }));
+    lines.push(
+        match (is_business_day(holiday_info, date), japanese) {
+            (true, true) => "営業日",
+            (false, true) => "休業日",
+            (true, false) => "Business day",
+            (false, false) => "Day off",
+        }
+        .to_string(),
+    );
+    match zone.transition_on(date) {
+        Some((before, after)) => lines.push(format!(
+            "UTC{before} → UTC{after} ({})",
+            if japanese {
+                "夏時間の切り替え"
+            } else {
+                "daylight saving time transition"
+            }
+        )),
+        None => {
+            if let Some(offset) = zone.offset_on(date) {
+                lines.push(format!("UTC{offset}"));
+            }
+        }
+    }
+    if let Some((lat, lon)) = sun.location() {
+        lines.push(format_sun_times(sun_times(date, lat, lon), zone, japanese));
+    }
+    lines
+}
@@ -0,0 +1,207 @@
+use chrono::{NaiveDate, Weekday};
+
+use crate::Lang;
+
+/// Promulgation of the latest change [`national_holidays`] follows: the
+/// 2021 moves for the Tokyo Olympics.
+pub const RULES_AS_OF: &str = "2020-12-04";
+
+/// Years for which the equinoxes, and so 春分の日 and 秋分の日, are computed
+pub const EQUINOX_YEARS: (i32, i32) =
+    (EQUINOX_BASES[0].0, EQUINOX_BASES[EQUINOX_BASES.len() - 1].1);
+
+/// First and last year, and the March and September constants of the
+/// equinox approximation the National Astronomical Observatory publishes.
+const EQUINOX_BASES: [(i32, i32, f64, f64); 3] = [
+    (1900, 1979, 20.8357, 23.2588),
+    (1980, 2099, 20.8431, 23.2488),
+    (2100, 2150, 21.8510, 24.2488),
+];
+
+/// Japanese national holidays (国民の祝日) of `year`, following the amendments
+/// to the Act on National Holidays since 1949. Substitute holidays and
+/// sandwiched days are derived later by `HolidayInfo::add_substitute_holidays`.
+pub fn national_holidays(year: i32, lang: Lang) -> Vec<(NaiveDate, &'static str)> {
+    if year < 1949 {
+        return vec![];
+    }
+    let ymd = |m, d| NaiveDate::from_ymd_opt(year, m, d);
+    let monday = |m, n| NaiveDate::from_weekday_of_month_opt(year, m, Weekday::Mon, n);
+    let mut days: Vec<(Option<NaiveDate>, &str, &str)> = vec![
+        (ymd(1, 1), "元日", "New Year's Day"),
+        (
+            if year < 2000 {
+                ymd(1, 15)
+            } else {
+                monday(1, 2)
+            },
+            "成人の日",
+            "Coming of Age Day",
+        ),
+        (ymd(5, 3), "憲法記念日", "Constitution Memorial Day"),
+        (ymd(5, 5), "こどもの日", "Children's Day"),
+        (ymd(11, 3), "文化の日", "Culture Day"),
+        (ymd(11, 23), "勤労感謝の日", "Labor Thanksgiving Day"),
+    ];
+    if let Some(day) = vernal_equinox(year) {
+        days.push((ymd(3, day), "春分の日", "Vernal Equinox Day"));
+    }
+    if let Some(day) = autumnal_equinox(year) {
+        days.push((ymd(9, day), "秋分の日", "Autumnal Equinox Day"));
+    }
+    if year >= 1967 {
+        days.push((ymd(2, 11), "建国記念の日", "National Foundation Day"));
+    }
+    let emperors_birthday = match year {
+        ..=1988 => ymd(4, 29),
+        1989..=2018 => ymd(12, 23),
+        2019 => None,
+        _ => ymd(2, 23),
+    };
+    days.push((emperors_birthday, "天皇誕生日", "Emperor's Birthday"));
+    match year {
+        1989..=2006 => days.push((ymd(4, 29), "みどりの日", "Greenery Day")),
+        2007.. => {
+            days.push((ymd(4, 29), "昭和の日", "Showa Day"));
+            days.push((ymd(5, 4), "みどりの日", "Greenery Day"));
+        }
+        _ => (),
+    }
+    let marine_day = match year {
+        ..=1995 => None,
+        1996..=2002 => ymd(7, 20),
+        2020 => ymd(7, 23),
+        2021 => ymd(7, 22),
+        _ => monday(7, 3),
+    };
+    days.push((marine_day, "海の日", "Marine Day"));
+    let mountain_day = match year {
+        ..=2015 => None,
+        2020 => ymd(8, 10),
+        2021 => ymd(8, 8),
+        _ => ymd(8, 11),
+    };
+    days.push((mountain_day, "山の日", "Mountain Day"));
+    let respect_for_the_aged = match year {
+        ..=1965 => None,
+        1966..=2002 => ymd(9, 15),
+        _ => monday(9, 3),
+    };
+    days.push((respect_for_the_aged, "敬老の日", "Respect for the Aged Day"));
+    match year {
+        ..=1965 => (),
+        1966..=1999 => days.push((ymd(10, 10), "体育の日", "Health and Sports Day")),
+        2000..=2019 => days.push((monday(10, 2), "体育の日", "Health and Sports Day")),
+        2020 => days.push((ymd(7, 24), "スポーツの日", "Sports Day")),
+        2021 => days.push((ymd(7, 23), "スポーツの日", "Sports Day")),
+        _ => days.push((monday(10, 2), "スポーツの日", "Sports Day")),
+    }
+    // One-off holidays enacted by special laws
+    let one_off = match year {
+        1959 => vec![(ymd(4, 10), "結婚の儀", "Wedding of Crown Prince Akihito")],
+        1989 => vec![(ymd(2, 24), "大喪の礼", "Funeral of Emperor Showa")],
+        1990 => vec![(ymd(11, 12), "即位礼正殿の儀", "Enthronement Ceremony")],
+        1993 => vec![(ymd(6, 9), "結婚の儀", "Wedding of Crown Prince Naruhito")],
+        2019 => vec![
+            (ymd(5, 1), "天皇の即位の日", "Enthronement Day"),
+            (ymd(10, 22), "即位礼正殿の儀", "Enthronement Ceremony"),
+        ],
+        _ => vec![],
+    };
+    days.extend(one_off);
+
+    let mut days = days
+        .into_iter()
+        .filter_map(|(date, ja, en)| {
+            Some((
+                date?,
+                match lang {
+                    Lang::Japanese => ja,
+                    Lang::English => en,
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+    days.sort();
+    days
+}
+
+/// Day of March of the vernal equinox in JST, within [`EQUINOX_YEARS`].
+fn vernal_equinox(year: i32) -> Option<u32> {
+    let (.., base, _) = equinox_bases(year)?;
+    Some(equinox_day(year, base))
+}
+
+/// Day of September of the autumnal equinox in JST; see [`vernal_equinox`].
+fn autumnal_equinox(year: i32) -> Option<u32> {
+    let (.., base) = equinox_bases(year)?;
+    Some(equinox_day(year, base))
+}
+
+fn equinox_bases(year: i32) -> Option<(i32, i32, f64, f64)> {
+    EQUINOX_BASES
+        .into_iter()
+        .find(|(first, last, ..)| (*first..=*last).contains(&year))
+}
+
+fn equinox_day(year: i32, base: f64) -> u32 {
+    // Integer division truncates toward zero, as the published formula does.
+    let leaps = if year < 1980 {
+        (year - 1983) / 4
+    } else {
+        (year - 1980) / 4
+    };
+    (base + 0.242194 * (year - 1980) as f64 - leaps as f64) as u32
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        jp_holidays::{autumnal_equinox, national_holidays, vernal_equinox, EQUINOX_YEARS},
+        Lang,
+    };
+
+    #[test]
+    fn test_equinoxes() {
+        assert_eq!(vernal_equinox(1960), Some(20));
+        assert_eq!(vernal_equinox(2024), Some(20));
+        assert_eq!(vernal_equinox(2025), Some(20));
+        assert_eq!(vernal_equinox(2026), Some(20));
+        assert_eq!(vernal_equinox(2023), Some(21));
+        assert_eq!(autumnal_equinox(1979), Some(24));
+        assert_eq!(autumnal_equinox(2012), Some(22));
+        assert_eq!(autumnal_equinox(2024), Some(22));
+        assert_eq!(autumnal_equinox(2025), Some(23));
+        assert_eq!(vernal_equinox(2200), None);
+        let (first, last) = EQUINOX_YEARS;
+        assert!(vernal_equinox(first).is_some() && autumnal_equinox(last).is_some());
+        assert!(vernal_equinox(first - 1).is_none() && autumnal_equinox(last + 1).is_none());
+    }
+
+    #[test]
+    fn test_national_holidays() {
+        let dates = |year| {
+            national_holidays(year, Lang::Japanese)
+                .into_iter()
+                .map(|(d, _)| d.format("%m/%d").to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dates(2024),
+            [
+                "01/01", "01/08", "02/11", "02/23", "03/20", "04/29", "05/03", "05/04", "05/05",
+                "07/15", "08/11", "09/16", "09/22", "10/14", "11/03", "11/23"
+            ]
+        );
+        assert_eq!(dates(2019).len(), 17);
+        assert!(dates(2020).contains(&"07/24".to_string()));
+        assert!(dates(1988).contains(&"04/29".to_string()));
+        assert!(national_holidays(1948, Lang::Japanese).is_empty());
+
+        let holidays = national_holidays(2019, Lang::English);
+        let enthronement = NaiveDate::from_ymd_opt(2019, 5, 1).unwrap();
+        assert!(holidays.contains(&(enthronement, "Enthronement Day")));
+    }
+}
@@ -1,62 +1,621 @@
 use std::{
-    collections::HashMap,
-    env,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     error::Error,
-    fs::File,
-    io::{BufRead, BufReader, Cursor, Read},
+    fs,
+    io::{self, IsTerminal, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
 };
 
-use ansi_term::{Colour, Style};
-use chrono::{Datelike, Local, NaiveDate};
-use clap::{builder::PossibleValue, Args, Parser, ValueEnum};
+use agenda::{
+    format_agenda, format_conflicts, format_holidays, format_search, format_sources,
+    format_upcoming, window_end,
+};
+use business::count_business_days;
+pub use business::{
+    add_business_days, is_business_day, next_business_day, prev_business_day, roll, RollConvention,
+    WorkWeek,
+};
+use caps::{escapes, parse_cap, CapOverride, Caps, ColorChoice};
+use chrono::{Datelike, Days, NaiveDate, Utc, Weekday};
+use clap::{builder::PossibleValue, Args, Parser, Subcommand, ValueEnum};
+pub use config::parse_config;
+use config::{export_config, parse_path, ConfigCommand};
 use consts::{
     ENGLISH_MONTH_NAMES, ENGLISH_WEEK_NAMES, JAPANESE_LUNAR_MONTH_NAMES, JAPANESE_WEEK_NAMES,
 };
-use encoding_rs::SHIFT_JIS;
-use itertools::izip;
-use months_parser::{parse_months, Months};
+use cron::{parse_cron, CronSchedule};
+use date_parser::{parse_date, parse_strftime, parse_weekday, parse_year_month, parse_years};
+use deadline::{format_countdown, load_deadlines};
+use decorator::run_decorator;
+use emoji::{pad_marker, parse_emoji, MARKER_WIDTH};
+use events::EventsFile;
+pub use filter::DayFilter;
+use footnote::{cell_marker, Footnotes};
+use gitlog::commit_counts;
+#[doc(hidden)]
+pub use grid::validate_grid;
+use holiday::{default_holiday_file, forget_missing_holiday_file, DateFormat};
+pub use holiday::{Category, Holiday, HolidayInfo};
+use ics::format_ics;
+use info::format_info;
+use locale::default_first_weekday;
+use months_parser::{parse_month_selection, MonthSelection};
+use names::{truncate, NameFit};
+use notify::{format_notification, send_notification};
+use nu_ansi_term::{Color, Style};
+use observance::Observance;
+use oncalendar::{parse_oncalendar, OnCalendar};
+use output::{Output, OutputEncoding};
+use paths::{holiday_file, holiday_file_candidates, state_dir};
+use pick::{load_last_viewed, pick, save_last_viewed, Edit, PickOutcome, PickView};
+use query::{query, QueryFormat};
+pub use render::{
+    Calendar, CsvRenderer, Day, HtmlRenderer, JsonRenderer, MarkdownRenderer, Month, Renderer,
+    SvgRenderer,
+};
+use seq::print_seq;
+use source::{configured_sources, load_sources, parse_holiday_file, DateSource};
+use sun::{extreme_days, SunConfig};
+use theme::{layer, parse_colour, parse_style, Palette, Theme};
+use timeline::format_timeline;
+use trace::Tracer;
+use tz::{parse_tz, Zone};
+use unicode_width::UnicodeWidthStr;
+use version::format_version;
+use wall::format_wall;
+use watch::FileWatcher;
+use weeknum::{WeekLabel, WeekNumbers};
+use weekplan::{format_week_planner, parse_hours, week_start};
+#[cfg(feature = "xlsx")]
+use xlsx::write_xlsx;
+use yearmap::format_year_heatmap;
 
+mod agenda;
+#[doc(hidden)]
+pub mod bench;
+mod business;
+mod cache;
+mod caps;
+mod clipboard;
+mod config;
 mod consts;
+mod cron;
+mod date_parser;
+mod deadline;
+mod decorator;
+mod emoji;
+mod events;
+mod filter;
+mod footnote;
+mod gitlog;
+mod grid;
+mod holiday;
+mod ics;
+mod info;
+mod jp_holidays;
+mod locale;
+mod lunisolar;
 mod months_parser;
+mod names;
+mod notify;
+mod observance;
+mod oncalendar;
+mod output;
+mod overrides;
+mod paths;
+mod pick;
+mod query;
+mod render;
+mod seq;
+mod source;
+mod sun;
+mod theme;
+mod timeline;
+mod trace;
+mod tz;
+mod version;
+mod wall;
+mod watch;
+mod weeknum;
+mod weekplan;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+mod yearmap;
 
 type LibResult<T> = Result<T, Box<dyn Error>>;
 
+/// The first year calp shows and computes holidays for.
+pub const MIN_YEAR: i32 = 1;
+/// The last year calp shows and computes holidays for.
+pub const MAX_YEAR: i32 = 9999;
+/// January 1 of [`MIN_YEAR`].
+pub const MIN_DATE: NaiveDate = NaiveDate::from_ymd_opt(MIN_YEAR, 1, 1).unwrap();
+/// December 31 of [`MAX_YEAR`].
+pub const MAX_DATE: NaiveDate = NaiveDate::from_ymd_opt(MAX_YEAR, 12, 31).unwrap();
+
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 pub struct Config {
-    /// Selected Months(1-12) e.g. 1,3,5 1,3-5,12
-    #[arg(short, value_name = "MONTHS", value_parser=parse_months)]
-    months: Option<Months>,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Year (1-9999)
-    #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(1..=9999))]
-    year: Option<i32>,
+    /// Every option value in effect, as exported by `config export`
+    #[arg(skip)]
+    effective: toml::Table,
+
+    /// Selected Months(1-12) e.g. 1,3,5 1,3-5,12, or relative to this month e.g. -1 +2 -1..+1; repeat to add more
+    #[arg(short, value_name = "MONTHS", value_parser=parse_month_selection, allow_hyphen_values = true)]
+    months: Vec<MonthSelection>,
+
+    /// All `-m` values merged
+    #[arg(skip)]
+    month_selection: Option<MonthSelection>,
+
+    /// Years (1-9999) e.g. 2025, 2024 2025 or 2024-2026
+    #[arg(value_name = "YEAR", value_parser = parse_years)]
+    year: Vec<RangeInclusive<i32>>,
 
     /// Show whole current year
     #[arg(short='y', long="year", conflicts_with_all=&["months", "year"])]
     cur_year: bool,
 
+    /// Show the previous, current and next month side by side
+    #[arg(short = '3', long, conflicts_with_all = ["months", "cur_year"])]
+    three: bool,
+
+    /// Show a quarter of the year: 1 is January-March
+    #[arg(long, value_name = "1-4", value_parser = clap::value_parser!(u32).range(1..=4), conflicts_with_all = ["months", "cur_year", "three", "from"])]
+    quarter: Option<u32>,
+
+    /// Show a half of the year: 1 is January-June
+    #[arg(long, value_name = "1|2", value_parser = clap::value_parser!(u32).range(1..=2), conflicts_with_all = ["months", "cur_year", "three", "from", "quarter"])]
+    half: Option<u32>,
+
+    /// Use Japanese fiscal years (年度) from April: YEAR, --quarter and --half count from April
+    #[arg(long, conflicts_with_all = ["months", "three", "from"])]
+    fiscal: bool,
+
+    /// First month of a range that may span years e.g. 2024-11 (with --to)
+    #[arg(long, value_name = "YYYY-MM", value_parser = parse_year_month, requires = "to", conflicts_with_all = ["months", "year", "cur_year", "three"])]
+    from: Option<(i32, u32)>,
+
+    /// Last month of the --from range e.g. 2025-03
+    #[arg(long, value_name = "YYYY-MM", value_parser = parse_year_month, requires = "from")]
+    to: Option<(i32, u32)>,
+
+    /// Also show N months after the selected months
+    #[arg(short = 'A', long, value_name = "N", default_value_t = 0)]
+    after: u32,
+
+    /// Also show N months before the selected months
+    #[arg(short = 'B', long, value_name = "N", default_value_t = 0)]
+    before: u32,
+
+    /// Config file (default: calp/config.toml in the platform config directory, e.g. ~/.config)
+    #[arg(long, value_name = "FILE", env = "CALP_CONFIG", global = true)]
+    config: Option<String>,
+
+    /// Named profile from the config file
+    #[arg(long, value_name = "NAME", env = "CALP_PROFILE", global = true)]
+    profile: Option<String>,
+
     /// Language
-    #[arg(short, long, value_parser=clap::value_parser!(Lang), default_value="ja")]
+    #[arg(short, long, value_parser=clap::value_parser!(Lang), default_value="ja", global = true)]
     lang: Lang,
 
+    /// Don't print warnings; errors still go to stderr and data to stdout
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Write the timing of each pipeline stage (load, observances, render, ...) to stderr as JSON lines
+    #[arg(long, global = true)]
+    trace_json: bool,
+
+    /// Parse the holiday files again instead of reading them from the cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Timezone e.g. Europe/Berlin (default: system timezone)
+    #[arg(long, value_name = "TZ", value_parser = parse_tz, global = true)]
+    tz: Option<chrono_tz::Tz>,
+
+    /// First day of the week e.g. sun, mon (default: derived from the locale)
+    #[arg(long, value_name = "WEEKDAY", value_parser = parse_weekday)]
+    first_weekday: Option<Weekday>,
+
+    /// Start weeks on Monday; same as --first-weekday mon
+    #[arg(long, conflicts_with = "first_weekday")]
+    monday: bool,
+
+    /// Label each week row with its week-of-month index (第1週… / W1…)
+    #[arg(long, conflicts_with = "week_numbers")]
+    week_of_month: bool,
+
+    /// Label each week row with its week number: iso or us
+    #[arg(long, value_name = "SCHEME", value_parser = clap::value_parser!(WeekNumbers))]
+    week_numbers: Option<WeekNumbers>,
+
+    /// Label each week row with its project week, PW1 being the week of DATE e.g. a project kickoff
+    #[arg(long, value_name = "DATE", value_parser = parse_date, conflicts_with_all = ["week_numbers", "week_of_month"])]
+    week_anchor: Option<NaiveDate>,
+
+    /// Number each day's holidays/events with a superscript marker and list them below
+    #[arg(long)]
+    footnotes: bool,
+
+    /// Append each month's holiday count to its header in year view e.g. (祝2)
+    #[arg(long)]
+    badges: bool,
+
+    /// Widen day cells to leave room for a marker after each day number
+    #[arg(long)]
+    wide: bool,
+
+    /// Marker drawn after days of a category e.g. national=🎌,personal=✈️ (implies --wide)
+    #[arg(long, value_name = "CATEGORY=EMOJI", value_delimiter = ',', value_parser = parse_emoji)]
+    emoji: Vec<(Category, String)>,
+
+    /// Fill blank cells with the neighboring months' days, dimmed
+    #[arg(long)]
+    spillover: bool,
+
+    /// Draw each day as the number of days left until DATE, advent-calendar style; later days show as --
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    countdown_to: Option<NaiveDate>,
+
+    /// Fail if any line of the grid is not exactly as wide as its row of months
+    #[arg(long)]
+    check_layout: bool,
+
+    /// Months per row (default: as many as fit the terminal, or 3)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=12))]
+    columns: Option<u32>,
+
+    /// Separate whole years into quarters with labeled rules: quarter (from January, or April with --fiscal) or fiscal (from April)
+    #[arg(long, value_name = "PERIOD", value_parser = clap::value_parser!(Grouping))]
+    group: Option<Grouping>,
+
+    /// Print weekdays down the side and weeks as columns, like ncal
+    #[arg(long, conflicts_with_all = ["wide", "emoji", "spillover", "week_numbers", "week_anchor", "week_of_month"])]
+    vertical: bool,
+
+    /// Link month headers to a URL template with %Y, %y, %m, %B and %b e.g. https://wiki.example.com/%Y-%m; other % are kept
+    #[arg(long, value_name = "TEMPLATE")]
+    month_url: Option<String>,
+
+    /// Color each day's background by its number of holidays/events
+    #[arg(long)]
+    heatmap: bool,
+
+    /// Git repository whose commits per day are drawn as background intensity, relative to the busiest day shown
+    #[arg(long, value_name = "PATH")]
+    git: Option<String>,
+
+    /// Deadlines file (date,name per line) marked in the grid with a countdown below
+    #[arg(long, value_name = "FILE", value_parser = parse_path, global = true)]
+    deadlines: Option<String>,
+
+    /// Mark days on which a crontab schedule fires e.g. "0 3 1,15 * *"
+    #[arg(long, value_name = "EXPR", value_parser = parse_cron)]
+    cron: Option<CronSchedule>,
+
+    /// Mark days matching a systemd OnCalendar expression e.g. "Mon..Fri *-*-1..7 12:00"
+    #[arg(long, value_name = "EXPR", value_parser = parse_oncalendar)]
+    oncalendar: Option<OnCalendar>,
+
+    /// Command run once with the displayed dates on stdin; each output line
+    /// `date[<TAB>annotation]` marks that day and lists the annotation below
+    #[arg(long, value_name = "COMMAND")]
+    decorate: Option<String>,
+
+    /// Also copy the output, without colors, to the clipboard
+    #[arg(long, global = true)]
+    copy: bool,
+
+    /// Color theme; the colorblind presets avoid relying on red/blue contrast, and mono on color at all
+    #[arg(long, value_parser=clap::value_parser!(Theme), default_value="default", global = true)]
+    theme: Theme,
+
+    /// When to color output: auto (when stdout is a terminal or CLICOLOR_FORCE is set, unless NO_COLOR is), always, or never
+    #[arg(long, value_name = "WHEN", value_parser = clap::value_parser!(ColorChoice), default_value = "auto", global = true)]
+    color: ColorChoice,
+
+    /// Color of holidays, over the theme's: a 256-color index e.g. 196, or #RRGGBB
+    #[arg(long, value_name = "COLOR", value_parser = parse_colour, global = true)]
+    holiday_color: Option<Color>,
+
+    /// Color of Saturdays: a 256-color index, or #RRGGBB
+    #[arg(long, value_name = "COLOR", value_parser = parse_colour, global = true)]
+    saturday_color: Option<Color>,
+
+    /// Color of Sundays: a 256-color index, or #RRGGBB
+    #[arg(long, value_name = "COLOR", value_parser = parse_colour, global = true)]
+    sunday_color: Option<Color>,
+
+    /// How today is highlighted instead of reversed e.g. bold,underline, 208 or bg=#303030
+    #[arg(long, value_name = "STYLE", value_parser = parse_style, global = true)]
+    today_style: Option<Style>,
+
+    /// Override detected terminal capabilities e.g. unicode=false,color=8,hyperlinks=false,ambiguous=wide,width=70
+    #[arg(long, value_name = "CAP=VALUE", value_delimiter = ',', value_parser = parse_cap, global = true)]
+    caps: Vec<CapOverride>,
+
+    /// Output encoding
+    #[arg(long, value_parser=clap::value_parser!(OutputEncoding), default_value="utf8", global = true)]
+    output_encoding: OutputEncoding,
+
+    /// Output format
+    #[arg(long, value_parser=clap::value_parser!(OutputFormat), default_value="grid")]
+    format: OutputFormat,
+
+    /// Redraw whenever a holiday, override or deadline file changes, and at midnight
+    #[arg(long)]
+    watch: bool,
+
+    /// File written by binary formats e.g. --format xlsx
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    /// Hour rows shown by --format week-planner e.g. 8-18
+    #[arg(long, value_name = "START-END", value_parser = parse_hours, default_value = "9-18")]
+    hours: RangeInclusive<u32>,
+
+    /// Print the next N holidays/events above the calendar
+    #[arg(long, value_name = "N")]
+    upcoming: Option<usize>,
+
     #[command(flatten)]
     file_config: FileConfig,
+
+    #[command(flatten)]
+    day_filter: DayFilter,
+
+    #[command(flatten)]
+    name_fit: NameFit,
+
+    #[command(flatten)]
+    sun: SunConfig,
+
+    #[command(flatten)]
+    work_week: WorkWeek,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List holidays and events within the coming days
+    Upcoming {
+        /// Number of days to look ahead
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+
+    /// List every holiday/event in a year (default: current year)
+    Holidays {
+        /// Year (1-9999)
+        #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: Option<i32>,
+
+        /// Append "in N days"/"N days ago" to each entry
+        #[arg(long)]
+        relative: bool,
+
+        /// Reference date for --relative (YYYY-MM-DD, default: today)
+        #[arg(long, value_name = "DATE", value_parser = parse_date, requires = "relative")]
+        today: Option<NaiveDate>,
+
+        /// List the loaded files/observances with entry counts, coverage and modification time instead
+        #[arg(long, conflicts_with_all = ["year", "relative"])]
+        sources: bool,
+    },
+
+    /// Search holiday/event names across all loaded files and observances
+    Search {
+        /// Text to look for (case-insensitive)
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+
+        /// Only entries in this year
+        #[arg(long, value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: Option<i32>,
+    },
+
+    /// List days on which entries from different files/observances overlap
+    Conflicts {
+        /// Year (1-9999, default: current year)
+        #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: Option<i32>,
+    },
+
+    /// Look up weekday, holidays and business-day flag for each date
+    Query {
+        /// Dates (YYYY-MM-DD)
+        #[arg(value_name = "DATE")]
+        dates: Vec<String>,
+
+        /// Read dates from stdin, one per line
+        #[arg(long)]
+        stdin: bool,
+
+        /// Output format
+        #[arg(long, value_parser=clap::value_parser!(QueryFormat), default_value="text")]
+        format: QueryFormat,
+    },
+
+    /// Send a desktop notification of today's holidays/events and upcoming deadlines
+    Notify {
+        /// Include deadlines due within this many days
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+
+    /// Show exactly the given months side by side, in order, e.g. 2024-12 2025-01 2026-03
+    Show {
+        /// Months to show
+        #[arg(value_name = "YYYY-MM", value_parser = parse_year_month, required = true)]
+        months: Vec<(i32, u32)>,
+    },
+
+    /// Show a year as a 12×31 matrix of holidays, weekends and bridge days
+    Heatmap {
+        /// Year (1-9999)
+        #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: i32,
+    },
+
+    /// Render a printable wall calendar, one framed page per month
+    Wall {
+        /// Year (1-9999)
+        #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: i32,
+
+        /// Put all twelve months on a single page
+        #[arg(long)]
+        one_page: bool,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+
+    /// Export a year's holidays/events for other calendar apps
+    Export {
+        /// Write an iCalendar (.ics) file, for Google Calendar or Outlook
+        #[arg(long, required = true)]
+        ics: bool,
+
+        /// Year (1-9999, default: current year)
+        #[arg(value_name = "YEAR", value_parser=clap::value_parser!(i32).range(MIN_YEAR as i64..=MAX_YEAR as i64))]
+        year: Option<i32>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+
+    /// Pick a date on an interactive calendar and print it (arrows/hjkl or click, p/n or wheel for months, t today, a/e add/edit event, / search, Enter, q)
+    Pick {
+        /// Date selected initially (default: where the last pick left off)
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Start at today instead of the last viewed date
+        #[arg(long, conflicts_with = "date")]
+        fresh: bool,
+
+        /// UTF-8 events file shown and edited with a/e, created if missing
+        #[arg(long, value_name = "FILE")]
+        events: Option<PathBuf>,
+
+        /// Format of the printed date
+        #[arg(long, value_name = "FORMAT", value_parser = parse_strftime, default_value = "%Y-%m-%d")]
+        date_format: String,
+    },
+
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// Describe a single day (default: today)
+    Info {
+        /// Date (YYYY-MM-DD)
+        #[arg(value_name = "DATE", value_parser = parse_date)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Count business days in a range, both ends included
+    Workdays {
+        /// First date (YYYY-MM-DD)
+        #[arg(value_name = "FROM", value_parser = parse_date)]
+        from: NaiveDate,
+
+        /// Last date (YYYY-MM-DD)
+        #[arg(value_name = "TO", value_parser = parse_date)]
+        to: NaiveDate,
+    },
+
+    /// Print the date N business days after DATE, or before it for negative N
+    AddBusinessDays {
+        /// Start date (YYYY-MM-DD)
+        #[arg(value_name = "DATE", value_parser = parse_date)]
+        date: NaiveDate,
+
+        /// Business days to move, half days counting 0.5
+        #[arg(value_name = "N", allow_negative_numbers = true)]
+        n: i64,
+    },
+
+    /// Print every date in a range
+    Seq {
+        /// First date (YYYY-MM-DD)
+        #[arg(value_name = "FROM", value_parser = parse_date)]
+        from: NaiveDate,
+
+        /// Last date (YYYY-MM-DD)
+        #[arg(value_name = "TO", value_parser = parse_date)]
+        to: NaiveDate,
+
+        /// Only business days
+        #[arg(long)]
+        business_days: bool,
+
+        /// Only the given weekdays e.g. mon,fri
+        #[arg(long, value_name = "WEEKDAYS", value_delimiter = ',', value_parser = parse_weekday)]
+        weekday: Vec<Weekday>,
+
+        /// Output date format (strftime)
+        #[arg(long, default_value = "%Y-%m-%d", value_parser = parse_strftime)]
+        format: String,
+    },
+
+    /// Print the version
+    Version {
+        /// Also print the target, cargo features and built-in holiday data
+        #[arg(short, long)]
+        verbose: bool,
+    },
 }
 
 #[derive(Debug, Args)]
 struct FileConfig {
-    /// Input Japanese national holiday file
-    #[arg(short, long, value_name = "FILE")]
-    file: Option<String>,
+    /// Input Japanese national holiday file, or iCalendar file ending in .ics, repeatable (`#include path` pulls in other files); FILE=CATEGORY styles its uncategorized lines e.g. work.csv=company
+    #[arg(short, long, value_name = "FILE", value_parser = parse_holiday_file, global = true)]
+    file: Vec<(PathBuf, Category)>,
+
+    /// Command whose output is read as a holiday file, or as iCalendar e.g. a CalDAV calendar fetched with curl; uncategorized entries are personal, repeatable
+    #[arg(long = "source-command", value_name = "COMMAND", global = true)]
+    source_commands: Vec<String>,
 
     /// Japanese national holiday file encoding
-    #[arg(short, long, value_parser=clap::value_parser!(Encoding), default_value="sjis")]
+    #[arg(short, long, value_parser=clap::value_parser!(Encoding), default_value="sjis", global = true)]
     encoding: Encoding,
+
+    /// Date column of holiday files: ymd (2025/01/01), or excel to also accept serial numbers e.g. 45658
+    // Not global: `pick --date-format` is the format of the printed date
+    #[arg(long, value_name = "FORMAT", value_parser=clap::value_parser!(DateFormat), default_value="ymd")]
+    date_format: DateFormat,
+
+    /// Categories to show e.g. national,company (default: all)
+    #[arg(long, value_name = "CATEGORIES", value_delimiter = ',', global = true)]
+    show_categories: Option<Vec<Category>>,
+
+    /// Computed observances to overlay e.g. japan,christian (japan is used when no holiday file is found)
+    #[arg(long, value_name = "OBSERVANCES", value_delimiter = ',', global = true)]
+    observances: Vec<Observance>,
+
+    /// Override file of `+date,name` / `-date[,name]` lines applied after all other sources, repeatable
+    #[arg(long = "override", value_name = "FILE", value_parser = parse_path, global = true)]
+    overrides: Vec<String>,
 }
 
+/// Text encoding of a holiday file.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Encoding {
+pub enum Encoding {
+    /// What the Cabinet Office publishes its list in
     ShiftJis,
     Utf8,
 }
@@ -74,6 +633,75 @@ impl ValueEnum for Encoding {
     }
 }
 
+/// Periods that `--group` separates whole-year output into.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Grouping {
+    Quarter,
+    Fiscal,
+}
+
+impl ValueEnum for Grouping {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Grouping::Quarter, Grouping::Fiscal]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Grouping::Quarter => PossibleValue::new("quarter"),
+            Grouping::Fiscal => PossibleValue::new("fiscal"),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum OutputFormat {
+    Grid,
+    Markdown,
+    Html,
+    Json,
+    Svg,
+    Csv,
+    Tsv,
+    Timeline,
+    WeekPlanner,
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Grid,
+            Self::Markdown,
+            Self::Html,
+            Self::Json,
+            Self::Svg,
+            Self::Csv,
+            Self::Tsv,
+            Self::Timeline,
+            Self::WeekPlanner,
+            #[cfg(feature = "xlsx")]
+            Self::Xlsx,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            OutputFormat::Grid => PossibleValue::new("grid"),
+            OutputFormat::Markdown => PossibleValue::new("markdown"),
+            OutputFormat::Html => PossibleValue::new("html"),
+            OutputFormat::Json => PossibleValue::new("json"),
+            OutputFormat::Svg => PossibleValue::new("svg"),
+            OutputFormat::Csv => PossibleValue::new("csv"),
+            OutputFormat::Tsv => PossibleValue::new("tsv"),
+            OutputFormat::Timeline => PossibleValue::new("timeline"),
+            OutputFormat::WeekPlanner => PossibleValue::new("week-planner"),
+            #[cfg(feature = "xlsx")]
+            OutputFormat::Xlsx => PossibleValue::new("xlsx"),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Lang {
     Japanese,
@@ -95,149 +723,1245 @@ impl ValueEnum for Lang {
 
 struct FormatConfig {
     show_year: bool,
+    /// Year banners name fiscal years (年度), which start in April
+    fiscal: bool,
+    /// Rows start at each quarter, under a labeled rule
+    group: Option<Grouping>,
     lang: Lang,
+    first_weekday: Weekday,
+    week_label: Option<WeekLabel>,
+    /// Append the number of holidays to each month header
+    badges: bool,
+    spillover: bool,
+    /// Days are drawn as the days left until this date instead of their day of month
+    countdown_to: Option<NaiveDate>,
+    /// Weekdays as rows and weeks as columns
+    vertical: bool,
+    /// Day cells reserve `emoji::MARKER_WIDTH` extra columns
+    wide: bool,
+    /// Fail on grid lines whose display width is not their row's
+    check_layout: bool,
+    /// Months side by side: `--columns`, as many as fit the terminal, or 3
+    months_per_row: usize,
+    /// Marker drawn after days of each category in wide cells
+    emoji: HashMap<Category, String>,
+    /// strftime template linked from each month header
+    month_url: Option<String>,
+    /// Days drawn underlined
+    marks: HashSet<NaiveDate>,
+    /// Number of entries per day, drawn as background intensity
+    heat: HashMap<NaiveDate, usize>,
+    /// Days on which the UTC offset changes, drawn bold
+    transitions: HashSet<NaiveDate>,
+    /// Days covered by multi-day entries, drawn with the span style
+    spans: HashSet<NaiveDate>,
+    /// Footnote number per day, marked after the day number and listed below the grid
+    footnotes: HashMap<NaiveDate, usize>,
+    palette: Palette,
+}
+
+impl FormatConfig {
+    /// A single month with no badges, markers or per-day decorations.
+    fn plain(lang: Lang, first_weekday: Weekday, palette: Palette) -> FormatConfig {
+        FormatConfig {
+            show_year: true,
+            fiscal: false,
+            group: None,
+            lang,
+            first_weekday,
+            week_label: None,
+            badges: false,
+            spillover: false,
+            countdown_to: None,
+            vertical: false,
+            wide: false,
+            check_layout: false,
+            months_per_row: 3,
+            emoji: HashMap::new(),
+            month_url: None,
+            marks: HashSet::new(),
+            heat: HashMap::new(),
+            transitions: HashSet::new(),
+            spans: HashSet::new(),
+            footnotes: HashMap::new(),
+            palette,
+        }
+    }
 }
 
-struct HolidayInfo {
-    info: HashMap<i32, HashMap<u32, u32>>,
+/// Runs the command `config` describes once. `--watch` is left to the
+/// caller, which redraws after each [`Watch`].
+pub fn run(mut config: Config) -> LibResult<()> {
+    config.month_selection = MonthSelection::merge(&config.months)?;
+    run_once(config)
 }
 
-impl HolidayInfo {
-    fn new() -> HolidayInfo {
-        HolidayInfo {
-            info: HashMap::new(),
-        }
+impl Config {
+    /// Whether `--watch` was given.
+    pub fn watch(&self) -> bool {
+        self.watch
     }
+}
 
-    fn is_holiday(&self, year: i32, month: u32, day: u32) -> bool {
-        let b = self
-            .info
-            .get(&year)
-            .and_then(|m| m.get(&month))
-            .unwrap_or(&0);
-        b & (1 << (day - 1)) != 0
+/// What `--watch` waits for between redraws: a change to one of the files
+/// a [`Config`] reads, or midnight in its zone, when today moves on.
+pub struct Watch {
+    files: FileWatcher,
+    midnight: Option<SystemTime>,
+}
+
+impl Watch {
+    pub fn new(config: &Config) -> LibResult<Watch> {
+        let months = MonthSelection::merge(&config.months)?;
+        Ok(Watch {
+            files: FileWatcher::new(watched_paths(config, months.as_ref())),
+            midnight: Zone::new(config.tz)
+                .next_midnight(Utc::now())
+                .map(Into::into),
+        })
     }
 
-    fn add(&mut self, date: NaiveDate) {
-        let (year, month, day) = (date.year(), date.month(), date.day());
-        let m = self.info.entry(year).or_default();
-        let d = m.entry(month).or_insert(0);
-        *d |= 1 << (day - 1);
+    /// Blocks until the next redraw is due.
+    pub fn wait(mut self) {
+        self.files.wait(self.midnight);
+        forget_missing_holiday_file();
     }
 }
 
-pub fn run(config: Config) -> LibResult<()> {
-    // println!("{:#?}", config);
-    let today = Local::now().date_naive();
-    let holiday_info = load_holiday_file(&config.file_config)?;
-    let show_whole_year = config.cur_year || (config.year.is_some() && config.months.is_none());
+/// The files `--watch` redraws on.
+fn watched_paths(config: &Config, months: Option<&MonthSelection>) -> Vec<PathBuf> {
+    let today = Zone::new(config.tz).today();
+    let years = data_years(config, months, today);
+    let sources = configured_sources(&config.file_config, years, config.lang);
+    let mut paths = sources
+        .iter()
+        .filter_map(|s| s.path())
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+    paths.extend(config.deadlines.iter().map(PathBuf::from));
+    if config.file_config.file.is_empty() {
+        // Picks up a holiday file downloaded while watching.
+        paths.extend(holiday_file_candidates());
+    }
+    paths
+}
 
-    let year = config.year.unwrap_or_else(|| today.year());
-    let months = if show_whole_year {
-        vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
+/// Merges `sources` and applies the work week and `--show-categories`.
+fn load_holiday_info(
+    sources: &[Box<dyn DateSource>],
+    tracer: &Tracer,
+    work_week: &WorkWeek,
+    show_categories: Option<&[Category]>,
+    cache: bool,
+) -> LibResult<HolidayInfo> {
+    let mut holiday_info = if cache {
+        cache::load_cached(sources, tracer)?
     } else {
-        config.months.unwrap_or_else(|| vec![today.month()])
+        load_sources(sources, tracer)?
     };
-    let format_config = FormatConfig {
-        show_year: months.len() == 1,
-        lang: config.lang,
-    };
-    print_months(year, &months, format_config, today, &holiday_info);
-
-    Ok(())
+    holiday_info.set_work_week(work_week.clone());
+    if let Some(categories) = show_categories {
+        holiday_info.retain_categories(categories);
+    }
+    Ok(holiday_info)
 }
 
-fn load_holiday_file(file_config: &FileConfig) -> LibResult<HolidayInfo> {
-    let (load_default, file) = match &file_config.file {
-        Some(v) => (false, v.clone()),
-        None => match env::var("HOME") {
-            Ok(home) => (true, format!("{home}/.calp_shuku")),
-            _ => return Ok(HolidayInfo::new()),
+fn run_once(config: Config) -> LibResult<()> {
+    // println!("{:#?}", config);
+    #[cfg(feature = "xlsx")]
+    let writes_file = config.format == OutputFormat::Xlsx;
+    #[cfg(not(feature = "xlsx"))]
+    let writes_file = false;
+    if config.output.is_some() && !writes_file {
+        return Err("--output is only used by --format xlsx".into());
+    }
+    let zone = Zone::new(config.tz);
+    let today = zone.today();
+    let first_weekday = resolve_first_weekday(&config);
+    let caps = Caps::detect(config.color, &config.caps);
+    let palette = palette(&config);
+    let mut out = Output::new(config.output_encoding, caps, config.copy);
+    if let Some(Command::Config {
+        action: ConfigCommand::Export { format, output },
+    }) = &config.command
+    {
+        let exported = export_config(&config.effective, *format)?;
+        match output {
+            Some(path) => fs::write(path, exported).map_err(|e| format!("{path}: {e}"))?,
+            None => write!(out, "{exported}")?,
+        }
+        out.finish()?;
+        return Ok(());
+    }
+    if let Some(Command::Version { verbose }) = config.command {
+        for line in format_version(verbose) {
+            writeln!(out, "{line}")?;
+        }
+        return out.finish();
+    }
+    let tracer = Tracer::new(config.trace_json);
+    let years = data_years(&config, config.month_selection.as_ref(), today);
+    let sources = configured_sources(&config.file_config, years, config.lang);
+    if config.file_config.file.is_empty() && default_holiday_file().is_none() {
+        hint_holiday_file(config.quiet, config.lang);
+    }
+    let mut holiday_info = load_holiday_info(
+        &sources,
+        &tracer,
+        &config.work_week,
+        config.file_config.show_categories.as_deref(),
+        !config.no_cache,
+    )?;
+    let render = config.command.is_some().then(|| tracer.span("render"));
+    match config.command {
+        Some(Command::Upcoming { days }) => {
+            for line in format_agenda(
+                &holiday_info,
+                today,
+                days,
+                &config.day_filter,
+                &config.name_fit,
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Query {
+            dates,
+            stdin,
+            format,
+        }) => {
+            query(&mut out, &holiday_info, &dates, stdin, format, config.lang)?;
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Seq {
+            from,
+            to,
+            business_days,
+            weekday,
+            format,
+        }) => {
+            print_seq(
+                &mut out,
+                &holiday_info,
+                from,
+                to,
+                business_days,
+                &weekday,
+                &config.day_filter,
+                &format,
+            )?;
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Info { date }) => {
+            for line in format_info(
+                &holiday_info,
+                date.unwrap_or(today),
+                zone,
+                &config.sun,
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Holidays {
+            year,
+            relative,
+            today: reference,
+            sources,
+        }) => {
+            if sources {
+                for line in format_sources(&holiday_info, config.lang) {
+                    writeln!(out, "{line}")?;
+                }
+                out.finish()?;
+                return Ok(());
+            }
+            let year = year.unwrap_or_else(|| today.year());
+            warn_uncovered(&holiday_info, year, config.quiet, config.lang);
+            for line in format_holidays(
+                &holiday_info,
+                year,
+                &config.day_filter,
+                &config.name_fit,
+                relative.then(|| reference.unwrap_or(today)),
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Workdays { from, to }) => {
+            writeln!(out, "{}", count_business_days(&holiday_info, from..=to))?;
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::AddBusinessDays { date, n }) => {
+            let moved = add_business_days(&holiday_info, date, n)
+                .ok_or("no business day within the supported range")?;
+            writeln!(out, "{moved}")?;
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Search { pattern, year }) => {
+            for line in format_search(
+                &holiday_info,
+                &pattern,
+                year,
+                &config.day_filter,
+                &config.name_fit,
+                today,
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Conflicts { year }) => {
+            let year = year.unwrap_or_else(|| today.year());
+            warn_uncovered(&holiday_info, year, config.quiet, config.lang);
+            let range = NaiveDate::from_ymd_opt(year, 1, 1).unwrap()
+                ..=NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
+            let conflicts = holiday_info.conflicts(range).collect::<Vec<_>>();
+            for line in format_conflicts(&conflicts, config.lang) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Wall {
+            year,
+            one_page,
+            output,
+        }) => {
+            warn_uncovered(&holiday_info, year, config.quiet, config.lang);
+            let wall = format_wall(&holiday_info, year, first_weekday, one_page, config.lang);
+            match output {
+                Some(path) => fs::write(&path, wall).map_err(|e| format!("{path}: {e}"))?,
+                None => write!(out, "{wall}")?,
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Export { year, output, .. }) => {
+            let year = year.unwrap_or_else(|| today.year());
+            warn_uncovered(&holiday_info, year, config.quiet, config.lang);
+            let stamp = Utc::now().naive_utc();
+            let ics = format_ics(&holiday_info, year, &config.day_filter, stamp);
+            match output {
+                Some(path) => fs::write(&path, ics).map_err(|e| format!("{path}: {e}"))?,
+                None => out.write_raw(ics.as_bytes())?,
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Heatmap { year }) => {
+            warn_uncovered(&holiday_info, year, config.quiet, config.lang);
+            for line in format_year_heatmap(&holiday_info, year, &palette, config.lang) {
+                writeln!(out, "{line}")?;
+            }
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Pick {
+            date,
+            fresh,
+            events,
+            date_format,
+        }) => {
+            let events = events.map(|path| EventsFile { path });
+            if let Some(events) = &events {
+                for (date, holiday) in events.load()? {
+                    holiday_info.add(date, holiday);
+                }
+            }
+            let watcher = FileWatcher::new(
+                sources
+                    .iter()
+                    .filter_map(|s| s.path())
+                    .map(Path::to_path_buf)
+                    .chain(events.iter().map(|e| e.path.clone())),
+            );
+            let (work_week, show_categories, cache) = (
+                config.work_week,
+                config.file_config.show_categories,
+                !config.no_cache,
+            );
+            let mut view = PickCalendar {
+                format_config: FormatConfig {
+                    marks: HashSet::from([today]),
+                    ..FormatConfig::plain(config.lang, first_weekday, palette)
+                },
+                holiday_info: &mut holiday_info,
+                events,
+                caps,
+                watcher,
+                load: Box::new(move || {
+                    load_holiday_info(
+                        &sources,
+                        &Tracer::new(false),
+                        &work_week,
+                        show_categories.as_deref(),
+                        cache,
+                    )
+                }),
+            };
+            let start = date
+                .or_else(|| if fresh { None } else { load_last_viewed() })
+                .unwrap_or(today);
+            let outcome = pick(&mut view, start, today, first_weekday, config.lang)?;
+            let picked = match outcome {
+                PickOutcome::Picked(date) => date,
+                PickOutcome::Cancelled(date) => {
+                    save_last_viewed(date);
+                    return Err("cancelled".into());
+                }
+            };
+            save_last_viewed(picked);
+            writeln!(out, "{}", picked.format(&date_format))?;
+            out.finish()?;
+            return Ok(());
+        }
+        Some(Command::Notify { days }) => {
+            let deadlines = match &config.deadlines {
+                Some(path) => load_deadlines(path)?,
+                None => vec![],
+            };
+            if let Some((title, body)) =
+                format_notification(&holiday_info, today, &deadlines, days, config.lang)
+            {
+                send_notification(&title, &body)?;
+            }
+            return Ok(());
+        }
+        // Printed before any holiday file is loaded.
+        Some(Command::Config { .. })
+        | Some(Command::Version { .. })
+        | Some(Command::Show { .. })
+        | None => (),
+    }
+    drop(render);
+    let compute = tracer.span("compute");
+
+    // (number, months long) of --quarter or --half
+    let period = config
+        .quarter
+        .map(|q| (q, 3))
+        .or(config.half.map(|h| (h, 6)));
+    let show_whole_year = config.cur_year
+        || (config.fiscal && period.is_none())
+        || (!config.year.is_empty()
+            && config.month_selection.is_none()
+            && !config.three
+            && period.is_none());
+
+    let selected_years = if config.year.is_empty() {
+        vec![if config.fiscal {
+            fiscal_year(today)
+        } else {
+            today.year()
+        }]
+    } else {
+        config.year.iter().flat_map(|r| r.clone()).collect()
+    };
+    let year = selected_years[0];
+    // (year, month) of each displayed month, which may span years.
+    let mut months: Vec<(i32, u32)> = if let Some(Command::Show { months }) = &config.command {
+        let mut months = months.clone();
+        months.sort_unstable();
+        months.dedup();
+        months
+    } else if let (Some(from), Some(to)) = (config.from, config.to) {
+        if to < from {
+            return Err(format!(
+                "--to {}-{:02} is before --from {}-{:02}",
+                to.0, to.1, from.0, from.1
+            )
+            .into());
+        }
+        (0..)
+            .map_while(|n| shift_month(from, n).filter(|m| *m <= to))
+            .collect()
+    } else if show_whole_year {
+        let years = selected_years.iter();
+        years
+            .map(|y| period_months(*y, 1, 12, config.fiscal))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(out_of_range)?
+            .concat()
+    } else if config.three {
+        (-1..=1)
+            .map(|n| shift_month((year, today.month()), n))
+            .collect::<Option<_>>()
+            .ok_or_else(out_of_range)?
+    } else if let Some((number, len)) = period {
+        selected_years
+            .iter()
+            .map(|y| period_months(*y, number, len, config.fiscal))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(out_of_range)?
+            .concat()
+    } else if let Some(MonthSelection::Relative(offsets)) = &config.month_selection {
+        if !config.year.is_empty() {
+            return Err(
+                "relative months (-m -1, +2) count from today and cannot take a YEAR".into(),
+            );
+        }
+        let current = (today.year(), today.month());
+        offsets
+            .iter()
+            .map(|n| shift_month(current, *n))
+            .collect::<Option<_>>()
+            .ok_or_else(out_of_range)?
+    } else {
+        let months = match config.month_selection {
+            Some(MonthSelection::Absolute(months)) => months,
+            _ => vec![today.month()],
+        };
+        let years = selected_years.iter();
+        years
+            .flat_map(|y| months.iter().map(|m| (*y, *m)))
+            .collect()
+    };
+    if let (Some(&first), Some(&last)) = (months.first(), months.last()) {
+        let count = |n: u32| i32::try_from(n).map_err(|_| out_of_range());
+        let before = (1..=count(config.before)?)
+            .rev()
+            .map(|n| shift_month(first, -n));
+        let after = (1..=count(config.after)?).map(|n| shift_month(last, n));
+        months = before
+            .chain(months.into_iter().map(Some))
+            .chain(after)
+            .collect::<Option<_>>()
+            .ok_or_else(out_of_range)?;
+    }
+    let years = months.iter().map(|(y, _)| *y).collect::<BTreeSet<_>>();
+    for year in &years {
+        warn_uncovered(&holiday_info, *year, config.quiet, config.lang);
+    }
+    let deadlines = match &config.deadlines {
+        Some(path) => load_deadlines(path)?,
+        None => vec![],
+    };
+    let mut marks = HashSet::new();
+    if let Some((lat, lon)) = config.sun.location() {
+        for year in &years {
+            marks.extend(
+                extreme_days(*year, lat, lon)
+                    .into_iter()
+                    .flat_map(<[_; 2]>::from),
+            );
+        }
+    }
+    marks.extend(deadlines.iter().map(|d| d.date));
+    let displayed_days = months
+        .iter()
+        .flat_map(|(y, m)| {
+            let first = NaiveDate::from_ymd_opt(*y, *m, 1).unwrap();
+            first.iter_days().take_while(move |d| d.month() == *m)
+        })
+        .collect::<Vec<_>>();
+    if let Some(cron) = &config.cron {
+        marks.extend(displayed_days.iter().filter(|d| cron.matches(**d)));
+    }
+    if let Some(event) = &config.oncalendar {
+        marks.extend(displayed_days.iter().filter(|d| event.matches(**d)));
+    }
+    let decorations = match &config.decorate {
+        Some(command) => {
+            let _span = tracer.span("decorate");
+            run_decorator(command, &displayed_days)?
+        }
+        None => BTreeMap::new(),
+    };
+    marks.extend(decorations.keys());
+    // Years given as `calp 2025 2024` are shown in that order
+    let (first_day, last_day) = (displayed_days.iter().min(), displayed_days.iter().max());
+    let conflicts = match (first_day, last_day) {
+        (Some(first), Some(last)) => holiday_info
+            .conflicts(*first..=*last)
+            .filter(|(d, _)| months.contains(&(d.year(), d.month())))
+            .collect(),
+        _ => vec![],
+    };
+    let mut heat = if config.heatmap {
+        holiday_info.entry_counts()
+    } else {
+        HashMap::new()
+    };
+    if let (Some(path), Some(first), Some(last)) = (&config.git, first_day, last_day) {
+        let _span = tracer.span("git");
+        for (date, n) in commit_counts(path, *first, *last)? {
+            *heat.entry(date).or_default() += n;
+        }
+    }
+    let heat = heat_levels(&heat, &displayed_days);
+    let mut footnotes = Footnotes::default();
+    let mut footnote_days = HashMap::new();
+    for (date, line) in conflicts
+        .iter()
+        .map(|(d, _)| *d)
+        .zip(format_conflicts(&conflicts, config.lang))
+    {
+        footnote_days.insert(date, footnotes.add(line));
+    }
+    if config.footnotes {
+        for date in &displayed_days {
+            let names = holiday_info
+                .holidays_on(*date)
+                .iter()
+                .map(|h| h.name.as_str())
+                .collect::<Vec<_>>();
+            if !names.is_empty() && !footnote_days.contains_key(date) {
+                footnote_days.insert(*date, footnotes.add(names.join(" / ")));
+            }
+        }
+    }
+    let banner_years = months
+        .iter()
+        .map(|m| banner_year(*m, config.fiscal))
+        .collect::<BTreeSet<_>>()
+        .len();
+    let mut format_config = FormatConfig {
+        // Whole years get a banner each; otherwise headers name their year
+        // when the months span years.
+        show_year: months.len() == 1 || (banner_years > 1 && !show_whole_year),
+        fiscal: config.fiscal,
+        group: config.group.filter(|_| show_whole_year),
+        lang: config.lang,
+        first_weekday,
+        week_label: match (config.week_numbers, config.week_anchor) {
+            (Some(WeekNumbers::Iso), _) => Some(WeekLabel::Iso),
+            (Some(WeekNumbers::Us), _) => Some(WeekLabel::Us),
+            (_, Some(anchor)) => Some(WeekLabel::Project(anchor)),
+            _ if config.week_of_month => Some(WeekLabel::OfMonth),
+            _ => None,
         },
+        badges: config.badges && show_whole_year,
+        spillover: config.spillover,
+        countdown_to: config.countdown_to,
+        vertical: config.vertical,
+        wide: config.wide || !config.emoji.is_empty(),
+        check_layout: config.check_layout,
+        months_per_row: 3,
+        emoji: config.emoji.into_iter().collect(),
+        month_url: config.month_url,
+        marks,
+        heat,
+        transitions: displayed_days
+            .iter()
+            .copied()
+            .filter(|d| zone.transition_on(*d).is_some())
+            .collect(),
+        spans: displayed_days
+            .iter()
+            .copied()
+            .filter(|d| {
+                holiday_info
+                    .holidays_on(*d)
+                    .iter()
+                    .any(|h| h.span.is_some())
+            })
+            .collect(),
+        footnotes: footnote_days,
+        palette,
     };
-    let f = match File::open(file) {
-        Ok(f) => f,
-        Err(e) => {
-            if load_default {
-                return Ok(HolidayInfo::new());
+    if let Some(columns) = config.columns {
+        format_config.months_per_row = columns as usize;
+    } else if let Some(width) = caps.width {
+        // As many as fit, in rows that divide a year evenly
+        let stride = month_stride(&format_config);
+        format_config.months_per_row = [12, 6, 4, 3, 2]
+            .into_iter()
+            .find(|n| n * stride <= width)
+            .unwrap_or(1);
+    }
+    // Structured formats carry only the calendar itself.
+    let structured = matches!(
+        config.format,
+        OutputFormat::Markdown
+            | OutputFormat::Html
+            | OutputFormat::Json
+            | OutputFormat::Svg
+            | OutputFormat::Csv
+            | OutputFormat::Tsv
+    );
+    if let Some(n) = config.upcoming.filter(|_| !structured) {
+        for line in format_upcoming(
+            &holiday_info,
+            today,
+            n,
+            &config.day_filter,
+            &config.name_fit,
+            config.lang,
+        ) {
+            writeln!(out, "{line}")?;
+        }
+        writeln!(out)?;
+    }
+    drop(compute);
+    let _render = tracer.span("render");
+    match config.format {
+        OutputFormat::Grid
+        | OutputFormat::Markdown
+        | OutputFormat::Html
+        | OutputFormat::Json
+        | OutputFormat::Svg
+        | OutputFormat::Csv
+        | OutputFormat::Tsv => {
+            let titles = months.iter().map(|(y, m)| {
+                let title = match config.lang {
+                    Lang::Japanese => format_header_jp(*y, *m, true),
+                    Lang::English => format_header_en(*y, *m, true),
+                };
+                (*y, *m, title.trim().to_string())
+            });
+            let mut calendar = Calendar::new(
+                &holiday_info,
+                titles,
+                first_weekday,
+                weekday_names(config.lang, first_weekday),
+                today,
+                &config.day_filter,
+            );
+            calendar.set_footnotes(&format_config.footnotes, footnotes.notes());
+            let renderer: Box<dyn Renderer> = match config.format {
+                OutputFormat::Markdown => Box::new(MarkdownRenderer),
+                OutputFormat::Html => Box::new(HtmlRenderer),
+                OutputFormat::Json => Box::new(JsonRenderer),
+                OutputFormat::Svg => Box::new(SvgRenderer),
+                OutputFormat::Csv => Box::new(CsvRenderer { delimiter: ',' }),
+                OutputFormat::Tsv => Box::new(CsvRenderer { delimiter: '\t' }),
+                _ => Box::new(GridRenderer {
+                    format_config,
+                    holiday_info: &holiday_info,
+                    today,
+                }),
+            };
+            renderer.render(&calendar, &mut out)?;
+            if structured {
+                return out.finish();
+            }
+        }
+        OutputFormat::Timeline => {
+            let (first, last) = (months[0], months[months.len() - 1]);
+            let start = NaiveDate::from_ymd_opt(first.0, first.1, 1).unwrap();
+            let end = last_day_in_month(last.0, last.1);
+            for line in format_timeline(
+                &holiday_info,
+                start,
+                end,
+                format_config.first_weekday,
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
+            }
+        }
+        OutputFormat::WeekPlanner => {
+            // The current week, or the first week of another chosen month.
+            let date = if months[0] == (today.year(), today.month()) {
+                today
             } else {
-                return Err(e.into());
+                NaiveDate::from_ymd_opt(months[0].0, months[0].1, 1).unwrap()
+            };
+            for line in format_week_planner(
+                &holiday_info,
+                week_start(date, format_config.first_weekday),
+                config.hours,
+                today,
+                config.lang,
+            ) {
+                writeln!(out, "{line}")?;
             }
         }
-    };
+        #[cfg(feature = "xlsx")]
+        OutputFormat::Xlsx => {
+            let path = config
+                .output
+                .as_deref()
+                .ok_or("--format xlsx requires --output FILE")?;
+            return write_xlsx(
+                path,
+                &holiday_info,
+                &months,
+                format_config.first_weekday,
+                config.lang,
+            );
+        }
+    }
+    if !footnotes.is_empty() && config.format == OutputFormat::Grid {
+        writeln!(out)?;
+        for line in footnotes.lines(&config.name_fit) {
+            writeln!(out, "{line}")?;
+        }
+    }
+    if decorations.values().any(|a| !a.is_empty()) {
+        writeln!(out)?;
+        for (date, annotation) in decorations.iter().filter(|(_, a)| !a.is_empty()) {
+            writeln!(out, "{}: {annotation}", date.format("%Y-%m-%d"))?;
+        }
+    }
+    if !deadlines.is_empty() {
+        writeln!(out)?;
+        for line in format_countdown(&deadlines, &holiday_info, today, config.lang) {
+            writeln!(out, "{line}")?;
+        }
+    }
+    out.finish()?;
+
+    Ok(())
+}
 
-    let mut file = BufReader::new(f);
-    let mut buf = Vec::new();
-    file.read_to_end(&mut buf)?;
-    let s = match file_config.encoding {
-        Encoding::ShiftJis => {
-            let (s, _, _) = SHIFT_JIS.decode(&buf);
-            s
+/// Years for which computed holidays are generated: a century either side of
+/// today, widened to cover explicitly requested years, and those of the merged
+/// `-m` selection `months`.
+fn data_years(
+    config: &Config,
+    months: Option<&MonthSelection>,
+    today: NaiveDate,
+) -> RangeInclusive<i32> {
+    let (mut start, mut end) = (today.year() - 100, today.year() + 100);
+    let requested = match &config.command {
+        Some(Command::Seq { from, to, .. }) | Some(Command::Workdays { from, to }) => {
+            Some((from.year(), to.year()))
+        }
+        // Enough to cover a year of business days either way
+        Some(Command::AddBusinessDays { date, n }) => {
+            let years = i32::try_from(n.unsigned_abs() / 200 + 1).unwrap_or(MAX_YEAR);
+            Some((
+                date.year().saturating_sub(years).max(MIN_YEAR),
+                date.year().saturating_add(years).min(MAX_YEAR),
+            ))
+        }
+        Some(Command::Heatmap { year }) | Some(Command::Wall { year, .. }) => Some((*year, *year)),
+        Some(Command::Show { months }) => {
+            let years = months.iter().map(|(y, _)| *y);
+            years.clone().min().zip(years.max())
+        }
+        Some(Command::Upcoming { days }) | Some(Command::Notify { days }) => {
+            Some((today.year(), window_end(today, *days).year()))
         }
-        Encoding::Utf8 => String::from_utf8_lossy(&buf), // UTF-8 is the default encoding in Rust.
+        Some(Command::Holidays { year, .. })
+        | Some(Command::Conflicts { year })
+        | Some(Command::Export { year, .. })
+        | Some(Command::Search { year, .. }) => year.map(|y| (y, y)),
+        None => config
+            .from
+            .zip(config.to)
+            .map(|(from, to)| (from.0, to.0))
+            .or_else(|| {
+                let years = config.year.iter().flat_map(|r| [*r.start(), *r.end()]);
+                years.clone().min().zip(years.max())
+            })
+            .or_else(|| match months {
+                Some(MonthSelection::Relative(offsets)) => {
+                    let year = |n: &i32| shift_month((today.year(), today.month()), *n);
+                    let first = offsets.first().and_then(year)?;
+                    let last = offsets.last().and_then(year)?;
+                    Some((first.0, last.0))
+                }
+                _ => None,
+            })
+            .map(|(first, last)| {
+                // -3, -A and -B reach into neighboring years.
+                let spill = |n: u32| (n as i32).saturating_add(11) / 12 + i32::from(config.three);
+                (
+                    first - spill(config.before),
+                    // A fiscal year ends in March of the next year.
+                    last.saturating_add(spill(config.after) + i32::from(config.fiscal)),
+                )
+            }),
+        _ => None,
     };
+    if let Some((s, e)) = requested {
+        start = start.min(s);
+        end = end.max(e);
+    }
+    start.max(MIN_YEAR)..=end.min(MAX_YEAR)
+}
 
-    let cursor = Cursor::new(s.as_bytes());
-    let r = BufReader::new(cursor);
-    let holidays = r
-        .lines()
-        .filter_map(|line| match line {
-            Ok(line) => {
-                let ls = line.split(",").next()?;
-                NaiveDate::parse_from_str(ls, "%Y/%m/%d").ok()
-            }
-            _ => None,
-        })
-        .collect::<Vec<_>>();
+/// `--first-weekday`, `--monday`, or the locale's default.
+fn resolve_first_weekday(config: &Config) -> Weekday {
+    match (config.first_weekday, config.monday) {
+        (Some(weekday), _) => weekday,
+        (None, true) => Weekday::Mon,
+        (None, false) => default_first_weekday(config.lang),
+    }
+}
+
+/// Warns on stderr, unless `--quiet`, that `year` would render without any holidays.
+fn warn_uncovered(holiday_info: &HolidayInfo, year: i32, quiet: bool, lang: Lang) {
+    if quiet || holiday_info.covers_year(year) {
+        return;
+    }
+    match lang {
+        Lang::Japanese => eprintln!("警告: {year}年の祝日データがありません"),
+        Lang::English => eprintln!("warning: no holiday data for {year}"),
+    }
+}
 
-    let mut ret = HolidayInfo::new();
-    for date in holidays {
-        ret.add(date);
+/// Suggests downloading the Cabinet Office list when no default holiday
+/// file exists, once: the hint is recorded in the state directory.
+fn hint_holiday_file(quiet: bool, lang: Lang) {
+    static HINTED: AtomicBool = AtomicBool::new(false);
+    if quiet || !io::stderr().is_terminal() || HINTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let Some(marker) = state_dir().map(|dir| dir.join("holiday-file-hint")) else {
+        return;
+    };
+    if !claim_hint(&marker) {
+        return;
     }
+    let Some(path) = holiday_file() else {
+        return;
+    };
+    let (path, url) = (
+        path.display(),
+        "https://www8.cao.go.jp/chosei/shukujitsu/syukujitsu.csv",
+    );
+    match lang {
+        Lang::Japanese => eprintln!(
+            "ヒント: 祝日ファイルがないため祝日を計算で表示しています。内閣府の祝日一覧を使うには: curl --create-dirs -o \"{path}\" {url}"
+        ),
+        Lang::English => eprintln!(
+            "hint: no holiday file, so holidays are computed. To use the Cabinet Office list: curl --create-dirs -o \"{path}\" {url}"
+        ),
+    }
+}
 
-    Ok(ret)
+/// Whether a hint recorded by `marker` is still to be shown, recording it
+/// if so.
+fn claim_hint(marker: &Path) -> bool {
+    if marker.exists() {
+        return false;
+    }
+    if let Some(dir) = marker.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(marker, "");
+    true
 }
 
-fn print_months(
-    year: i32,
-    months: &Months,
+/// The terminal grid, drawn with everything `run` computed for the displayed days.
+struct GridRenderer<'a> {
     format_config: FormatConfig,
+    holiday_info: &'a HolidayInfo,
     today: NaiveDate,
-    holiday_info: &HolidayInfo,
-) {
-    if !format_config.show_year {
-        if months.len() == 2 {
-            println!("{:^40}", year);
-        } else {
-            println!("{:^60}", year);
+}
+
+impl Renderer for GridRenderer<'_> {
+    fn render(&self, calendar: &Calendar, mut out: &mut dyn Write) -> io::Result<()> {
+        let months = calendar
+            .months
+            .iter()
+            .map(|m| (m.year, m.month))
+            .collect::<Vec<_>>();
+        print_months(
+            &mut out,
+            &months,
+            &self.format_config,
+            self.today,
+            self.holiday_info,
+        )
+    }
+}
+
+/// The `calp pick` screen: one month with the cursor drawn like today
+/// usually is (today is underlined), and the selected day's entries below.
+struct PickCalendar<'a> {
+    format_config: FormatConfig,
+    holiday_info: &'a mut HolidayInfo,
+    events: Option<EventsFile>,
+    caps: Caps,
+    /// Holiday/event files, reloaded when they change
+    watcher: FileWatcher,
+    /// Loads the configured sources again, without the events file
+    load: Box<dyn Fn() -> LibResult<HolidayInfo>>,
+}
+
+impl PickCalendar<'_> {
+    fn events(&self) -> LibResult<&EventsFile> {
+        self.events
+            .as_ref()
+            .ok_or_else(|| match self.format_config.lang {
+                Lang::Japanese => "予定を編集するには --events FILE を指定してください".into(),
+                Lang::English => "start calp pick with --events FILE to edit events".into(),
+            })
+    }
+}
+
+impl PickView for PickCalendar<'_> {
+    fn draw(&self, cursor: NaiveDate) -> Vec<String> {
+        let mut lines = format_month(
+            cursor.year(),
+            cursor.month(),
+            &self.format_config,
+            cursor,
+            self.holiday_info,
+        );
+        let names = self
+            .holiday_info
+            .holidays_on(cursor)
+            .iter()
+            .map(|h| h.name.as_str())
+            .collect::<Vec<_>>();
+        lines.push(String::new());
+        lines.push(format!(
+            "{} {}",
+            cursor.format("%Y-%m-%d"),
+            names.join(" / ")
+        ));
+        lines
+            .iter()
+            .map(|l| self.caps.degrade(l).into_owned())
+            .collect()
+    }
+
+    fn event_on(&self, date: NaiveDate) -> Option<String> {
+        let source = self.events.as_ref()?.path.display().to_string();
+        self.holiday_info
+            .holidays_on(date)
+            .iter()
+            .find(|h| h.source == source && h.span.is_none())
+            .map(|h| h.name.clone())
+    }
+
+    fn edit(&mut self, date: NaiveDate, edit: Edit) -> LibResult<()> {
+        let events = self.events()?;
+        let source = events.path.display().to_string();
+        let added = match edit {
+            Edit::Add(name) => {
+                events.add(date, &name)?;
+                Some(name)
+            }
+            Edit::Rename { from, to } => {
+                events.rename(date, &from, &to)?;
+                self.holiday_info.remove_from(date, &from, &source);
+                Some(to)
+            }
+            Edit::Remove(name) => {
+                events.remove(date, &name)?;
+                self.holiday_info.remove_from(date, &name, &source);
+                None
+            }
+        };
+        if let Some(name) = added {
+            self.holiday_info.add(
+                date,
+                Holiday {
+                    name: name.trim().to_string(),
+                    category: Category::Personal,
+                    time: None,
+                    span: None,
+                    source,
+                },
+            );
         }
+        Ok(())
     }
 
-    let v = months
-        .iter()
-        .map(|month| format_month(year, *month, &format_config, today, holiday_info))
-        .collect::<Vec<Vec<_>>>();
-    for (i, chunk) in v.chunks(3).enumerate() {
+    fn find_next(&self, date: NaiveDate, query: &str) -> Option<NaiveDate> {
+        self.holiday_info.find_next(date, query)
+    }
+
+    fn reload(&mut self) -> LibResult<bool> {
+        if !self.watcher.changed() {
+            return Ok(false);
+        }
+        let mut holiday_info = (self.load)()?;
+        if let Some(events) = &self.events {
+            for (date, holiday) in events.load()? {
+                holiday_info.add(date, holiday);
+            }
+        }
+        *self.holiday_info = holiday_info;
+        Ok(true)
+    }
+}
+
+/// Weekday names in column order, starting from `first_weekday`.
+fn weekday_names(lang: Lang, first_weekday: Weekday) -> [String; 7] {
+    let names = match lang {
+        Lang::Japanese => JAPANESE_WEEK_NAMES,
+        Lang::English => ENGLISH_WEEK_NAMES,
+    };
+    let offset = first_weekday.num_days_from_sunday() as usize;
+    std::array::from_fn(|i| names[(offset + i) % 7].to_string())
+}
+
+fn print_months(
+    out: &mut impl Write,
+    months: &[(i32, u32)],
+    format_config: &FormatConfig,
+    today: NaiveDate,
+    holiday_info: &HolidayInfo,
+) -> io::Result<()> {
+    // Without years in the month headers, each year gets a banner above its months.
+    let fiscal = format_config.fiscal;
+    let groups = if format_config.show_year {
+        vec![months]
+    } else {
+        months
+            .chunk_by(|a, b| banner_year(*a, fiscal) == banner_year(*b, fiscal))
+            .collect()
+    };
+    for (i, group) in groups.into_iter().enumerate() {
         if i > 0 {
-            println!();
+            writeln!(out)?;
         }
-        match chunk {
-            [m1, m2, m3] => {
-                for (e1, e2, e3) in izip!(m1, m2, m3) {
-                    println!("{}{}{}", e1, e2, e3);
-                }
+        if !format_config.show_year {
+            // Over the months of the first row, without the last one's trailing gap
+            let row = match format_config.group {
+                Some(_) => format_config.months_per_row.min(3),
+                None => format_config.months_per_row,
+            };
+            let months = group.len().min(row);
+            let width = month_stride(format_config) * months - 2;
+            let year = banner_year(group[0], fiscal);
+            let banner = match (fiscal, format_config.lang) {
+                (false, _) => year.to_string(),
+                (true, Lang::Japanese) => format!("{year}年度"),
+                (true, Lang::English) => format!("FY{year}"),
+            };
+            let banner = center(&banner, width);
+            check_layout(&banner, width, format_config)?;
+            writeln!(out, "{banner}")?;
+        }
+
+        let sections = match format_config.group {
+            Some(grouping) => {
+                let fiscal = fiscal || grouping == Grouping::Fiscal;
+                group
+                    .chunk_by(|a, b| quarter(*a, fiscal) == quarter(*b, fiscal))
+                    .collect()
             }
-            [m1, m2] => {
-                for (e1, e2) in izip!(m1, m2) {
-                    println!("{}{}", e1, e2);
-                }
+            None => vec![group],
+        };
+        for (i, section) in sections.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            if let Some(grouping) = format_config.group {
+                let months = section.len().min(format_config.months_per_row);
+                let width = month_stride(format_config) * months - 2;
+                let label = quarter_label(section[0], grouping, fiscal, format_config.lang);
+                let rule = "─".repeat(width.saturating_sub(label.width() + 4));
+                let line = format!("── {label} {rule}");
+                check_layout(&line, width, format_config)?;
+                writeln!(out, "{line}")?;
             }
-            [m1] => {
-                println!("{}", m1.join("\n"));
+            let v = section
+                .iter()
+                .map(|(year, month)| {
+                    format_month(*year, *month, format_config, today, holiday_info)
+                })
+                .collect::<Vec<Vec<_>>>();
+            for (i, chunk) in v.chunks(format_config.months_per_row).enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                // Every month has the same number of lines.
+                for row in 0..chunk[0].len() {
+                    let line = chunk
+                        .iter()
+                        .map(|month| month[row].as_str())
+                        .collect::<String>();
+                    check_layout(
+                        &line,
+                        month_stride(format_config) * chunk.len(),
+                        format_config,
+                    )?;
+                    writeln!(out, "{line}")?;
+                }
             }
-            _ => (),
         }
     }
+    Ok(())
+}
+
+/// With `--check-layout`, fails unless `line` takes `width` display columns
+/// once colors and hyperlinks are removed.
+fn check_layout(line: &str, width: usize, format_config: &FormatConfig) -> io::Result<()> {
+    if !format_config.check_layout {
+        return Ok(());
+    }
+    let text = escapes().replace_all(line, "");
+    if text.width() == width {
+        return Ok(());
+    }
+    Err(io::Error::other(format!(
+        "--check-layout: line is {} columns wide, expected {width}: \"{}\"",
+        text.width(),
+        text.trim_end()
+    )))
+}
+
+/// The calendar or, when `fiscal`, fiscal year of a month and its quarter (1-4).
+fn quarter((year, month): (i32, u32), fiscal: bool) -> (i32, u32) {
+    let index = if fiscal { (month + 8) % 12 } else { month - 1 };
+    (banner_year((year, month), fiscal), index / 3 + 1)
+}
+
+/// `--group` label of the quarter a month is in, e.g. 第1四半期 or Q1 2025.
+/// Fiscal quarters are named with their fiscal year unless the banners
+/// already are.
+fn quarter_label(month: (i32, u32), grouping: Grouping, fiscal: bool, lang: Lang) -> String {
+    let named_fiscal = grouping == Grouping::Fiscal && !fiscal;
+    let (year, q) = quarter(month, fiscal || grouping == Grouping::Fiscal);
+    match (lang, named_fiscal || fiscal) {
+        (Lang::Japanese, _) if named_fiscal => format!("{year}年度 第{q}四半期"),
+        (Lang::Japanese, _) => format!("第{q}四半期"),
+        (Lang::English, true) => format!("FY{year} Q{q}"),
+        (Lang::English, false) => format!("Q{q} {year}"),
+    }
+}
+
+/// The month `n` months after (or before, if negative) `(year, month)`,
+/// or `None` outside [`MIN_YEAR`]-[`MAX_YEAR`].
+fn shift_month((year, month): (i32, u32), n: i32) -> Option<(i32, u32)> {
+    let index = (year * 12 + month as i32 - 1).checked_add(n)?;
+    let (y, m) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    (MIN_YEAR..=MAX_YEAR).contains(&y).then_some((y, m))
+}
+
+/// The error for months that `shift_month` cannot reach.
+fn out_of_range() -> Box<dyn Error> {
+    format!(
+        "months before {MIN_YEAR:04}-01 or after {MAX_YEAR}-12 cannot be shown (years {MIN_YEAR}-{MAX_YEAR})"
+    )
+    .into()
+}
+
+/// The `number`th period of `len` months in `year`, which starts in April
+/// when `fiscal`; `None` when it runs past [`MAX_YEAR`].
+fn period_months(year: i32, number: u32, len: u32, fiscal: bool) -> Option<Vec<(i32, u32)>> {
+    let first = (number - 1) * len + if fiscal { 3 } else { 0 };
+    (first..first + len)
+        .map(|n| shift_month((year, 1), n as i32))
+        .collect()
+}
+
+/// The Japanese fiscal year (年度) `date` is in, which starts on April 1.
+fn fiscal_year(date: NaiveDate) -> i32 {
+    banner_year((date.year(), date.month()), true)
+}
+
+/// The calendar or, when `fiscal`, fiscal year a month is shown under.
+fn banner_year((year, month): (i32, u32), fiscal: bool) -> i32 {
+    if fiscal && month < 4 {
+        year - 1
+    } else {
+        year
+    }
 }
 
 fn last_day_in_month(year: i32, month: u32) -> NaiveDate {
@@ -258,40 +1982,154 @@ fn format_month(
     today: NaiveDate,
     holiday_info: &HolidayInfo,
 ) -> Vec<String> {
-    let formatted_days = format_days(year, month, today, holiday_info);
-
     let header = match format_config.lang {
         Lang::Japanese => format_header_jp(year, month, format_config.show_year),
         Lang::English => format_header_en(year, month, format_config.show_year),
     };
+    let width = month_width(format_config);
+    let header = if format_config.wide {
+        format!("{}  ", center(header.trim(), width))
+    } else {
+        header
+    };
+    let header = if format_config.badges {
+        let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        let holidays = holiday_info.holiday_count(first..=last_day_in_month(year, month));
+        let badge = match format_config.lang {
+            Lang::Japanese => format!("(祝{holidays})"),
+            Lang::English => format!("({holidays} hol)"),
+        };
+        let text = format!("{} {badge}", header.trim());
+        let text = if text.width() > width {
+            truncate(&text, width)
+        } else {
+            text
+        };
+        format!("{}  ", center(&text, width))
+    } else {
+        header
+    };
+    let header = match (
+        &format_config.month_url,
+        NaiveDate::from_ymd_opt(year, month, 1),
+    ) {
+        (Some(template), Some(first)) => {
+            let text = header.trim();
+            let url = expand_url(template, first);
+            header.replacen(text, &hyperlink(&url, text), 1)
+        }
+        _ => header,
+    };
+    if format_config.vertical {
+        let mut ret = vec![header];
+        ret.extend(format_days_vertical(
+            year,
+            month,
+            format_config,
+            today,
+            holiday_info,
+        ));
+        return ret;
+    }
 
+    let week_names = match format_config.lang {
+        Lang::Japanese => JAPANESE_WEEK_NAMES,
+        Lang::English => ENGLISH_WEEK_NAMES,
+    };
+    let offset = format_config.first_weekday.num_days_from_sunday() as usize;
+    let cell = if format_config.wide {
+        2 + MARKER_WIDTH
+    } else {
+        2
+    };
     let week_names = format!(
         "{}  ",
-        match format_config.lang {
-            Lang::Japanese => JAPANESE_WEEK_NAMES.join(" "),
-            Lang::English => ENGLISH_WEEK_NAMES.join(" "),
-        }
+        (0..7)
+            .map(|i| week_names[(offset + i) % 7])
+            .map(|name| format!("{name}{}", " ".repeat(cell.saturating_sub(name.width()))))
+            .collect::<Vec<_>>()
+            .join(" ")
     );
 
-    let mut ret = vec![header, week_names];
-    ret.extend(formatted_days);
+    let mut ret = if let Some(week_label) = format_config.week_label {
+        let padding = week_label.padding(format_config.lang);
+        vec![
+            format!("{padding}{header}"),
+            format!("{padding}{week_names}"),
+        ]
+    } else {
+        vec![header, week_names]
+    };
+    ret.extend(format_days(year, month, format_config, today, holiday_info));
 
     ret
 }
 
-fn format_header_jp(year: i32, month: u32, show_year: bool) -> String {
-    format!(
-        "{:^17}  ",
-        format!(
-            "{month}月({}){}",
-            JAPANESE_LUNAR_MONTH_NAMES[month as usize - 1],
-            if show_year {
-                format!(" {year}")
-            } else {
-                "".to_string()
+/// Display columns of a month's day grid, without the trailing gap.
+fn month_width(format_config: &FormatConfig) -> usize {
+    if format_config.wide {
+        7 * (2 + MARKER_WIDTH) + 6
+    } else {
+        20
+    }
+}
+
+/// Display columns from one month's grid to the next in a row: its week
+/// labels, grid and trailing gap.
+fn month_stride(format_config: &FormatConfig) -> usize {
+    let label = format_config
+        .week_label
+        .map_or(0, |l| l.padding(format_config.lang).width());
+    label + month_width(format_config) + 2
+}
+
+/// Centers `s` in `width` display columns.
+fn center(s: &str, width: usize) -> String {
+    let pad = width.saturating_sub(s.width());
+    format!("{}{s}{}", " ".repeat(pad / 2), " ".repeat(pad - pad / 2))
+}
+
+/// `template` with the year and month of `first` in place of %Y, %y, %m,
+/// %B and %b, and %% as %. Any other `%`, e.g. of `%20`, is left as it is.
+fn expand_url(template: &str, first: NaiveDate) -> String {
+    let mut url = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find('%') {
+        url.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let spec = rest
+            .get(..2)
+            .filter(|s| ["%Y", "%y", "%m", "%B", "%b", "%%"].contains(s));
+        match spec {
+            Some(spec) => {
+                url += &first.format(spec).to_string();
+                rest = &rest[2..];
             }
-        )
-    )
+            None => {
+                url.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    url + rest
+}
+
+/// Wraps `text` in an OSC 8 terminal hyperlink.
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+fn format_header_jp(year: i32, month: u32, show_year: bool) -> String {
+    let title = format!(
+        "{month}月({}){}",
+        JAPANESE_LUNAR_MONTH_NAMES[month as usize - 1],
+        if show_year {
+            format!(" {year}")
+        } else {
+            "".to_string()
+        }
+    );
+    format!("{}  ", center(&title, 20))
 }
 
 fn format_header_en(year: i32, month: u32, show_year: bool) -> String {
@@ -309,103 +2147,672 @@ fn format_header_en(year: i32, month: u32, show_year: bool) -> String {
     )
 }
 
-fn format_days(year: i32, month: u32, today: NaiveDate, holiday_info: &HolidayInfo) -> Vec<String> {
-    let is_today = |d: u32| year == today.year() && month == today.month() && d == today.day();
-    let days = preformat_days(year, month);
+fn format_days(
+    year: i32,
+    month: u32,
+    format_config: &FormatConfig,
+    today: NaiveDate,
+    holiday_info: &HolidayInfo,
+) -> Vec<String> {
+    let first_weekday = format_config.first_weekday;
+    let days = preformat_days(year, month, first_weekday);
+    let offset = days.iter().position(|d| *d != 0).unwrap_or(0);
+    let last = last_day_in_month(year, month).day() as usize;
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let prev_last = NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(31, |d| d.day() as usize);
     days.chunks(7)
-        .map(|d| {
-            let s = d
+        .enumerate()
+        .map(|(week, d)| {
+            let cells = d
                 .iter()
                 .enumerate()
                 .map(|(i, d)| {
-                    if *d == 0 {
+                    let weekday = column_weekday(first_weekday, i);
+                    if *d == 0 && format_config.spillover {
+                        let idx = week * 7 + i;
+                        let n = if idx < offset {
+                            prev_last + idx + 1 - offset
+                        } else {
+                            idx + 1 - offset - last
+                        };
+                        let label = first
+                            .checked_sub_days(Days::new(offset as u64))
+                            .and_then(|d| d.checked_add_days(Days::new(idx as u64)))
+                            .map_or_else(|| format!("{:>2}", n), |d| day_number(d, format_config));
+                        format_config.palette.spillover.paint(label).to_string()
+                    } else if *d == 0 {
                         "  ".to_string()
                     } else {
-                        let s = format!("{:>2}", d);
-                        Some(Style::new())
-                            .map(|v| {
-                                if i == 0 || holiday_info.is_holiday(year, month, *d) {
-                                    v.fg(Colour::Red)
-                                } else if i == 6 {
-                                    v.fg(Colour::Blue)
-                                } else {
-                                    v
-                                }
-                            })
-                            .map(|v| if is_today(*d) { v.reverse() } else { v })
-                            .map(|v| v.paint(&s).to_string())
-                            .unwrap_or(s)
+                        let date = first.with_day(*d).unwrap();
+                        day_style(date, weekday, format_config, today, holiday_info)
+                            .paint(day_number(date, format_config))
+                            .to_string()
                     }
                 })
-                .collect::<Vec<_>>()
-                .join(" ");
-            format!("{}  ", s)
+                .zip(d)
+                .map(|(cell, d)| {
+                    if !format_config.wide {
+                        return cell;
+                    }
+                    let marker = (*d != 0)
+                        .then(|| holiday_info.category(year, month, *d))
+                        .flatten()
+                        .and_then(|c| format_config.emoji.get(&c));
+                    cell + &pad_marker(marker.map_or("", String::as_str))
+                })
+                .collect::<Vec<_>>();
+            // Join cells. The gap after a day carries its footnote marker.
+            let mut s = String::new();
+            for (i, cell) in cells.iter().enumerate() {
+                s += cell;
+                let date = NaiveDate::from_ymd_opt(year, month, d[i]);
+                match date.and_then(|d| format_config.footnotes.get(&d)) {
+                    Some(n) => s.push(cell_marker(*n)),
+                    None => s.push(' '),
+                }
+            }
+            match format_config.week_label {
+                Some(week_label) => {
+                    let first_day = first - Days::new(offset as u64) + Days::new(week as u64 * 7);
+                    let label = week_label.format(
+                        week,
+                        first_day,
+                        first_weekday,
+                        d.iter().all(|d| *d == 0),
+                        format_config.lang,
+                    );
+                    format!("{label}{} ", s)
+                }
+                None => format!("{} ", s),
+            }
         })
         .collect::<Vec<_>>()
 }
 
-fn preformat_days(year: i32, month: u32) -> Vec<u32> {
-    let last = last_day_in_month(year, month);
+/// The `--theme` palette with the `--*-color` and `--today-style` overrides.
+fn palette(config: &Config) -> Palette {
+    let mut palette = config.theme.palette();
+    if let Some(colour) = config.holiday_color {
+        palette.national = palette.national.fg(colour);
+        palette.overrides = palette.overrides.fg(colour);
+    }
+    if let Some(colour) = config.saturday_color {
+        palette.saturday = palette.saturday.fg(colour);
+    }
+    if let Some(colour) = config.sunday_color {
+        palette.sunday = palette.sunday.fg(colour);
+    }
+    if let Some(style) = config.today_style {
+        palette.today = style;
+    }
+    palette
+}
+
+/// The days of a month transposed, like ncal: a row per weekday, led by its
+/// name, with a column per week. Gaps after days carry footnote markers as
+/// in [`format_days`].
+fn format_days_vertical(
+    year: i32,
+    month: u32,
+    format_config: &FormatConfig,
+    today: NaiveDate,
+    holiday_info: &HolidayInfo,
+) -> Vec<String> {
+    let first_weekday = format_config.first_weekday;
+    let weeks = month_layout(year, month, first_weekday);
+    let week_names = match format_config.lang {
+        Lang::Japanese => JAPANESE_WEEK_NAMES,
+        Lang::English => ENGLISH_WEEK_NAMES,
+    };
+    (0..7)
+        .map(|i| {
+            let weekday = column_weekday(first_weekday, i);
+            let name = week_names[weekday.num_days_from_sunday() as usize];
+            let mut s = format!("{name}{}", " ".repeat(2usize.saturating_sub(name.width())));
+            let mut gap = ' ';
+            for week in &weeks {
+                s.push(gap);
+                gap = ' ';
+                let Some(date) = week[i].and_then(|d| NaiveDate::from_ymd_opt(year, month, d))
+                else {
+                    s += "  ";
+                    continue;
+                };
+                s += &day_style(date, weekday, format_config, today, holiday_info)
+                    .paint(day_number(date, format_config))
+                    .to_string();
+                if let Some(n) = format_config.footnotes.get(&date) {
+                    gap = cell_marker(*n);
+                }
+            }
+            s.push(gap);
+            s + " "
+        })
+        .collect()
+}
+
+/// The two columns of a day cell: its day of month, or with `--countdown-to`
+/// the days left until the target, `--` once it has passed and `..` for more
+/// than 99 days.
+fn day_number(date: NaiveDate, format_config: &FormatConfig) -> String {
+    let Some(target) = format_config.countdown_to else {
+        return format!("{:>2}", date.day());
+    };
+    match (target - date).num_days() {
+        n @ 0..=99 => format!("{:>2}", n),
+        n if n < 0 => "--".to_string(),
+        _ => "..".to_string(),
+    }
+}
+
+/// How a day number is drawn: colored by its holidays, or by whether
+/// `--work-week` makes it a workday when it has none, then highlighted for today, marks and the like.
+fn day_style(
+    date: NaiveDate,
+    weekday: Weekday,
+    format_config: &FormatConfig,
+    today: NaiveDate,
+    holiday_info: &HolidayInfo,
+) -> Style {
+    let palette = &format_config.palette;
+    // Days off by --work-week look like Sunday, half days like Saturday
+    let weight = holiday_info.work_week().weight(weekday);
+    let mut style = match holiday_info.category(date.year(), date.month(), date.day()) {
+        Some(c) => palette.category(c),
+        None if weight == 1.0 => palette.weekday,
+        None if weight > 0.0 || weekday == Weekday::Sat => palette.saturday,
+        None => palette.sunday,
+    };
+    if let Some(n) = format_config.heat.get(&date) {
+        style = style.on(heat_colour(*n));
+    }
+    if date == today {
+        style = layer(style, palette.today);
+    }
+    if format_config.marks.contains(&date) {
+        style = style.underline();
+    }
+    if format_config.spans.contains(&date) {
+        style = layer(style, palette.span);
+    }
+    if format_config.transitions.contains(&date) {
+        style = style.bold();
+    }
+    style
+}
+
+/// Weekday shown in column `i` of a week starting on `first_weekday`.
+fn column_weekday(first_weekday: Weekday, i: usize) -> Weekday {
+    let n = (first_weekday.num_days_from_monday() as usize + i) % 7;
+    Weekday::try_from(n as u8).unwrap()
+}
+
+/// Heat level 1 to 4 of each of `days` with a count, scaled so the
+/// busiest of them gets 4.
+fn heat_levels(
+    counts: &HashMap<NaiveDate, usize>,
+    days: &[NaiveDate],
+) -> HashMap<NaiveDate, usize> {
+    let counted = days
+        .iter()
+        .filter_map(|d| Some((*d, *counts.get(d).filter(|n| **n > 0)?)))
+        .collect::<Vec<_>>();
+    let max = counted.iter().map(|(_, n)| *n).max().unwrap_or(1);
+    counted
+        .into_iter()
+        .map(|(d, n)| (d, (n * 4).div_ceil(max)))
+        .collect()
+}
+
+/// Background for a heatmap cell of heat `level`, GitHub-graph style.
+fn heat_colour(level: usize) -> Color {
+    match level {
+        0 | 1 => Color::Fixed(22),
+        2 => Color::Fixed(28),
+        3 => Color::Fixed(34),
+        _ => Color::Fixed(40),
+    }
+}
+
+/// Day numbers of `month` laid out in six weeks starting on `first_weekday`,
+/// with `None` for the cells before the 1st and after the last day.
+///
+/// # Panics
+///
+/// Panics if `year`/`month` is not a valid month.
+pub fn month_layout(year: i32, month: u32, first_weekday: Weekday) -> [[Option<u32>; 7]; 6] {
+    let last = last_day_in_month(year, month).day() as usize;
     let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
-    let mut days = vec![0; 7 * 6];
-    let first_weekday = first.weekday().num_days_from_sunday() as usize;
-    days.splice(
-        first_weekday..first_weekday + last.day() as usize,
-        (1..=last.day()).collect::<Vec<_>>(),
-    );
-    days
+    let offset = first.weekday().days_since(first_weekday) as usize;
+    let mut weeks = [[None; 7]; 6];
+    for i in offset..offset + last {
+        weeks[i / 7][i % 7] = Some((i - offset + 1) as u32);
+    }
+    weeks
+}
+
+/// [`month_layout`] flattened, with 0 for empty cells.
+fn preformat_days(year: i32, month: u32, first_weekday: Weekday) -> Vec<u32> {
+    month_layout(year, month, first_weekday)
+        .iter()
+        .flatten()
+        .map(|d| d.unwrap_or(0))
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
-    use crate::preformat_days;
+    use std::collections::HashMap;
+
+    use chrono::{Datelike, NaiveDate, Weekday};
+    use nu_ansi_term::Style;
+
+    use crate::{
+        business::WorkWeek,
+        check_layout, claim_hint, column_weekday, day_style, expand_url, fiscal_year, format_month,
+        heat_colour, heat_levels,
+        holiday::{holidays_of, HolidayInfo},
+        month_layout, period_months, preformat_days, print_months, quarter, quarter_label,
+        shift_month,
+        theme::Theme,
+        weekday_names,
+        weeknum::WeekLabel,
+        FormatConfig, Grouping, Lang,
+    };
+
+    #[test]
+    fn test_month_layout() {
+        // 2024-09-01 is a Sunday
+        let weeks = month_layout(2024, 9, Weekday::Mon);
+        assert_eq!(weeks[0], [None, None, None, None, None, None, Some(1)]);
+        assert_eq!(weeks[5], [Some(30), None, None, None, None, None, None]);
+        let weeks = month_layout(2024, 9, Weekday::Sun);
+        assert_eq!(weeks[0][0], Some(1));
+        assert_eq!(weeks[4][1], Some(30));
+        assert_eq!(weeks[5], [None; 7]);
+    }
+
+    #[test]
+    fn test_shift_month() {
+        assert_eq!(shift_month((2024, 12), 1), Some((2025, 1)));
+        assert_eq!(shift_month((2025, 1), -1), Some((2024, 12)));
+        assert_eq!(shift_month((2025, 3), -15), Some((2023, 12)));
+        assert_eq!(shift_month((9999, 12), 1), None);
+        assert_eq!(shift_month((1, 1), -1), None);
+        assert_eq!(shift_month((1, 1), i32::MAX), None);
+    }
+
+    #[test]
+    fn test_period_months() {
+        assert_eq!(
+            period_months(2025, 2, 3, false).unwrap(),
+            [(2025, 4), (2025, 5), (2025, 6)]
+        );
+        assert_eq!(
+            period_months(2025, 4, 3, true).unwrap(),
+            [(2026, 1), (2026, 2), (2026, 3)]
+        );
+        assert_eq!(
+            period_months(2025, 2, 6, true).unwrap()[..2],
+            [(2025, 10), (2025, 11)]
+        );
+        assert_eq!(period_months(9999, 3, 3, true).unwrap()[2], (9999, 12));
+        assert_eq!(period_months(9999, 4, 3, true), None);
+        let date = |m| NaiveDate::from_ymd_opt(2026, m, 1).unwrap();
+        assert_eq!(fiscal_year(date(3)), 2025);
+        assert_eq!(fiscal_year(date(4)), 2026);
+    }
+
+    #[test]
+    fn test_quarter() {
+        assert_eq!(quarter((2025, 3), false), (2025, 1));
+        assert_eq!(quarter((2025, 4), false), (2025, 2));
+        assert_eq!(quarter((2025, 4), true), (2025, 1));
+        assert_eq!(quarter((2026, 3), true), (2025, 4));
+        assert_eq!(
+            quarter_label((2025, 12), Grouping::Quarter, false, Lang::English),
+            "Q4 2025"
+        );
+        assert_eq!(
+            quarter_label((2025, 12), Grouping::Quarter, true, Lang::English),
+            "FY2025 Q3"
+        );
+        assert_eq!(
+            quarter_label((2025, 2), Grouping::Fiscal, false, Lang::Japanese),
+            "2024年度 第4四半期"
+        );
+        assert_eq!(
+            quarter_label((2025, 2), Grouping::Fiscal, true, Lang::Japanese),
+            "第4四半期"
+        );
+    }
+
+    #[test]
+    fn test_vertical_month() {
+        let format_config = FormatConfig {
+            vertical: true,
+            ..FormatConfig::plain(Lang::English, Weekday::Mon, Theme::Default.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let lines = format_month(2025, 5, &format_config, today, &HolidayInfo::new());
+        // A header and a row per weekday, as tall as a horizontal month
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[1], "Mo     5 12 19 26     ");
+        assert_eq!(lines[4], "Th  1  8 15 22 29     ");
+    }
+
+    #[test]
+    fn test_claim_hint() {
+        let dir = std::env::temp_dir().join(format!("calp-hint-{}", std::process::id()));
+        let marker = dir.join("state").join("holiday-file-hint");
+        assert!(claim_hint(&marker));
+        assert!(marker.exists());
+        assert!(!claim_hint(&marker));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expand_url() {
+        let first = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+        assert_eq!(
+            expand_url("https://wiki.example.com/%Y-%m", first),
+            "https://wiki.example.com/2025-08"
+        );
+        assert_eq!(
+            expand_url("https://example.com/Team%20Plan/%b%y?q=100%%25%", first),
+            "https://example.com/Team%20Plan/Aug25?q=100%25%"
+        );
+    }
+
+    #[test]
+    fn test_heat_levels() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let days = (1..=31).map(date).collect::<Vec<_>>();
+        let counts = HashMap::from([
+            (date(1), 1),
+            (date(2), 10),
+            (date(3), 20),
+            (date(4), 0),
+            (NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(), 100),
+        ]);
+        let levels = heat_levels(&counts, &days);
+        assert_eq!(levels.len(), 3);
+        assert_eq!(
+            (levels[&date(1)], levels[&date(2)], levels[&date(3)]),
+            (1, 2, 4)
+        );
+
+        let format_config = FormatConfig {
+            heat: levels,
+            ..FormatConfig::plain(Lang::English, Weekday::Sun, Theme::Mono.palette())
+        };
+        let lines = format_month(2025, 1, &format_config, date(31), &HolidayInfo::new());
+        let heat = |d, level| Style::new().on(heat_colour(level)).paint(d).to_string();
+        let week = format!("{} {} {} ", heat(" 1", 1), heat(" 2", 2), heat(" 3", 4));
+        assert!(lines[2].contains(&week), "{:?}", lines[2]);
+        // No entries on the 4th, which keeps its Saturday style.
+        let saturday = Style::new().italic().paint(" 4").to_string();
+        assert!(lines[2].contains(&saturday), "{:?}", lines[2]);
+    }
+
+    #[test]
+    fn test_span_underline() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 5, d).unwrap();
+        let format_config = FormatConfig {
+            spans: (6..=8).map(date).collect(),
+            ..FormatConfig::plain(Lang::English, Weekday::Sun, Theme::Mono.palette())
+        };
+        let lines = format_month(2025, 5, &format_config, date(1), &HolidayInfo::new());
+        // The days are underlined, the gaps between them are not.
+        let u = |d: &str| Style::new().underline().paint(d).to_string();
+        let week = format!("  5 {} {} {}  9 ", u(" 6"), u(" 7"), u(" 8"));
+        assert!(lines[3].contains(&week), "{:?}", lines[3]);
+    }
+
+    #[test]
+    fn test_badges() {
+        let format_config = FormatConfig {
+            badges: true,
+            show_year: false,
+            ..FormatConfig::plain(Lang::Japanese, Weekday::Sun, Theme::Default.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let hi = holidays_of(&[
+            "2025/05/03..2025/05/06,連休,company",
+            "2025/05/05,こどもの日",
+        ]);
+        let lines = format_month(2025, 5, &format_config, today, &hi);
+        assert!(lines[0].contains("(祝2)"), "{}", lines[0]);
+        // Too long for the grid with the year, so cut to its width
+        let format_config = FormatConfig {
+            lang: Lang::English,
+            show_year: true,
+            ..format_config
+        };
+        let lines = format_month(2025, 9, &format_config, today, &hi);
+        assert_eq!(lines[0].trim_end(), "September 2025 (0 h…");
+    }
+
+    #[test]
+    fn test_week_of_month() {
+        let format_config = FormatConfig {
+            week_label: Some(WeekLabel::OfMonth),
+            show_year: false,
+            ..FormatConfig::plain(Lang::Japanese, Weekday::Sun, Theme::Default.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        // 2025-06-01 is a Sunday, so June has five week rows.
+        let lines = format_month(2025, 6, &format_config, today, &HolidayInfo::new());
+        assert!(lines[1].starts_with("      日"), "{:?}", lines[1]);
+        assert!(lines[4].starts_with("第3週 "), "{:?}", lines[4]);
+        assert!(lines[4].contains(" 16 17 18 19 20 "), "{:?}", lines[4]);
+        assert!(lines[6].starts_with("第5週 "), "{:?}", lines[6]);
+        assert_eq!(lines[7].trim(), "");
+        let format_config = FormatConfig {
+            lang: Lang::English,
+            ..format_config
+        };
+        let lines = format_month(2025, 6, &format_config, today, &HolidayInfo::new());
+        assert!(lines[2].starts_with("W1 "), "{:?}", lines[2]);
+        assert!(lines[3].starts_with("W2 "), "{:?}", lines[3]);
+    }
+
+    #[test]
+    fn test_spillover() {
+        let format_config = FormatConfig {
+            spillover: true,
+            show_year: false,
+            ..FormatConfig::plain(Lang::English, Weekday::Sun, Theme::Mono.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        // 2025-03-01 is a Saturday and 2025-03-31 a Monday.
+        let lines = format_month(2025, 3, &format_config, today, &HolidayInfo::new());
+        let dim = |days: &[&str]| {
+            days.iter()
+                .map(|d| Style::new().dimmed().paint(*d).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let lead = dim(&["23", "24", "25", "26", "27", "28"]);
+        assert!(lines[2].starts_with(&lead), "{:?}", lines[2]);
+        let trail = dim(&[" 1", " 2", " 3", " 4", " 5"]);
+        assert!(lines[7].contains(&format!(" 31 {trail}")), "{:?}", lines[7]);
+        // January follows December of the year before.
+        let lines = format_month(2025, 1, &format_config, today, &HolidayInfo::new());
+        assert!(
+            lines[2].starts_with(&dim(&["29", "30", "31"])),
+            "{:?}",
+            lines[2]
+        );
+    }
+
+    #[test]
+    fn test_countdown_month() {
+        let format_config = FormatConfig {
+            countdown_to: NaiveDate::from_ymd_opt(2025, 12, 24),
+            ..FormatConfig::plain(Lang::English, Weekday::Mon, Theme::Default.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let lines = format_month(2025, 12, &format_config, today, &HolidayInfo::new());
+        // 2025-12-01 is a Monday
+        assert!(lines[2].starts_with("23 22 21 20 19 "));
+        assert!(lines[5].starts_with(" 2  1  0 -- -- "));
+        let lines = format_month(2025, 8, &format_config, today, &HolidayInfo::new());
+        assert!(lines[2].contains(".."));
+    }
+
+    #[test]
+    fn test_print_months_columns() {
+        let format_config = FormatConfig {
+            show_year: false,
+            months_per_row: 4,
+            ..FormatConfig::plain(Lang::English, Weekday::Sun, Theme::Default.palette())
+        };
+        let months = (1..=12).map(|m| (2025, m)).collect::<Vec<_>>();
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let mut out = Vec::new();
+        print_months(
+            &mut out,
+            &months,
+            &format_config,
+            today,
+            &HolidayInfo::new(),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        // A banner, then three rows of four months with blank lines between
+        assert_eq!(lines.len(), 1 + 3 * 8 + 2);
+        assert_eq!(lines[0].trim(), "2025");
+        // Centered over the four grids, not the gap after the last
+        assert_eq!(lines[0].len(), 4 * 22 - 2);
+        assert!(lines[1].contains("January") && lines[1].contains("April"));
+        assert!(lines[10].contains("May"));
+    }
+
+    #[test]
+    fn test_check_layout() {
+        let format_config = FormatConfig {
+            show_year: false,
+            check_layout: true,
+            ..FormatConfig::plain(Lang::Japanese, Weekday::Sun, Theme::Default.palette())
+        };
+        // Three-kanji month names such as 水無月 are as wide as the others
+        let months = (1..=12).map(|m| (2026, m)).collect::<Vec<_>>();
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let mut out = Vec::new();
+        print_months(
+            &mut out,
+            &months,
+            &format_config,
+            today,
+            &HolidayInfo::new(),
+        )
+        .unwrap();
+        assert!(check_layout("\x1b[31m 1\x1b[0m  ", 4, &format_config).is_ok());
+        assert!(check_layout("月 ", 2, &format_config).is_err());
+    }
+
+    #[test]
+    fn test_monday_start() {
+        let format_config = FormatConfig {
+            show_year: false,
+            ..FormatConfig::plain(Lang::English, Weekday::Mon, Theme::Mono.palette())
+        };
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let lines = format_month(2025, 6, &format_config, today, &HolidayInfo::new());
+        assert_eq!(lines[1].trim_end(), "Mo Tu We Th Fr Sa Su");
+        // 2025-06-01 is a Sunday, now the last column, and keeps Sunday's style.
+        let (sat, sun) = (Style::new().italic(), Style::new().bold());
+        let first = format!("{:18}{} ", "", sun.paint(" 1"));
+        assert!(lines[2].starts_with(&first), "{:?}", lines[2]);
+        let second = format!(" 2  3  4  5  6 {} {} ", sat.paint(" 7"), sun.paint(" 8"));
+        assert!(lines[3].starts_with(&second), "{:?}", lines[3]);
+    }
+
+    #[test]
+    fn test_saturday_start() {
+        // 2024-09-01 is a Sunday, the second column
+        let weeks = month_layout(2024, 9, Weekday::Sat);
+        assert_eq!(weeks[0][..3], [None, Some(1), Some(2)]);
+        assert_eq!(column_weekday(Weekday::Sat, 0), Weekday::Sat);
+        assert_eq!(column_weekday(Weekday::Sat, 1), Weekday::Sun);
+        assert_eq!(column_weekday(Weekday::Sat, 6), Weekday::Fri);
+        assert_eq!(
+            weekday_names(Lang::Japanese, Weekday::Sat).join(" "),
+            "土 日 月 火 水 木 金"
+        );
+    }
+
+    #[test]
+    fn test_day_style_work_week() {
+        let format_config =
+            FormatConfig::plain(Lang::English, Weekday::Sun, Theme::Default.palette());
+        let palette = &format_config.palette;
+        let mut hi = HolidayInfo::new();
+        hi.set_work_week(WorkWeek::new(
+            vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu],
+            vec![Weekday::Sat],
+        ));
+        let style = |d| {
+            let date = NaiveDate::from_ymd_opt(2025, 5, d).unwrap();
+            day_style(date, date.weekday(), &format_config, NaiveDate::MIN, &hi)
+        };
+        // Thu 8, Fri 9, Sat 10 and Sun 11 May
+        assert_eq!(style(8), palette.weekday);
+        assert_eq!(style(9), palette.sunday);
+        assert_eq!(style(10), palette.saturday);
+        assert_eq!(style(11), palette.sunday);
+    }
 
     #[test]
     fn test_preformat_days() {
         // start Su
-        let res = preformat_days(2024, 12);
+        let res = preformat_days(2024, 12, Weekday::Sun);
         let mut cmp = vec![];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 4 + 7]);
         assert_eq!(res, cmp);
 
         // start Mo
-        let res = preformat_days(2024, 7);
+        let res = preformat_days(2024, 7, Weekday::Sun);
         let mut cmp = vec![0; 1];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 3 + 7]);
         assert_eq!(res, cmp);
 
         // start Tu
-        let res = preformat_days(2024, 10);
+        let res = preformat_days(2024, 10, Weekday::Sun);
         let mut cmp = vec![0; 2];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 2 + 7]);
         assert_eq!(res, cmp);
 
         // start We
-        let res = preformat_days(2024, 5);
+        let res = preformat_days(2024, 5, Weekday::Sun);
         let mut cmp = vec![0; 3];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 1 + 7]);
         assert_eq!(res, cmp);
 
         // start Th
-        let res = preformat_days(2024, 8);
+        let res = preformat_days(2024, 8, Weekday::Sun);
         let mut cmp = vec![0; 4];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 7]);
         assert_eq!(res, cmp);
 
         // start Fr
-        let res = preformat_days(2024, 3);
+        let res = preformat_days(2024, 3, Weekday::Sun);
         let mut cmp = vec![0; 5];
         cmp.extend((1..=31).collect::<Vec<_>>());
         cmp.extend(vec![0; 6]);
         assert_eq!(res, cmp);
 
         // start Sa
-        let res = preformat_days(2024, 6);
+        let res = preformat_days(2024, 6, Weekday::Sun);
         let mut cmp = vec![0; 6];
         cmp.extend((1..=30).collect::<Vec<_>>());
         cmp.extend(vec![0; 6]);
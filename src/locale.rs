@@ -0,0 +1,73 @@
+use std::env;
+
+use chrono::Weekday;
+
+use crate::Lang;
+
+/// Territories whose weeks start on Sunday (CLDR `firstDay`).
+const SUNDAY_FIRST_TERRITORIES: [&str; 55] = [
+    "AG", "AS", "BD", "BR", "BS", "BT", "BW", "BZ", "CA", "CN", "CO", "DM", "DO", "ET", "GT", "GU",
+    "HK", "HN", "ID", "IL", "IN", "JM", "JP", "KE", "KH", "KR", "LA", "MH", "MM", "MO", "MT", "MX",
+    "MZ", "NI", "NP", "PA", "PE", "PH", "PK", "PR", "PT", "PY", "SA", "SG", "SV", "TH", "TT", "TW",
+    "UM", "US", "VE", "VI", "WS", "YE", "ZA",
+];
+
+/// Territories whose weeks start on Saturday (CLDR `firstDay`).
+const SATURDAY_FIRST_TERRITORIES: [&str; 15] = [
+    "AE", "AF", "BH", "DJ", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SD", "SY",
+];
+
+/// Derives the first weekday from the selected language and, for English,
+/// the territory of the environment locale (`LC_ALL`, `LC_TIME`, `LANG`).
+pub fn default_first_weekday(lang: Lang) -> Weekday {
+    match lang {
+        Lang::Japanese => Weekday::Sun,
+        Lang::English => env_territory()
+            .map(|t| first_weekday_of_territory(&t))
+            .unwrap_or(Weekday::Sun),
+    }
+}
+
+fn env_territory() -> Option<String> {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|v| !v.is_empty())
+        .and_then(|v| territory(&v))
+}
+
+/// Extracts the territory from a POSIX locale name such as `en_GB.UTF-8`.
+fn territory(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?;
+    let (_, territory) = locale.split_once(['_', '-'])?;
+    Some(territory.to_ascii_uppercase())
+}
+
+fn first_weekday_of_territory(territory: &str) -> Weekday {
+    if SUNDAY_FIRST_TERRITORIES.contains(&territory) {
+        Weekday::Sun
+    } else if SATURDAY_FIRST_TERRITORIES.contains(&territory) {
+        Weekday::Sat
+    } else {
+        Weekday::Mon
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Weekday;
+
+    use crate::locale::{first_weekday_of_territory, territory};
+
+    #[test]
+    fn test_territory() {
+        assert_eq!(territory("en_GB.UTF-8"), Some("GB".to_string()));
+        assert_eq!(territory("de_DE@euro"), Some("DE".to_string()));
+        assert_eq!(territory("en-us"), Some("US".to_string()));
+        assert_eq!(territory("C"), None);
+
+        assert_eq!(first_weekday_of_territory("US"), Weekday::Sun);
+        assert_eq!(first_weekday_of_territory("DE"), Weekday::Mon);
+        assert_eq!(first_weekday_of_territory("EG"), Weekday::Sat);
+    }
+}
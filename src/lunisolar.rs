@@ -0,0 +1,112 @@
+use chrono::{Days, NaiveDate};
+
+/// The calendar a lunar new year is reckoned in: the same rules, but the
+/// day of a new moon is taken in China's or in Korea's time zone, so the
+/// two differ in a few years.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LunarCalendar {
+    Chinese,
+    Korean,
+}
+
+/// First year in the tables
+const FIRST_YEAR: i32 = 1900;
+
+/// Days from January 21 to the Chinese New Year (春節) of each year since
+/// [`FIRST_YEAR`]. From ICU's Chinese calendar, except 1954, 2027 and 2030,
+/// where ICU takes a new moon close to midnight to fall on the wrong day.
+const CHINESE: [u8; 201] = [
+    10, 29, 18, 8, 26, 14, 4, 23, 12, 1, // 1900
+    20, 9, 28, 16, 5, 24, 13, 2, 21, 11, // 1910
+    30, 18, 7, 26, 15, 3, 23, 12, 2, 20, // 1920
+    9, 27, 16, 5, 24, 14, 3, 21, 10, 29, // 1930
+    18, 6, 25, 15, 4, 23, 12, 1, 20, 8, // 1940
+    27, 16, 6, 24, 13, 3, 22, 10, 28, 18, // 1950
+    7, 25, 15, 4, 23, 12, 0, 19, 9, 27, // 1960
+    16, 6, 25, 13, 2, 21, 10, 28, 17, 7, // 1970
+    26, 15, 4, 23, 12, 30, 19, 8, 27, 16, // 1980
+    6, 25, 14, 2, 20, 10, 29, 17, 7, 26, // 1990
+    15, 3, 22, 11, 1, 19, 8, 28, 17, 5, // 2000
+    24, 13, 2, 20, 10, 29, 18, 7, 26, 15, // 2010
+    4, 22, 11, 1, 20, 8, 27, 16, 5, 23, // 2020
+    13, 2, 21, 10, 29, 18, 7, 25, 14, 3, // 2030
+    22, 11, 1, 20, 9, 27, 16, 5, 24, 12, // 2040
+    2, 21, 11, 29, 18, 7, 25, 14, 3, 22, // 2050
+    12, 0, 19, 8, 27, 15, 5, 24, 13, 2, // 2060
+    21, 10, 29, 17, 6, 25, 15, 3, 22, 12, // 2070
+    1, 19, 8, 27, 16, 5, 24, 13, 3, 20, // 2080
+    9, 28, 17, 6, 25, 15, 4, 22, 11, 0,  // 2090
+    19, // 2100
+];
+
+/// Days from January 21 to Seollal of each year since [`FIRST_YEAR`], from
+/// ICU's Korean (dangi) calendar.
+const KOREAN: [u8; 201] = [
+    10, 29, 18, 8, 26, 14, 4, 23, 12, 1, // 1900
+    20, 9, 28, 16, 5, 24, 14, 2, 21, 11, // 1910
+    30, 18, 7, 26, 15, 3, 23, 12, 2, 20, // 1920
+    9, 27, 16, 5, 24, 14, 3, 21, 10, 29, // 1930
+    18, 6, 25, 15, 5, 23, 12, 1, 20, 8, // 1940
+    27, 16, 6, 24, 14, 3, 22, 10, 29, 18, // 1950
+    7, 25, 15, 4, 23, 12, 1, 19, 9, 27, // 1960
+    16, 6, 25, 13, 2, 21, 10, 28, 17, 7, // 1970
+    26, 15, 4, 23, 12, 30, 19, 8, 28, 16, // 1980
+    6, 25, 14, 2, 20, 10, 29, 18, 7, 26, // 1990
+    15, 3, 22, 11, 1, 19, 8, 28, 17, 5, // 2000
+    24, 13, 2, 20, 10, 29, 18, 7, 26, 15, // 2010
+    4, 22, 11, 1, 20, 8, 27, 17, 6, 23, // 2020
+    13, 2, 21, 10, 29, 18, 7, 25, 14, 3, // 2030
+    22, 11, 1, 20, 9, 27, 16, 5, 24, 12, // 2040
+    2, 21, 11, 29, 18, 7, 25, 14, 3, 22, // 2050
+    12, 1, 19, 8, 27, 15, 5, 24, 13, 2, // 2060
+    21, 10, 29, 17, 6, 25, 15, 3, 22, 12, // 2070
+    1, 19, 8, 27, 16, 5, 24, 13, 3, 21, // 2080
+    9, 28, 18, 6, 25, 15, 4, 22, 11, 0,  // 2090
+    19, // 2100
+];
+
+/// First day of the first lunar month of `year` in `calendar`, for 1900 to
+/// 2100. The new moon that begins it cannot be told from the one before or
+/// after by a rule of thumb such as "between January 21 and February 20",
+/// which is wrong in 1920 and 1985, so the dates are looked up.
+pub fn lunar_new_year(year: i32, calendar: LunarCalendar) -> Option<NaiveDate> {
+    let table = match calendar {
+        LunarCalendar::Chinese => &CHINESE,
+        LunarCalendar::Korean => &KOREAN,
+    };
+    let days = table.get(usize::try_from(year - FIRST_YEAR).ok()?)?;
+    NaiveDate::from_ymd_opt(year, 1, 21)?.checked_add_days(Days::new((*days).into()))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::lunisolar::{lunar_new_year, LunarCalendar};
+
+    #[test]
+    fn test_lunar_new_year() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        let chinese = |y| lunar_new_year(y, LunarCalendar::Chinese);
+        assert_eq!(chinese(1900), ymd(1900, 1, 31));
+        assert_eq!(chinese(1966), ymd(1966, 1, 21));
+        assert_eq!(chinese(2023), ymd(2023, 1, 22));
+        assert_eq!(chinese(2024), ymd(2024, 2, 10));
+        assert_eq!(chinese(2025), ymd(2025, 1, 29));
+        assert_eq!(chinese(2026), ymd(2026, 2, 17));
+        assert_eq!(chinese(2030), ymd(2030, 2, 3));
+        assert_eq!(chinese(2100), ymd(2100, 2, 9));
+        assert_eq!(chinese(1899), None);
+        assert_eq!(chinese(2101), None);
+        // A new moon on January 21 that still ends the old year
+        assert_eq!(chinese(1920), ymd(1920, 2, 20));
+        assert_eq!(chinese(1985), ymd(1985, 2, 20));
+        // New moons just before midnight in Beijing
+        assert_eq!(chinese(1997), ymd(1997, 2, 7));
+        assert_eq!(chinese(2027), ymd(2027, 2, 6));
+        let korean = |y| lunar_new_year(y, LunarCalendar::Korean);
+        assert_eq!(korean(1997), ymd(1997, 2, 8));
+        assert_eq!(korean(2027), ymd(2027, 2, 7));
+        assert_eq!(korean(2025), ymd(2025, 1, 29));
+    }
+}
@@ -1,9 +1,37 @@
-use calp::{run, Config};
-use clap::Parser;
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+};
+
+use calp::{parse_config, run, Config, Watch};
 
 fn main() {
-    if let Err(e) = run(Config::parse()) {
+    let result = parse_config().and_then(|config| {
+        if config.watch() {
+            watch(config)
+        } else {
+            run(config)
+        }
+    });
+    if let Err(e) = result {
         eprintln!("{e}");
         std::process::exit(1);
     }
 }
+
+/// Redraws after every [`Watch`], reading the command line and config file
+/// again so that edits to the config file apply too.
+fn watch(mut config: Config) -> Result<(), Box<dyn Error>> {
+    loop {
+        let watch = Watch::new(&config)?;
+        if io::stdout().is_terminal() {
+            print!("\x1b[H\x1b[2J");
+        }
+        // Keep watching after errors, which are often a half-saved file.
+        if let Err(e) = run(config) {
+            eprintln!("{e}");
+        }
+        watch.wait();
+        config = parse_config()?;
+    }
+}
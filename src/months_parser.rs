@@ -1,39 +1,166 @@
 use std::{
     cmp::{max, min},
     error::Error,
+    fmt,
     ops::Range,
 };
 
 use regex::Regex;
+use unicode_width::UnicodeWidthStr;
 
 pub type Months = Vec<u32>;
 
-pub fn parse_months(value: &str) -> Result<Months, String> {
+/// What `-m` selects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonthSelection {
+    /// Months of the chosen year
+    Absolute(Months),
+    /// Offsets from the current month, sorted, e.g. `-1..+1`
+    Relative(Vec<i32>),
+}
+
+impl MonthSelection {
+    /// Combines repeated `-m` values into the union of their months, or of
+    /// their offsets; months and offsets cannot be mixed.
+    pub fn merge(selections: &[MonthSelection]) -> Result<Option<MonthSelection>, String> {
+        let mut merged = None;
+        for selection in selections {
+            merged = Some(match (merged, selection) {
+                (None, s) => s.clone(),
+                (Some(MonthSelection::Absolute(mut a)), MonthSelection::Absolute(b)) => {
+                    a.extend(b);
+                    a.sort();
+                    a.dedup();
+                    MonthSelection::Absolute(a)
+                }
+                (Some(MonthSelection::Relative(mut a)), MonthSelection::Relative(b)) => {
+                    a.extend(b);
+                    a.sort();
+                    a.dedup();
+                    MonthSelection::Relative(a)
+                }
+                _ => {
+                    return Err(
+                        "-m cannot mix months (1,3-5) with relative months (-1, +2)".to_string()
+                    )
+                }
+            });
+        }
+        Ok(merged)
+    }
+}
+
+/// A `-m` parse error, pointing at the bytes of the offending token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthsError {
+    pub message: String,
+    pub input: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for MonthsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pad = self.input[..self.span.start].width();
+        let len = self.input[self.span.clone()].width().max(1);
+        writeln!(f, "{}", self.message)?;
+        writeln!(f, "  {}", self.input)?;
+        write!(f, "  {}{}", " ".repeat(pad), "^".repeat(len))
+    }
+}
+
+impl Error for MonthsError {}
+
+/// Parses `-m`: relative offsets when the value starts with `+` or `-`,
+/// otherwise a list of months.
+pub fn parse_month_selection(value: &str) -> Result<MonthSelection, MonthsError> {
+    if value.starts_with(['+', '-']) {
+        parse_relative_months(value).map(MonthSelection::Relative)
+    } else {
+        parse_months(value).map(MonthSelection::Absolute)
+    }
+}
+
+/// Parses a list of month offsets or inclusive `FROM..TO` offset ranges,
+/// e.g. `-1`, `+2` or `-1..+1,+6`.
+fn parse_relative_months(value: &str) -> Result<Vec<i32>, MonthsError> {
+    let error = |message: String, span: Range<usize>| MonthsError {
+        message,
+        input: value.to_string(),
+        span,
+    };
+    let re = Regex::new(r"^[+-]?\d{1,6}$").unwrap();
+    let mut offset = 0;
+    let mut months = vec![];
+    for ele in value.split(',') {
+        let at = offset;
+        offset += ele.len() + 1;
+        let whole = at..at + ele.len();
+        let (first, last) = ele.split_once("..").unwrap_or((ele, ele));
+        let parse = |s: &str, span: Range<usize>| {
+            re.is_match(s)
+                .then(|| s.parse::<i32>().ok())
+                .flatten()
+                .ok_or_else(|| error(format!("invalid relative month: \"{s}\""), span))
+        };
+        let s = parse(first, at..at + first.len())?;
+        let e = parse(last, whole.end - last.len()..whole.end)?;
+        if s > e {
+            return Err(error(
+                format!("First offset in range ({s}) must not be greater than second offset ({e})"),
+                whole,
+            ));
+        }
+        months.extend(s..=e);
+    }
+    months.sort();
+    months.dedup();
+    Ok(months)
+}
+
+#[allow(clippy::map_flatten)]
+pub fn parse_months(value: &str) -> Result<Months, MonthsError> {
+    let error = |message: String, span: Range<usize>| MonthsError {
+        message,
+        input: value.to_string(),
+        span,
+    };
+    let mut offset = 0;
     let range = value
         .split(",")
         .map(|ele| {
+            let at = offset;
+            offset += ele.len() + 1;
+            let whole = at..at + ele.len();
             let (s, e) = parse_range(ele)
                 .map(|r| (r.start, r.end))
-                .map_err(|_| format!("illegal list value: \"{ele}\""))?;
+                .map_err(|_| error(format!("illegal list value: \"{ele}\""), whole.clone()))?;
+            // Both numbers are ASCII digits, so their spans follow from their lengths.
+            let first = ele.split('-').next().unwrap_or_default();
+            let first = at..at + first.len();
+            let second = first.end + 1..whole.end;
 
             let s = match s {
                 Some(v) if (1..=12).contains(&v) => v,
                 _ => {
                     // 範囲外
-                    return Err(format!(
-                        "invalid month: \"{}\"",
-                        s.map_or_else(|| ele.to_string(), |v| v.to_string())
+                    return Err(error(
+                        format!(
+                            "invalid month: \"{}\"",
+                            s.map_or_else(|| ele.to_string(), |v| v.to_string())
+                        ),
+                        first,
                     ));
                 }
             };
 
             let e = match e {
                 Some(e) if !(1..=12).contains(&e) => {
-                    return Err(format!("invalid month: \"{}\"", e));
+                    return Err(error(format!("invalid month: \"{}\"", e), second));
                 }
                 Some(e) if s >= e => {
-                    return Err(format!(
-                        "First month in range ({s}) must be lower than second month ({e})"
+                    return Err(error(
+                        format!("First month in range ({s}) must be lower than second month ({e})"),
+                        whole,
                     ));
                 }
                 Some(e) => e,
@@ -60,6 +187,7 @@ pub fn parse_months(value: &str) -> Result<Months, String> {
     Ok(months)
 }
 
+#[allow(clippy::needless_borrow)]
 fn parse_range(range: &str) -> Result<Range<Option<usize>>, Box<dyn Error>> {
     let re = Regex::new(r#"^(\d+)(-(\d+)){0,1}$"#)?;
     let caps = re.captures(range);
@@ -78,6 +206,7 @@ fn parse_range(range: &str) -> Result<Range<Option<usize>>, Box<dyn Error>> {
     }
 }
 
+#[allow(clippy::map_clone)]
 fn sort_month_range_list(l: &[Range<usize>]) -> Vec<Range<usize>> {
     let mut l = l.iter().map(|e| e.clone()).collect::<Vec<_>>();
     l.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end)));
@@ -93,3 +222,70 @@ fn extract_month(arr: &[u32], offset: usize, range: Range<usize>) -> (usize, &[u
         (e, &arr[s..e])
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::months_parser::{parse_month_selection, parse_months, MonthSelection};
+
+    #[test]
+    fn test_parse_months() {
+        assert_eq!(parse_months("11-12,1,3"), Ok(vec![1, 3, 11, 12]));
+        assert_eq!(parse_months("1-3,2-4"), Ok(vec![1, 2, 3, 4]));
+
+        let span = |s| parse_months(s).unwrap_err().span;
+        assert_eq!(span("1,13-x,5"), 2..6);
+        assert_eq!(span("1,13-14,5"), 2..4);
+        assert_eq!(span("1,3-14"), 4..6);
+        assert_eq!(span("1,5-3"), 2..5);
+        assert_eq!(span(""), 0..0);
+        assert_eq!(span("1,,2"), 2..2);
+        assert_eq!(span("99999999999999999999999"), 0..23);
+
+        assert_eq!(
+            parse_months("1,13-x,5").unwrap_err().to_string(),
+            "illegal list value: \"13-x\"\n  1,13-x,5\n    ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_months() {
+        assert_eq!(
+            parse_month_selection("-1..+1,+6"),
+            Ok(MonthSelection::Relative(vec![-1, 0, 1, 6]))
+        );
+        assert_eq!(
+            parse_month_selection("+2"),
+            Ok(MonthSelection::Relative(vec![2]))
+        );
+        assert_eq!(
+            parse_month_selection("1,3"),
+            Ok(MonthSelection::Absolute(vec![1, 3]))
+        );
+
+        let span = |s| parse_month_selection(s).unwrap_err().span;
+        assert_eq!(span("-1,+x"), 3..5);
+        assert_eq!(span("-1..y"), 4..5);
+        assert_eq!(span("+1..-1"), 0..6);
+    }
+
+    #[test]
+    fn test_merge() {
+        let parse = |values: &[&str]| {
+            let selections = values
+                .iter()
+                .map(|v| parse_month_selection(v).unwrap())
+                .collect::<Vec<_>>();
+            MonthSelection::merge(&selections)
+        };
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(
+            parse(&["10-12", "1-3", "2"]),
+            Ok(Some(MonthSelection::Absolute(vec![1, 2, 3, 10, 11, 12])))
+        );
+        assert_eq!(
+            parse(&["+1", "-1..+1"]),
+            Ok(Some(MonthSelection::Relative(vec![-1, 0, 1])))
+        );
+        assert!(parse(&["1", "+1"]).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use clap::Args;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[derive(Debug, Default, Clone, Copy, Args)]
+pub struct NameFit {
+    /// Fit holiday/event names in listings and footnotes into N display columns, cut with …
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), global = true)]
+    name_width: Option<u32>,
+
+    /// Wrap names longer than --name-width onto indented lines instead of cutting them
+    #[arg(long, requires = "name_width", global = true)]
+    wrap_names: bool,
+}
+
+impl NameFit {
+    /// `name` within `--name-width` columns. Wrapped lines after the first
+    /// are indented by `indent` columns, to line up under the name.
+    pub fn fit<'a>(&self, name: &'a str, indent: usize) -> Cow<'a, str> {
+        let Some(columns) = self.name_width.map(|w| w as usize) else {
+            return Cow::Borrowed(name);
+        };
+        if name.width() <= columns {
+            Cow::Borrowed(name)
+        } else if self.wrap_names {
+            Cow::Owned(wrap(name, columns).join(&format!("\n{}", " ".repeat(indent))))
+        } else {
+            Cow::Owned(truncate(name, columns))
+        }
+    }
+}
+
+/// The longest prefix of `name` that fits in `columns` with a trailing `…`.
+pub fn truncate(name: &str, columns: usize) -> String {
+    let mut width = 0;
+    let mut cut = name
+        .chars()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width < columns
+        })
+        .collect::<String>();
+    cut.truncate(cut.trim_end().len());
+    cut.push('…');
+    cut
+}
+
+/// `name` in lines of at most `columns`, broken after the last space of a
+/// line when there is one and between any two characters otherwise, as
+/// Japanese text has no spaces.
+fn wrap(name: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for c in name.chars() {
+        if line.width() + c.width().unwrap_or(0) > columns && !line.is_empty() {
+            let rest = match line.rfind(' ') {
+                Some(i) if c != ' ' => line.split_off(i + 1),
+                _ => String::new(),
+            };
+            lines.push(line.trim_end().to_string());
+            line = rest;
+        }
+        if !(c == ' ' && line.is_empty()) {
+            line.push(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use crate::names::NameFit;
+
+    #[test]
+    fn test_fit() {
+        let fit = |wrap_names| NameFit {
+            name_width: Some(8),
+            wrap_names,
+        };
+        assert_eq!(fit(false).fit("元日", 2), "元日");
+        assert_eq!(fit(false).fit("天皇誕生日", 2), "天皇誕…");
+        assert_eq!(fit(false).fit("Mountain Day", 2), "Mountai…");
+        assert_eq!(fit(true).fit("スポーツの日", 2), "スポーツ\n  の日");
+        assert_eq!(
+            fit(true).fit("Respect for the Aged Day", 1),
+            "Respect\n for the\n Aged Day"
+        );
+        assert_eq!(NameFit::default().fit("スポーツの日", 2), "スポーツの日");
+    }
+}
@@ -0,0 +1,149 @@
+use std::{io, process::Command};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    agenda::window_end,
+    consts::{ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    deadline::{format_countdown, Deadline},
+    holiday::HolidayInfo,
+    Lang, LibResult,
+};
+
+/// Title and body summarizing today's entries and the deadlines due within `days`.
+/// Returns `None` when there is nothing to report.
+pub fn format_notification(
+    holiday_info: &HolidayInfo,
+    today: NaiveDate,
+    deadlines: &[Deadline],
+    days: u32,
+    lang: Lang,
+) -> Option<(String, String)> {
+    let end = window_end(today, days);
+    let due = deadlines
+        .iter()
+        .filter(|d| today <= d.date && d.date <= end)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut body = holiday_info
+        .holidays_on(today)
+        .iter()
+        .map(|h| h.name.clone())
+        .collect::<Vec<_>>();
+    body.extend(format_countdown(&due, holiday_info, today, lang));
+    if body.is_empty() {
+        return None;
+    }
+    let weekday = today.weekday().num_days_from_sunday() as usize;
+    let title = match lang {
+        Lang::Japanese => format!(
+            "{} ({})",
+            today.format("%Y/%m/%d"),
+            JAPANESE_WEEK_NAMES[weekday]
+        ),
+        Lang::English => format!(
+            "{} ({})",
+            today.format("%Y-%m-%d"),
+            ENGLISH_WEEK_NAMES[weekday]
+        ),
+    };
+    Some((title, body.join("\n")))
+}
+
+/// Shows a desktop notification with the platform's own tool.
+pub fn send_notification(title: &str, body: &str) -> LibResult<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(windows) {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-Command", WINDOWS_TOAST])
+            .env("CALP_TITLE", title)
+            .env("CALP_BODY", body);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=calp", title, body]);
+        command
+    };
+    // Whatever the tool prints is a diagnostic, not calp output.
+    let status = command
+        .stdout(io::stderr())
+        .status()
+        .map_err(|e| format!("cannot send notification: {e}"))?;
+    if !status.success() {
+        return Err(format!("cannot send notification: exited with {status}").into());
+    }
+    Ok(())
+}
+
+const WINDOWS_TOAST: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:CALP_TITLE)) | Out-Null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:CALP_BODY)) | Out-Null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($xml)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('calp').Show($toast)
+"#;
+
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        deadline::Deadline,
+        holiday::{Category, Holiday, HolidayInfo},
+        notify::format_notification,
+        Lang,
+    };
+
+    #[test]
+    fn test_format_notification() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut hi = HolidayInfo::new();
+        assert_eq!(
+            format_notification(&hi, date(1), &[], 7, Lang::English),
+            None
+        );
+
+        hi.add(
+            date(1),
+            Holiday {
+                name: "New Year".to_string(),
+                category: Category::National,
+                time: None,
+                span: None,
+                source: String::new(),
+            },
+        );
+        let deadlines = [
+            Deadline {
+                date: date(5),
+                name: "report".to_string(),
+            },
+            Deadline {
+                date: date(20),
+                name: "release".to_string(),
+            },
+        ];
+        let (title, body) =
+            format_notification(&hi, date(1), &deadlines, 7, Lang::English).unwrap();
+        assert_eq!(title, "2024-01-01 (Mo)");
+        assert_eq!(
+            body,
+            "New Year\nreport: 2024-01-05, 4 days left / 3 business days"
+        );
+    }
+}
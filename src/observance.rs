@@ -0,0 +1,186 @@
+use std::ops::RangeInclusive;
+
+use chrono::{Days, NaiveDate};
+use clap::{builder::PossibleValue, ValueEnum};
+
+use crate::{
+    holiday::{Category, Holiday, HolidayInfo},
+    jp_holidays::national_holidays,
+    lunisolar::{lunar_new_year, LunarCalendar},
+    Lang,
+};
+
+/// Computed holiday sets that can be overlaid on the calendar. Japan is
+/// also used on its own when no holiday file is found.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Observance {
+    /// Japanese national holidays, computed instead of read from a file
+    Japan,
+    /// Easter and the movable Christian feasts derived from it
+    Christian,
+    /// Chinese New Year (Spring Festival) and the Lantern Festival
+    LunarNewYear,
+    /// Korean Seollal holidays (the day before, of and after the lunar new year)
+    Seollal,
+}
+
+impl ValueEnum for Observance {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Japan,
+            Self::Christian,
+            Self::LunarNewYear,
+            Self::Seollal,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Observance::Japan => PossibleValue::new("japan"),
+            Observance::Christian => PossibleValue::new("christian"),
+            Observance::LunarNewYear => PossibleValue::new("lunar-new-year"),
+            Observance::Seollal => PossibleValue::new("seollal"),
+        })
+    }
+}
+
+pub fn add_observances(
+    holiday_info: &mut HolidayInfo,
+    observances: &[Observance],
+    years: RangeInclusive<i32>,
+    lang: Lang,
+) {
+    for observance in observances {
+        for year in years.clone() {
+            let days = match observance {
+                Observance::Japan => national_holidays(year, lang),
+                Observance::Christian => christian_feasts(year, lang),
+                Observance::LunarNewYear => chinese_new_year(year, lang),
+                Observance::Seollal => seollal(year, lang),
+            };
+            let source = observance
+                .to_possible_value()
+                .map_or_else(String::new, |v| v.get_name().to_string());
+            for (date, name) in days {
+                holiday_info.add(
+                    date,
+                    Holiday {
+                        name: name.to_string(),
+                        category: Category::National,
+                        time: None,
+                        span: None,
+                        source: source.clone(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Easter Sunday in the Gregorian calendar (anonymous Gregorian computus).
+pub fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+fn christian_feasts(year: i32, lang: Lang) -> Vec<(NaiveDate, &'static str)> {
+    offset_days(
+        easter(year),
+        &[
+            (-2, "聖金曜日", "Good Friday"),
+            (0, "復活祭", "Easter Sunday"),
+            (1, "復活祭月曜日", "Easter Monday"),
+            (39, "昇天祭", "Ascension Day"),
+            (49, "聖霊降臨祭", "Pentecost"),
+            (50, "聖霊降臨祭月曜日", "Whit Monday"),
+        ],
+        lang,
+    )
+}
+
+/// Days relative to a base date, named in Japanese and English.
+type Feasts<'a> = [(i64, &'a str, &'a str)];
+
+fn offset_days(
+    base: NaiveDate,
+    feasts: &Feasts<'static>,
+    lang: Lang,
+) -> Vec<(NaiveDate, &'static str)> {
+    feasts
+        .iter()
+        .filter_map(|(offset, ja, en)| {
+            let date = if *offset < 0 {
+                base.checked_sub_days(Days::new(offset.unsigned_abs()))
+            } else {
+                base.checked_add_days(Days::new(*offset as u64))
+            }?;
+            Some((
+                date,
+                match lang {
+                    Lang::Japanese => *ja,
+                    Lang::English => *en,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn chinese_new_year(year: i32, lang: Lang) -> Vec<(NaiveDate, &'static str)> {
+    let Some(new_year) = lunar_new_year(year, LunarCalendar::Chinese) else {
+        return vec![];
+    };
+    offset_days(
+        new_year,
+        &[
+            (0, "春節", "Lunar New Year"),
+            (14, "元宵節", "Lantern Festival"),
+        ],
+        lang,
+    )
+}
+
+fn seollal(year: i32, lang: Lang) -> Vec<(NaiveDate, &'static str)> {
+    let Some(new_year) = lunar_new_year(year, LunarCalendar::Korean) else {
+        return vec![];
+    };
+    offset_days(
+        new_year,
+        &[
+            (-1, "ソルラル連休", "Seollal holiday"),
+            (0, "ソルラル", "Seollal"),
+            (1, "ソルラル連休", "Seollal holiday"),
+        ],
+        lang,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::observance::easter;
+
+    #[test]
+    fn test_easter() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(easter(1961), ymd(1961, 4, 2));
+        assert_eq!(easter(2000), ymd(2000, 4, 23));
+        assert_eq!(easter(2019), ymd(2019, 4, 21));
+        assert_eq!(easter(2024), ymd(2024, 3, 31));
+        assert_eq!(easter(2025), ymd(2025, 4, 20));
+        assert_eq!(easter(2038), ymd(2038, 4, 25));
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::{last_day_in_month, tz::parse_tz};
+
+/// Values of one date component: `*`, `5`, `1..7`, `*/2`, `1/3`, comma-separated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Spec(Vec<(u32, Option<u32>, u32)>);
+
+impl Spec {
+    fn any() -> Spec {
+        Spec(vec![(0, None, 1)])
+    }
+
+    fn parse(s: &str, min: u32) -> Result<Spec, String> {
+        let invalid = || format!("invalid calendar value: \"{s}\"");
+        let items = s
+            .split(',')
+            .map(|item| {
+                let (range, step) = match item.split_once('/') {
+                    Some((range, step)) => (
+                        range,
+                        step.parse().ok().filter(|s| *s > 0).ok_or_else(invalid)?,
+                    ),
+                    None => (item, 1),
+                };
+                let (start, end) = match range {
+                    "*" => (min, None),
+                    r => match r.split_once("..") {
+                        Some((a, b)) => (
+                            a.parse().map_err(|_| invalid())?,
+                            Some(b.parse().map_err(|_| invalid())?),
+                        ),
+                        // `a/n` repeats from a onwards.
+                        None if step > 1 => (r.parse().map_err(|_| invalid())?, None),
+                        None => {
+                            let v = r.parse().map_err(|_| invalid())?;
+                            (v, Some(v))
+                        }
+                    },
+                };
+                Ok((start, end, step))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Spec(items))
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.0.iter().any(|(start, end, step)| {
+            value >= *start
+                && end.is_none_or(|e| value <= e)
+                && (value - start).is_multiple_of(*step)
+        })
+    }
+}
+
+/// The date part of a systemd calendar event (`systemd.time(7)`); the time part is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnCalendar {
+    weekdays: Option<[bool; 7]>,
+    year: Spec,
+    month: Spec,
+    day: Spec,
+    /// `~` counts days back from the end of the month.
+    day_from_end: bool,
+}
+
+impl OnCalendar {
+    /// Whether the event elapses at least once on `date`.
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        let day = if self.day_from_end {
+            last_day_in_month(date.year(), date.month()).day() - date.day() + 1
+        } else {
+            date.day()
+        };
+        self.weekdays
+            .is_none_or(|w| w[date.weekday().num_days_from_monday() as usize])
+            && self.year.matches(date.year() as u32)
+            && self.month.matches(date.month())
+            && self.day.matches(day)
+    }
+}
+
+/// Full weekday names, whose first three letters are the abbreviations.
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Parses a calendar event such as `Mon..Fri *-*-1..7 12:00` or `weekly`.
+pub fn parse_oncalendar(s: &str) -> Result<OnCalendar, String> {
+    let expanded = match s.trim().to_ascii_lowercase().as_str() {
+        "minutely" | "hourly" | "daily" => "*-*-*".to_string(),
+        "weekly" => "Mon *-*-*".to_string(),
+        "monthly" => "*-*-01".to_string(),
+        "quarterly" => "*-01,04,07,10-01".to_string(),
+        "semiannually" => "*-01,07-01".to_string(),
+        "yearly" | "annually" => "*-01-01".to_string(),
+        _ => s.trim().to_string(),
+    };
+
+    let mut event = OnCalendar {
+        weekdays: None,
+        year: Spec::any(),
+        month: Spec::any(),
+        day: Spec::any(),
+        day_from_end: false,
+    };
+    let mut tokens = expanded.split_whitespace().peekable();
+    if let Some(t) = tokens.next_if(|t| t.starts_with(|c: char| c.is_ascii_alphabetic())) {
+        if parse_tz(t).is_err() {
+            event.weekdays = Some(parse_weekdays(t)?);
+        }
+    }
+    if let Some(date) = tokens.next_if(|t| !t.contains(':') && t.contains(['-', '~'])) {
+        let (rest, day, from_end) = match date.rsplit_once('~') {
+            Some((rest, day)) => (rest, day, true),
+            None => match date.rsplit_once('-') {
+                Some((rest, day)) => (rest, day, false),
+                None => return Err(format!("invalid calendar date: \"{date}\"")),
+            },
+        };
+        let (year, month) = rest.split_once('-').unwrap_or(("*", rest));
+        event.year = Spec::parse(year, 1)?;
+        event.month = Spec::parse(month, 1)?;
+        event.day = Spec::parse(day, 1)?;
+        event.day_from_end = from_end;
+    }
+    for t in tokens {
+        // The time and an optional timezone do not affect which days match.
+        if !t.contains(':') && parse_tz(t).is_err() {
+            return Err(format!("invalid calendar event: \"{s}\""));
+        }
+    }
+    Ok(event)
+}
+
+fn parse_weekdays(s: &str) -> Result<[bool; 7], String> {
+    let index = |name: &str| {
+        let lower = name.to_ascii_lowercase();
+        WEEKDAY_NAMES
+            .iter()
+            .position(|w| lower == *w || lower == w[..3])
+            .ok_or_else(|| format!("invalid weekday: \"{name}\""))
+    };
+    let mut weekdays = [false; 7];
+    for part in s.split(',') {
+        match part.split_once("..") {
+            Some((a, b)) => {
+                let (a, b) = (index(a)?, index(b)?);
+                for (i, w) in weekdays.iter_mut().enumerate() {
+                    *w |= if a <= b {
+                        (a..=b).contains(&i)
+                    } else {
+                        i >= a || i <= b
+                    };
+                }
+            }
+            None => weekdays[index(part)?] = true,
+        }
+    }
+    Ok(weekdays)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::oncalendar::parse_oncalendar;
+
+    #[test]
+    fn test_parse_oncalendar() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        // weekdays in the first seven days of the month
+        let event = parse_oncalendar("Mon..Fri *-*-1..7 12:00").unwrap();
+        assert!(event.matches(ymd(2024, 10, 1)));
+        assert!(!event.matches(ymd(2024, 10, 5)));
+        assert!(!event.matches(ymd(2024, 10, 8)));
+
+        // last day of February
+        let event = parse_oncalendar("*-02~01").unwrap();
+        assert!(event.matches(ymd(2024, 2, 29)));
+        assert!(!event.matches(ymd(2023, 2, 27)));
+
+        let event = parse_oncalendar("Sat,Sun 2024-*-1/10 Asia/Tokyo").unwrap();
+        assert!(event.matches(ymd(2024, 6, 1)));
+        assert!(!event.matches(ymd(2024, 6, 2)));
+        assert!(event.matches(ymd(2024, 8, 11)));
+
+        let event = parse_oncalendar("quarterly").unwrap();
+        assert!(event.matches(ymd(2024, 7, 1)));
+        assert!(!event.matches(ymd(2024, 8, 1)));
+
+        let event = parse_oncalendar("monday,WEDNESDAY").unwrap();
+        assert!(event.matches(ymd(2024, 10, 2)));
+        assert!(!event.matches(ymd(2024, 10, 3)));
+
+        assert!(parse_oncalendar("Mon..Fry").is_err());
+        assert!(parse_oncalendar("monkey").is_err());
+        assert!(parse_oncalendar("Mond").is_err());
+        assert!(parse_oncalendar("*-*-x").is_err());
+    }
+}
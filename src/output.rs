@@ -0,0 +1,151 @@
+use std::{
+    io::{self, BufWriter, Stdout, Write},
+    str,
+};
+
+use clap::{builder::PossibleValue, ValueEnum};
+use encoding_rs::{EncoderResult, SHIFT_JIS};
+
+use crate::{
+    caps::Caps,
+    clipboard::{self, strip_ansi},
+    LibResult,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OutputEncoding {
+    Utf8,
+    ShiftJis,
+}
+
+impl ValueEnum for OutputEncoding {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Utf8, Self::ShiftJis]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            OutputEncoding::Utf8 => PossibleValue::new("utf8"),
+            OutputEncoding::ShiftJis => PossibleValue::new("sjis"),
+        })
+    }
+}
+
+/// Buffered stdout that degrades output to the terminal's capabilities and
+/// re-encodes UTF-8 text to the selected output encoding.
+pub struct Output {
+    inner: BufWriter<Stdout>,
+    encoding: OutputEncoding,
+    caps: Caps,
+    /// Incomplete line, held back while degrading, since escape sequences
+    /// may be split across writes.
+    line: Vec<u8>,
+    /// Trailing bytes of an incomplete UTF-8 sequence from the previous write.
+    pending: Vec<u8>,
+    /// Copy of everything written, kept for `--copy`.
+    capture: Option<Vec<u8>>,
+}
+
+impl Output {
+    pub fn new(encoding: OutputEncoding, caps: Caps, copy: bool) -> Output {
+        Output {
+            inner: BufWriter::new(io::stdout()),
+            encoding,
+            caps,
+            line: Vec::new(),
+            pending: Vec::new(),
+            capture: copy.then(Vec::new),
+        }
+    }
+
+    /// Flushes stdout and, if capturing, places the color-stripped text on the clipboard.
+    pub fn finish(&mut self) -> LibResult<()> {
+        self.flush()?;
+        if let Some(capture) = self.capture.take() {
+            clipboard::copy(&strip_ansi(&String::from_utf8_lossy(&capture)))?;
+        }
+        Ok(())
+    }
+
+    /// Writes `bytes` unchanged, for formats such as iCalendar that are
+    /// UTF-8 whatever `--output-encoding` says and hold no escape codes.
+    pub fn write_raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.flush()?;
+        if let Some(capture) = &mut self.capture {
+            capture.extend_from_slice(bytes);
+        }
+        self.inner.write_all(bytes)
+    }
+
+    fn write_sjis(&mut self, s: &str) -> io::Result<()> {
+        let mut encoder = SHIFT_JIS.new_encoder();
+        let mut src = s;
+        let mut dst = [0; 1024];
+        loop {
+            let (result, read, written) =
+                encoder.encode_from_utf8_without_replacement(src, &mut dst, false);
+            self.inner.write_all(&dst[..written])?;
+            src = &src[read..];
+            match result {
+                EncoderResult::InputEmpty => return Ok(()),
+                EncoderResult::OutputFull => (),
+                // Characters without a Shift_JIS mapping (e.g. emoji)
+                EncoderResult::Unmappable(_) => self.inner.write_all(b"?")?,
+            }
+        }
+    }
+}
+
+impl Output {
+    fn emit(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self.encoding {
+            OutputEncoding::Utf8 => self.inner.write_all(buf),
+            OutputEncoding::ShiftJis => {
+                self.pending.extend_from_slice(buf);
+                let valid = match str::from_utf8(&self.pending) {
+                    Ok(s) => s.len(),
+                    Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                    Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                };
+                let pending = std::mem::take(&mut self.pending);
+                // Safe to unwrap: validated above.
+                self.write_sjis(str::from_utf8(&pending[..valid]).unwrap())?;
+                self.pending = pending[valid..].to_vec();
+                Ok(())
+            }
+        }
+    }
+
+    fn emit_degraded(&mut self, line: &[u8]) -> io::Result<()> {
+        let line = String::from_utf8_lossy(line);
+        let degraded = self.caps.degrade(&line).into_owned();
+        self.emit(degraded.as_bytes())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(capture) = &mut self.capture {
+            capture.extend_from_slice(buf);
+        }
+        if !self.caps.degrades() {
+            self.emit(buf)?;
+            return Ok(buf.len());
+        }
+        self.line.extend_from_slice(buf);
+        if let Some(end) = self.line.iter().rposition(|b| *b == b'\n') {
+            let rest = self.line.split_off(end + 1);
+            let lines = std::mem::replace(&mut self.line, rest);
+            self.emit_degraded(&lines)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.emit_degraded(&line)?;
+        }
+        self.inner.flush()
+    }
+}
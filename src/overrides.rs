@@ -0,0 +1,98 @@
+use std::fs;
+
+use chrono::NaiveDate;
+
+use crate::{
+    date_parser::parse_date,
+    holiday::{Category, Holiday, HolidayInfo},
+    LibResult,
+};
+
+/// A single-date change layered over every other source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Override {
+    /// `+date,name`: a one-off holiday, e.g. 2019/05/01 即位の日
+    Add(NaiveDate, String),
+    /// `-date[,name]`: drops that day's entries, or only the one called `name`
+    Remove(NaiveDate, Option<String>),
+}
+
+/// Reads an override file of `+date,name` and `-date[,name]` lines.
+/// Lines starting with `#` are comments.
+pub fn load_overrides(path: &str) -> LibResult<Vec<Override>> {
+    let s = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    s.trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|(n, line)| parse_override(line).map_err(|e| format!("{path}:{}: {e}", n + 1).into()))
+        .collect()
+}
+
+fn parse_override(line: &str) -> Result<Override, String> {
+    let line = line.trim();
+    let (date, name) = line.split_once(',').unwrap_or((line, ""));
+    let name = name.trim();
+    if let Some(date) = date.strip_prefix('+') {
+        if name.is_empty() {
+            return Err(format!("missing name: \"{line}\""));
+        }
+        Ok(Override::Add(parse_date(date.trim())?, name.to_string()))
+    } else if let Some(date) = date.strip_prefix('-') {
+        let name = (!name.is_empty()).then(|| name.to_string());
+        Ok(Override::Remove(parse_date(date.trim())?, name))
+    } else {
+        Err(format!("expected a leading + or -: \"{line}\""))
+    }
+}
+
+/// Applies `overrides` in order; run after all files and observances are loaded.
+pub fn apply_overrides(holiday_info: &mut HolidayInfo, overrides: &[Override], source: &str) {
+    for o in overrides {
+        match o {
+            Override::Add(date, name) => holiday_info.add(
+                *date,
+                Holiday {
+                    name: name.clone(),
+                    category: Category::Override,
+                    time: None,
+                    span: None,
+                    source: source.to_string(),
+                },
+            ),
+            Override::Remove(date, name) => holiday_info.remove(*date, name.as_deref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        holiday::{Category, HolidayInfo},
+        overrides::{apply_overrides, parse_override, Override},
+    };
+
+    #[test]
+    fn test_overrides() {
+        let date = NaiveDate::from_ymd_opt(2019, 5, 1).unwrap();
+        let add = parse_override("+2019/05/01,即位の日").unwrap();
+        assert_eq!(add, Override::Add(date, "即位の日".to_string()));
+        assert_eq!(
+            parse_override("-2019-05-01"),
+            Ok(Override::Remove(date, None))
+        );
+        assert!(parse_override("2019/05/01,x").is_err());
+        assert!(parse_override("+2019/05/01").is_err());
+
+        let mut hi = HolidayInfo::new();
+        apply_overrides(&mut hi, &[add], "overrides.csv");
+        assert_eq!(hi.category(2019, 5, 1), Some(Category::Override));
+        assert!(hi.is_day_off(date));
+
+        apply_overrides(&mut hi, &[Override::Remove(date, None)], "overrides.csv");
+        assert_eq!(hi.category(2019, 5, 1), None);
+        assert!(hi.holidays_on(date).is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{env, ffi::OsString, path::PathBuf};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Platform {
+    /// Linux and the BSDs, following the XDG base directory spec
+    Xdg,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    const CURRENT: Platform = if cfg!(windows) {
+        Platform::Windows
+    } else if cfg!(target_os = "macos") {
+        Platform::MacOs
+    } else {
+        Platform::Xdg
+    };
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Kind {
+    Config,
+    State,
+    Cache,
+}
+
+/// `kind`'s directory for calp on `platform`, with environment variables
+/// read through `var`. An XDG variable that is set wins on every platform.
+fn dir(kind: Kind, platform: Platform, var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let (xdg, xdg_default, macos, windows) = match kind {
+        Kind::Config => (
+            "XDG_CONFIG_HOME",
+            ".config",
+            "Library/Application Support",
+            "APPDATA",
+        ),
+        Kind::State => (
+            "XDG_STATE_HOME",
+            ".local/state",
+            "Library/Application Support",
+            "LOCALAPPDATA",
+        ),
+        Kind::Cache => ("XDG_CACHE_HOME", ".cache", "Library/Caches", "LOCALAPPDATA"),
+    };
+    let path = |name: &str| var(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    path(xdg)
+        .or_else(|| match platform {
+            Platform::Xdg => home(platform, &var).map(|home| home.join(xdg_default)),
+            Platform::MacOs => home(platform, &var).map(|home| home.join(macos)),
+            Platform::Windows => path(windows),
+        })
+        .map(|dir| dir.join("calp"))
+}
+
+fn home(platform: Platform, var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let path = |name: &str| var(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    match platform {
+        Platform::Windows => path("USERPROFILE").or_else(|| path("HOME")),
+        _ => path("HOME"),
+    }
+}
+
+/// `$HOME`, or `%USERPROFILE%` on Windows.
+pub fn home_dir() -> Option<PathBuf> {
+    home(Platform::CURRENT, |name| env::var_os(name))
+}
+
+/// `$XDG_CONFIG_HOME/calp` or `~/.config/calp`; `~/Library/Application
+/// Support/calp` on macOS and `%APPDATA%\calp` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    dir(Kind::Config, Platform::CURRENT, |name| env::var_os(name))
+}
+
+/// `$XDG_STATE_HOME/calp` or `~/.local/state/calp`; `~/Library/Application
+/// Support/calp` on macOS and `%LOCALAPPDATA%\calp` on Windows.
+pub fn state_dir() -> Option<PathBuf> {
+    dir(Kind::State, Platform::CURRENT, |name| env::var_os(name))
+}
+
+/// `$XDG_CACHE_HOME/calp` or `~/.cache/calp`; `~/Library/Caches/calp` on
+/// macOS and `%LOCALAPPDATA%\calp` on Windows.
+pub fn cache_dir() -> Option<PathBuf> {
+    dir(Kind::Cache, Platform::CURRENT, |name| env::var_os(name))
+}
+
+/// The config file, if there is one: `config.toml` in [`config_dir`], or
+/// in `~/.config/calp`, where it was looked for on every platform before.
+pub fn config_file() -> Option<PathBuf> {
+    let legacy = home_dir().map(|home| home.join(".config").join("calp"));
+    [config_dir(), legacy]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join("config.toml"))
+        .find(|path| path.exists())
+}
+
+/// Where the downloaded Cabinet Office list goes: `shuku.csv` in [`config_dir`].
+pub fn holiday_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("shuku.csv"))
+}
+
+/// Holiday files read when none is given, most preferred first: the
+/// [`holiday_file`], then the legacy `~/.calp_shuku`.
+pub fn holiday_file_candidates() -> Vec<PathBuf> {
+    let legacy = home_dir().map(|home| home.join(".calp_shuku"));
+    [holiday_file(), legacy].into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{ffi::OsString, path::PathBuf};
+
+    use crate::paths::{dir, Kind, Platform};
+
+    #[test]
+    fn test_dir() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| OsString::from(v))
+            }
+        };
+        let home = env(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "")]);
+        assert_eq!(
+            dir(Kind::Config, Platform::Xdg, home),
+            Some(PathBuf::from("/home/me/.config/calp"))
+        );
+        assert_eq!(
+            dir(Kind::State, Platform::Xdg, home),
+            Some(PathBuf::from("/home/me/.local/state/calp"))
+        );
+        assert_eq!(
+            dir(Kind::Cache, Platform::MacOs, home),
+            Some(PathBuf::from("/home/me/Library/Caches/calp"))
+        );
+        let xdg = env(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/cfg")]);
+        assert_eq!(
+            dir(Kind::Config, Platform::MacOs, xdg),
+            Some(PathBuf::from("/cfg/calp"))
+        );
+        let windows = env(&[("APPDATA", "C:/Users/me/AppData/Roaming")]);
+        assert_eq!(
+            dir(Kind::Config, Platform::Windows, windows),
+            Some(PathBuf::from("C:/Users/me/AppData/Roaming/calp"))
+        );
+        assert_eq!(dir(Kind::Cache, Platform::Windows, windows), None);
+        assert_eq!(dir(Kind::Config, Platform::Xdg, env(&[])), None);
+    }
+}
@@ -0,0 +1,492 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use crate::{
+    date_parser::parse_date, month_layout, paths::state_dir, watch::POLL_INTERVAL, Lang, LibResult,
+    MAX_DATE, MIN_DATE,
+};
+
+/// How [`pick`] ended, with the date the cursor was on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PickOutcome {
+    Picked(NaiveDate),
+    Cancelled(NaiveDate),
+}
+
+/// A change to the editable event on a day.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Edit {
+    Add(String),
+    Rename { from: String, to: String },
+    Remove(String),
+}
+
+/// What [`pick`] shows and edits.
+pub trait PickView {
+    /// The screen with the cursor on `cursor`, laid out as [`day_at`] expects.
+    fn draw(&self, cursor: NaiveDate) -> Vec<String>;
+
+    /// Name of the first editable event on `date`.
+    fn event_on(&self, date: NaiveDate) -> Option<String>;
+
+    fn edit(&mut self, date: NaiveDate, edit: Edit) -> LibResult<()>;
+
+    /// The next day after `date` with a holiday/event matching `query`.
+    fn find_next(&self, date: NaiveDate, query: &str) -> Option<NaiveDate>;
+
+    /// Reloads holidays/events whose files changed; whether anything did.
+    fn reload(&mut self) -> LibResult<bool>;
+}
+
+/// A keypress understood by [`pick`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    PrevMonth,
+    NextMonth,
+    Today,
+    Enter,
+    Cancel,
+    /// Add an event on the selected day
+    AddEvent,
+    /// Rename, or clear to delete, the selected day's event
+    EditEvent,
+    /// Jump to the next day whose holiday/event name matches a query
+    Search,
+    /// Left click at a zero-based terminal cell
+    Click {
+        column: usize,
+        row: usize,
+    },
+}
+
+/// Decodes the keys in one read from a raw-mode terminal: arrows or hjkl move
+/// by day/week, PageUp/PageDown, p/n or the scroll wheel by month, t jumps
+/// to today. Mouse events are SGR-encoded (`ESC [ < button ; x ; y M`).
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let (key, len) = match &bytes[i..] {
+            [0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+            [0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+            [0x1b, b'[', b'C', ..] => (Some(Key::Right), 3),
+            [0x1b, b'[', b'D', ..] => (Some(Key::Left), 3),
+            [0x1b, b'[', b'5', b'~', ..] => (Some(Key::PrevMonth), 4),
+            [0x1b, b'[', b'6', b'~', ..] => (Some(Key::NextMonth), 4),
+            [0x1b, b'[', b'<', rest @ ..] => {
+                let end = rest.iter().position(|b| *b == b'M' || *b == b'm');
+                let key = end.and_then(|e| mouse_key(&rest[..e], rest[e] == b'M'));
+                (key, 3 + end.map_or(rest.len(), |e| e + 1))
+            }
+            // Other escape sequences are skipped; a lone ESC cancels.
+            [0x1b, b'[', rest @ ..] => {
+                let end = rest.iter().position(|b| (0x40..=0x7e).contains(b));
+                (None, 2 + end.map_or(rest.len(), |e| e + 1))
+            }
+            [0x1b, ..] => (Some(Key::Cancel), 1),
+            [b, ..] => (
+                match b {
+                    b'h' => Some(Key::Left),
+                    b'l' => Some(Key::Right),
+                    b'k' => Some(Key::Up),
+                    b'j' => Some(Key::Down),
+                    b'p' => Some(Key::PrevMonth),
+                    b'n' => Some(Key::NextMonth),
+                    b't' => Some(Key::Today),
+                    b'a' => Some(Key::AddEvent),
+                    b'e' => Some(Key::EditEvent),
+                    b'/' => Some(Key::Search),
+                    b'\r' | b'\n' => Some(Key::Enter),
+                    // q, Ctrl-C, Ctrl-D
+                    b'q' | 0x03 | 0x04 => Some(Key::Cancel),
+                    _ => None,
+                },
+                1,
+            ),
+            [] => break,
+        };
+        keys.extend(key);
+        i += len;
+    }
+    keys
+}
+
+/// The key for the parameters of an SGR mouse event; releases and other
+/// buttons are ignored.
+fn mouse_key(params: &[u8], pressed: bool) -> Option<Key> {
+    let params = String::from_utf8_lossy(params);
+    let mut params = params.split(';').map(|p| p.parse::<usize>().ok());
+    let (button, x, y) = (params.next()??, params.next()??, params.next()??);
+    match button {
+        0 if pressed => Some(Key::Click {
+            column: x.checked_sub(1)?,
+            row: y.checked_sub(1)?,
+        }),
+        64 => Some(Key::PrevMonth),
+        65 => Some(Key::NextMonth),
+        _ => None,
+    }
+}
+
+/// The day drawn at a terminal cell by `format_month` for the month of
+/// `cursor`: a header and weekday row, then a row of 3-column cells per week.
+pub fn day_at(
+    cursor: NaiveDate,
+    first_weekday: Weekday,
+    column: usize,
+    row: usize,
+) -> Option<NaiveDate> {
+    let week = row.checked_sub(2)?;
+    let weeks = month_layout(cursor.year(), cursor.month(), first_weekday);
+    let day = (*weeks.get(week)?.get(column / 3)?)?;
+    cursor.with_day(day)
+}
+
+/// Where the cursor goes from `date` on a movement key; other keys leave it.
+/// Moving by month keeps the day, clamped to the end of shorter months, and
+/// the cursor stays within [`MIN_DATE`]-[`MAX_DATE`].
+pub fn move_cursor(date: NaiveDate, key: Key, today: NaiveDate) -> NaiveDate {
+    let moved = match key {
+        Key::Left => date.checked_sub_days(Days::new(1)),
+        Key::Right => date.checked_add_days(Days::new(1)),
+        Key::Up => date.checked_sub_days(Days::new(7)),
+        Key::Down => date.checked_add_days(Days::new(7)),
+        Key::PrevMonth => date.checked_sub_months(Months::new(1)),
+        Key::NextMonth => date.checked_add_months(Months::new(1)),
+        Key::Today => Some(today),
+        _ => None,
+    };
+    moved
+        .filter(|d| (MIN_DATE..=MAX_DATE).contains(d))
+        .unwrap_or(date)
+}
+
+/// The controlling terminal in raw mode on the alternate screen, restored when dropped.
+struct RawTerminal {
+    tty: File,
+    saved: String,
+}
+
+impl RawTerminal {
+    fn open() -> LibResult<RawTerminal> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(|e| format!("pick needs a terminal: /dev/tty: {e}"))?;
+        let saved = stty(&tty, &["-g"])?;
+        // Reads time out after POLL_INTERVAL (in tenths of a second) so
+        // that files can be polled.
+        let timeout = (POLL_INTERVAL.as_millis() / 100).to_string();
+        stty(&tty, &["raw", "-echo", "min", "0", "time", &timeout])?;
+        let mut terminal = RawTerminal {
+            tty,
+            saved: saved.trim().to_string(),
+        };
+        // Alternate screen, cursor hidden, SGR mouse reporting
+        write!(terminal.tty, "\x1b[?1049h\x1b[?25l\x1b[?1000h\x1b[?1006h")?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = write!(self.tty, "\x1b[?1006l\x1b[?1000l\x1b[?25h\x1b[?1049l");
+        let _ = stty(&self.tty, &[&self.saved]);
+    }
+}
+
+/// Runs `stty` on `tty`, returning what it printed.
+fn stty(tty: &File, args: &[&str]) -> LibResult<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(tty.try_clone()?)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("stty: {e}"))?;
+    if !output.status.success() {
+        return Err("stty failed; is this a terminal?".into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// What a [`Prompt`] is typed for.
+enum PromptKind {
+    AddEvent,
+    /// Renaming this event
+    EditEvent(String),
+    Search,
+}
+
+/// A line being typed at the bottom of the screen.
+struct Prompt {
+    kind: PromptKind,
+    text: String,
+}
+
+/// Applies typed `bytes` to `text`: `Some(true)` on Enter, `Some(false)` on ESC.
+fn edit_line(text: &mut String, bytes: &[u8]) -> Option<bool> {
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '\r' | '\n' => return Some(true),
+            '\x1b' | '\x03' => return Some(false),
+            '\x7f' | '\x08' => {
+                text.pop();
+            }
+            c if !c.is_control() => text.push(c),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Draws the screen of `view` and, below it, the prompt or status line.
+fn draw(
+    tty: &mut File,
+    view: &impl PickView,
+    date: NaiveDate,
+    prompt: Option<&Prompt>,
+    status: &str,
+    lang: Lang,
+) -> LibResult<()> {
+    // Raw mode needs explicit carriage returns.
+    write!(tty, "\x1b[H\x1b[2J")?;
+    for line in view.draw(date) {
+        write!(tty, "{line}\r\n")?;
+    }
+    match prompt {
+        Some(p) => {
+            let label = match (lang, &p.kind) {
+                (Lang::Japanese, PromptKind::AddEvent) => "追加",
+                (Lang::Japanese, PromptKind::EditEvent(_)) => "編集 (空で削除)",
+                (Lang::Japanese, PromptKind::Search) => "検索",
+                (Lang::English, PromptKind::AddEvent) => "Add",
+                (Lang::English, PromptKind::EditEvent(_)) => "Edit (empty to delete)",
+                (Lang::English, PromptKind::Search) => "Search",
+            };
+            write!(tty, "{label}: {}_\r\n", p.text)?;
+        }
+        None => write!(tty, "{status}\r\n")?,
+    }
+    tty.flush()?;
+    Ok(())
+}
+
+/// Lets the user move through the calendar of `view` on the terminal,
+/// starting at `start`, until a date is chosen with Enter, or by clicking
+/// the selected day, or the user quits. `a` and `e` add and edit events;
+/// `/` searches, and an empty query repeats the last search. Changed
+/// holiday/event files are reloaded while waiting for keys.
+pub fn pick(
+    view: &mut impl PickView,
+    start: NaiveDate,
+    today: NaiveDate,
+    first_weekday: Weekday,
+    lang: Lang,
+) -> LibResult<PickOutcome> {
+    let mut terminal = RawTerminal::open()?;
+    let mut date = start;
+    let mut prompt: Option<Prompt> = None;
+    let mut status = String::new();
+    let mut last_query = String::new();
+    let mut buf = [0; 32];
+    let mut redraw = true;
+    loop {
+        if redraw {
+            draw(
+                &mut terminal.tty,
+                view,
+                date,
+                prompt.as_ref(),
+                &status,
+                lang,
+            )?;
+        }
+        redraw = true;
+        let waited = Instant::now();
+        let n = terminal.tty.read(&mut buf)?;
+        if n == 0 {
+            // Reads time out after POLL_INTERVAL; one that returns at once
+            // means the terminal is gone.
+            if waited.elapsed() < POLL_INTERVAL / 10 {
+                return Ok(PickOutcome::Cancelled(date));
+            }
+            match view.reload() {
+                Ok(changed) => redraw = changed,
+                Err(e) => status = e.to_string(),
+            }
+            continue;
+        }
+        if let Some(p) = &mut prompt {
+            let done = edit_line(&mut p.text, &buf[..n]);
+            if done == Some(true) {
+                let text = p.text.trim().to_string();
+                let edit = match std::mem::replace(&mut p.kind, PromptKind::AddEvent) {
+                    PromptKind::AddEvent => Some(Edit::Add(text)),
+                    PromptKind::EditEvent(from) if text.is_empty() => Some(Edit::Remove(from)),
+                    PromptKind::EditEvent(from) => Some(Edit::Rename { from, to: text }),
+                    PromptKind::Search => {
+                        if !text.is_empty() {
+                            last_query = text;
+                        }
+                        if !last_query.is_empty() {
+                            match view.find_next(date, &last_query) {
+                                Some(found) => date = found,
+                                None => {
+                                    status = match lang {
+                                        Lang::Japanese => format!("見つかりません: {last_query}"),
+                                        Lang::English => format!("Not found: {last_query}"),
+                                    }
+                                }
+                            }
+                        }
+                        None
+                    }
+                };
+                if let Some(edit) = edit {
+                    status = match view.edit(date, edit) {
+                        Ok(()) => String::new(),
+                        Err(e) => e.to_string(),
+                    };
+                }
+            }
+            if done.is_some() {
+                prompt = None;
+            }
+            continue;
+        }
+        status.clear();
+        for key in parse_keys(&buf[..n]) {
+            match key {
+                Key::Enter => return Ok(PickOutcome::Picked(date)),
+                Key::Cancel => return Ok(PickOutcome::Cancelled(date)),
+                Key::Click { column, row } => match day_at(date, first_weekday, column, row) {
+                    Some(day) if day == date => return Ok(PickOutcome::Picked(date)),
+                    Some(day) => date = day,
+                    None => (),
+                },
+                Key::AddEvent => {
+                    prompt = Some(Prompt {
+                        kind: PromptKind::AddEvent,
+                        text: String::new(),
+                    });
+                    break;
+                }
+                Key::Search => {
+                    prompt = Some(Prompt {
+                        kind: PromptKind::Search,
+                        text: String::new(),
+                    });
+                    break;
+                }
+                Key::EditEvent => {
+                    if let Some(name) = view.event_on(date) {
+                        prompt = Some(Prompt {
+                            kind: PromptKind::EditEvent(name.clone()),
+                            text: name,
+                        });
+                        break;
+                    }
+                }
+                key => date = move_cursor(date, key, today),
+            }
+        }
+    }
+}
+
+/// `pick` in the state directory.
+fn state_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("pick"))
+}
+
+/// The date the cursor was on when [`pick`] last exited, if saved.
+pub fn load_last_viewed() -> Option<NaiveDate> {
+    let s = fs::read_to_string(state_path()?).ok()?;
+    parse_date(s.trim()).ok()
+}
+
+/// Remembers `date` for the next [`load_last_viewed`]. Best effort: losing
+/// the state must not fail the pick.
+pub fn save_last_viewed(date: NaiveDate) {
+    let Some(path) = state_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, format!("{}\n", date.format("%Y-%m-%d")));
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{
+        pick::{day_at, edit_line, move_cursor, parse_keys, Key},
+        MAX_DATE, MIN_DATE,
+    };
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"\x1b[A\x1b[6~jq"),
+            [Key::Up, Key::NextMonth, Key::Down, Key::Cancel]
+        );
+        assert_eq!(parse_keys(b"\x1b[1;5Ct\r"), [Key::Today, Key::Enter]);
+        assert_eq!(parse_keys(b"\x1b"), [Key::Cancel]);
+        assert_eq!(parse_keys(b"/"), [Key::Search]);
+        assert_eq!(
+            parse_keys(b"\x1b[<0;7;4M\x1b[<0;7;4m\x1b[<65;1;1M"),
+            [Key::Click { column: 6, row: 3 }, Key::NextMonth]
+        );
+    }
+
+    #[test]
+    fn test_edit_line() {
+        let mut text = String::new();
+        assert_eq!(edit_line(&mut text, "歯医者x".as_bytes()), None);
+        assert_eq!(edit_line(&mut text, b"\x7f\r"), Some(true));
+        assert_eq!(text, "歯医者");
+        assert_eq!(edit_line(&mut text, b"\x1b"), Some(false));
+    }
+
+    #[test]
+    fn test_day_at() {
+        // 2024-09-01 is a Sunday
+        let cursor = NaiveDate::from_ymd_opt(2024, 9, 15).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 9, d);
+        assert_eq!(day_at(cursor, Weekday::Sun, 0, 2), day(1));
+        assert_eq!(day_at(cursor, Weekday::Sun, 7, 3), day(10));
+        assert_eq!(day_at(cursor, Weekday::Mon, 18, 2), day(1));
+        assert_eq!(day_at(cursor, Weekday::Mon, 0, 2), None);
+        assert_eq!(day_at(cursor, Weekday::Sun, 0, 1), None);
+        assert_eq!(day_at(cursor, Weekday::Sun, 30, 3), None);
+    }
+
+    #[test]
+    fn test_move_cursor() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let today = date(6, 1);
+        assert_eq!(move_cursor(date(1, 31), Key::Right, today), date(2, 1));
+        assert_eq!(
+            move_cursor(date(1, 3), Key::Up, today),
+            NaiveDate::from_ymd_opt(2023, 12, 27).unwrap()
+        );
+        assert_eq!(move_cursor(date(1, 31), Key::NextMonth, today), date(2, 29));
+        assert_eq!(move_cursor(date(1, 31), Key::Today, today), today);
+        assert_eq!(move_cursor(date(1, 31), Key::Enter, today), date(1, 31));
+        assert_eq!(move_cursor(MAX_DATE, Key::Down, today), MAX_DATE);
+        assert_eq!(move_cursor(MIN_DATE, Key::PrevMonth, today), MIN_DATE);
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{self, BufRead, Write};
+
+use chrono::Datelike;
+use clap::{builder::PossibleValue, ValueEnum};
+use serde::Serialize;
+
+use crate::{
+    business::is_business_day,
+    consts::{ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    date_parser::parse_date,
+    holiday::HolidayInfo,
+    Lang, LibResult,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QueryFormat {
+    Text,
+    Json,
+}
+
+impl ValueEnum for QueryFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Text, Self::Json]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            QueryFormat::Text => PossibleValue::new("text"),
+            QueryFormat::Json => PossibleValue::new("json"),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct QueryResult<'a> {
+    date: String,
+    weekday: String,
+    holidays: Vec<&'a str>,
+    business_day: bool,
+}
+
+#[derive(Serialize)]
+struct QueryError<'a> {
+    input: &'a str,
+    error: String,
+}
+
+/// Answers one result line per input date, from `dates` followed by stdin if `stdin` is set.
+pub fn query(
+    out: &mut impl Write,
+    holiday_info: &HolidayInfo,
+    dates: &[String],
+    stdin: bool,
+    format: QueryFormat,
+    lang: Lang,
+) -> LibResult<()> {
+    for input in dates {
+        write_result(out, holiday_info, input, format, lang)?;
+    }
+    if stdin {
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            write_result(out, holiday_info, line.trim(), format, lang)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_result(
+    out: &mut impl Write,
+    holiday_info: &HolidayInfo,
+    input: &str,
+    format: QueryFormat,
+    lang: Lang,
+) -> LibResult<()> {
+    let date = match parse_date(input) {
+        Ok(date) => date,
+        Err(error) => {
+            match format {
+                QueryFormat::Text => writeln!(out, "{input}\terror: {error}")?,
+                QueryFormat::Json => {
+                    serde_json::to_writer(&mut *out, &QueryError { input, error })?;
+                    writeln!(out)?;
+                }
+            }
+            return Ok(());
+        }
+    };
+
+    let holidays = holiday_info
+        .holidays_on(date)
+        .iter()
+        .map(|h| h.name.as_str())
+        .collect::<Vec<_>>();
+    let business_day = is_business_day(holiday_info, date);
+    match format {
+        QueryFormat::Text => {
+            let weekday = date.weekday().num_days_from_sunday() as usize;
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                date.format("%Y-%m-%d"),
+                match lang {
+                    Lang::Japanese => JAPANESE_WEEK_NAMES[weekday],
+                    Lang::English => ENGLISH_WEEK_NAMES[weekday],
+                },
+                if holidays.is_empty() {
+                    "-".to_string()
+                } else {
+                    holidays.join("/")
+                },
+                business_day
+            )?;
+        }
+        QueryFormat::Json => {
+            let result = QueryResult {
+                date: date.format("%Y-%m-%d").to_string(),
+                weekday: date.format("%a").to_string(),
+                holidays,
+                business_day,
+            };
+            serde_json::to_writer(&mut *out, &result)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        holiday::holidays_of,
+        query::{query, write_result, QueryFormat},
+        Lang,
+    };
+
+    #[test]
+    fn test_query() {
+        let hi = holidays_of(&["2025/01/01,元日"]);
+        let dates = ["2025-01-01", "2025-01-02", "2025-01-04"].map(String::from);
+        let mut out = vec![];
+        query(
+            &mut out,
+            &hi,
+            &dates,
+            false,
+            QueryFormat::Text,
+            Lang::English,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2025-01-01\tWe\t元日\tfalse\n\
+             2025-01-02\tTh\t-\ttrue\n\
+             2025-01-04\tSa\t-\tfalse\n"
+        );
+
+        let mut out = vec![];
+        write_result(
+            &mut out,
+            &hi,
+            "2025-01-01",
+            QueryFormat::Json,
+            Lang::Japanese,
+        )
+        .unwrap();
+        write_result(&mut out, &hi, "someday", QueryFormat::Json, Lang::Japanese).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            r#"{"date":"2025-01-01","weekday":"Wed","holidays":["元日"],"business_day":false}"#
+        );
+        assert!(lines[1].starts_with(r#"{"input":"someday","error":"#));
+    }
+}
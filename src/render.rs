@@ -0,0 +1,530 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::{filter::DayFilter, holiday::HolidayInfo, month_layout};
+
+/// A day in a rendered month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Day {
+    pub date: NaiveDate,
+    /// Names of the day's holidays/events
+    pub holidays: Vec<String>,
+    pub day_off: bool,
+    pub today: bool,
+    /// Whether the day passes `--only-weekdays` and `--only`; the JSON and
+    /// CSV renderers leave out the others
+    pub shown: bool,
+    /// Number of the day's note in [`Calendar::footnotes`]
+    pub footnote: Option<usize>,
+}
+
+/// A month laid out in six weeks, `None` outside the month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Month {
+    pub year: i32,
+    pub month: u32,
+    /// Header text e.g. `1月(睦月) 2025` or `January 2025`
+    pub title: String,
+    pub weeks: [[Option<Day>; 7]; 6],
+}
+
+/// Everything a [`Renderer`] draws, already localized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub months: Vec<Month>,
+    pub first_weekday: Weekday,
+    /// Weekday names in column order
+    pub weekday_names: [String; 7],
+    /// Note texts, note `n` at index `n - 1`
+    pub footnotes: Vec<String>,
+}
+
+/// An output format for [`Calendar`]s, selected with `--format`.
+pub trait Renderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()>;
+}
+
+impl Calendar {
+    /// Lays out each of `months`, given as (year, month, title), in weeks
+    /// starting on `first_weekday`, with the entries of `holiday_info`. Days
+    /// not matching `day_filter` are not [shown](Day::shown).
+    pub fn new(
+        holiday_info: &HolidayInfo,
+        months: impl IntoIterator<Item = (i32, u32, String)>,
+        first_weekday: Weekday,
+        weekday_names: [String; 7],
+        today: NaiveDate,
+        day_filter: &DayFilter,
+    ) -> Calendar {
+        let months = months
+            .into_iter()
+            .map(|(year, month, title)| Month {
+                year,
+                month,
+                title,
+                weeks: month_layout(year, month, first_weekday).map(|week| {
+                    week.map(|d| {
+                        let date = NaiveDate::from_ymd_opt(year, month, d?)?;
+                        Some(Day {
+                            date,
+                            holidays: holiday_info
+                                .holidays_on(date)
+                                .iter()
+                                .map(|h| h.name.clone())
+                                .collect(),
+                            day_off: holiday_info.is_day_off(date),
+                            today: date == today,
+                            shown: day_filter.matches(holiday_info, date),
+                            footnote: None,
+                        })
+                    })
+                }),
+            })
+            .collect();
+        Calendar {
+            months,
+            first_weekday,
+            weekday_names,
+            footnotes: vec![],
+        }
+    }
+
+    /// Attaches `notes`, referenced from the days in `days` by number.
+    pub fn set_footnotes(&mut self, days: &HashMap<NaiveDate, usize>, notes: &[String]) {
+        for day in self
+            .months
+            .iter_mut()
+            .flat_map(|m| m.weeks.iter_mut().flatten().flatten())
+        {
+            day.footnote = days.get(&day.date).copied();
+        }
+        self.footnotes = notes.to_vec();
+    }
+}
+
+impl Month {
+    fn days(&self) -> impl Iterator<Item = &Day> {
+        self.weeks.iter().flatten().flatten()
+    }
+}
+
+/// GitHub-flavored Markdown tables; days off in bold, today in italics.
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()> {
+        for (i, month) in calendar.months.iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "### {}", month.title)?;
+            writeln!(out)?;
+            writeln!(out, "| {} |", calendar.weekday_names.join(" | "))?;
+            writeln!(out, "|{}", "---:|".repeat(7))?;
+            for week in month.weeks.iter().filter(|w| w.iter().any(Option::is_some)) {
+                let cells = week
+                    .iter()
+                    .map(|d| match d {
+                        None => String::new(),
+                        Some(d) => {
+                            let n = d.date.day().to_string();
+                            let n = if d.day_off { format!("**{n}**") } else { n };
+                            let n = if d.today { format!("_{n}_") } else { n };
+                            match d.footnote {
+                                Some(k) => format!("{n}[^{k}]"),
+                                None => n,
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(out, "| {} |", cells.join(" | "))?;
+            }
+            let days = month.days().filter(|d| !d.holidays.is_empty());
+            for (j, day) in days.enumerate() {
+                if j == 0 {
+                    writeln!(out)?;
+                }
+                let names = day.holidays.join(", ").replace('|', "\\|");
+                writeln!(out, "- {}: {names}", day.date.format("%m/%d"))?;
+            }
+        }
+        for (i, note) in calendar.footnotes.iter().enumerate() {
+            if i == 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "[^{}]: {note}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// One `<table class="calp-month">` per month, classed for styling.
+pub struct HtmlRenderer;
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()> {
+        for month in &calendar.months {
+            writeln!(out, "<table class=\"calp-month\">")?;
+            writeln!(out, "  <caption>{}</caption>", escape_html(&month.title))?;
+            let names = calendar
+                .weekday_names
+                .iter()
+                .map(|n| format!("<th>{}</th>", escape_html(n)))
+                .collect::<String>();
+            writeln!(out, "  <thead><tr>{names}</tr></thead>")?;
+            writeln!(out, "  <tbody>")?;
+            for week in month.weeks.iter().filter(|w| w.iter().any(Option::is_some)) {
+                let cells = week
+                    .iter()
+                    .map(|d| match d {
+                        None => "<td></td>".to_string(),
+                        Some(d) => {
+                            let mut classes = vec![];
+                            match d.date.weekday() {
+                                Weekday::Sun => classes.push("sunday"),
+                                Weekday::Sat => classes.push("saturday"),
+                                _ => (),
+                            }
+                            if d.day_off {
+                                classes.push("holiday");
+                            }
+                            if d.today {
+                                classes.push("today");
+                            }
+                            let class = if classes.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" class=\"{}\"", classes.join(" "))
+                            };
+                            let title = if d.holidays.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" title=\"{}\"", escape_html(&d.holidays.join(", ")))
+                            };
+                            let note = d
+                                .footnote
+                                .map_or(String::new(), |k| format!("<sup>{k}</sup>"));
+                            format!("<td{class}{title}>{}{note}</td>", d.date.day())
+                        }
+                    })
+                    .collect::<String>();
+                writeln!(out, "    <tr>{cells}</tr>")?;
+            }
+            writeln!(out, "  </tbody>")?;
+            writeln!(out, "</table>")?;
+        }
+        if !calendar.footnotes.is_empty() {
+            writeln!(out, "<ol class=\"calp-footnotes\">")?;
+            for note in &calendar.footnotes {
+                writeln!(out, "  <li>{}</li>", escape_html(note))?;
+            }
+            writeln!(out, "</ol>")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonMonth<'a> {
+    year: i32,
+    month: u32,
+    title: &'a str,
+    weeks: Vec<Vec<Option<JsonDay<'a>>>>,
+}
+
+#[derive(Serialize)]
+struct JsonDay<'a> {
+    date: String,
+    holidays: &'a [String],
+    day_off: bool,
+    today: bool,
+}
+
+/// The months as a JSON array, each with its weeks of days (`null` outside
+/// the month and for days that are not [shown](Day::shown)).
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()> {
+        let months = calendar
+            .months
+            .iter()
+            .map(|m| JsonMonth {
+                year: m.year,
+                month: m.month,
+                title: &m.title,
+                weeks: m
+                    .weeks
+                    .iter()
+                    .map(|w| {
+                        w.iter()
+                            .map(|d| {
+                                d.as_ref().filter(|d| d.shown).map(|d| JsonDay {
+                                    date: d.date.format("%Y-%m-%d").to_string(),
+                                    holidays: &d.holidays,
+                                    day_off: d.day_off,
+                                    today: d.today,
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_writer_pretty(&mut *out, &months)?;
+        writeln!(out)
+    }
+}
+
+/// One row per [shown](Day::shown) day after a header row:
+/// `year,month,day,weekday,is_holiday,is_today`, with weekdays as `Mon`…`Sun`.
+/// `delimiter` is `,` for CSV and a tab for TSV.
+pub struct CsvRenderer {
+    pub delimiter: char,
+}
+
+impl Renderer for CsvRenderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()> {
+        let columns = ["year", "month", "day", "weekday", "is_holiday", "is_today"];
+        let d = self.delimiter;
+        writeln!(out, "{}", columns.join(&d.to_string()))?;
+        for day in calendar
+            .months
+            .iter()
+            .flat_map(Month::days)
+            .filter(|d| d.shown)
+        {
+            writeln!(
+                out,
+                "{}{d}{}{d}{}{d}{}{d}{}{d}{}",
+                day.date.year(),
+                day.date.month(),
+                day.date.day(),
+                day.date.weekday(),
+                day.day_off,
+                day.today
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A standalone SVG image with up to three months per row.
+pub struct SvgRenderer;
+
+const SVG_CELL: usize = 32;
+const SVG_MONTH_WIDTH: usize = SVG_CELL * 7 + SVG_CELL / 2;
+const SVG_MONTH_HEIGHT: usize = SVG_CELL * 8 + SVG_CELL / 2;
+
+impl Renderer for SvgRenderer {
+    fn render(&self, calendar: &Calendar, out: &mut dyn Write) -> io::Result<()> {
+        let columns = calendar.months.len().clamp(1, 3);
+        let rows = calendar.months.len().div_ceil(3).max(1);
+        let (width, height) = (columns * SVG_MONTH_WIDTH, rows * SVG_MONTH_HEIGHT);
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             font-family=\"sans-serif\" font-size=\"14\" text-anchor=\"middle\">"
+        )?;
+        for (i, month) in calendar.months.iter().enumerate() {
+            let x0 = i % 3 * SVG_MONTH_WIDTH;
+            let y0 = i / 3 * SVG_MONTH_HEIGHT;
+            let centre = |column: usize| x0 + column * SVG_CELL + SVG_CELL / 2;
+            writeln!(
+                out,
+                "  <text x=\"{}\" y=\"{}\" font-weight=\"bold\">{}</text>",
+                x0 + SVG_CELL * 7 / 2,
+                y0 + SVG_CELL * 2 / 3,
+                escape_html(&month.title)
+            )?;
+            for (column, name) in calendar.weekday_names.iter().enumerate() {
+                writeln!(
+                    out,
+                    "  <text x=\"{}\" y=\"{}\">{}</text>",
+                    centre(column),
+                    y0 + SVG_CELL * 5 / 3,
+                    escape_html(name)
+                )?;
+            }
+            for (row, week) in month.weeks.iter().enumerate() {
+                for (column, day) in week.iter().enumerate() {
+                    let Some(day) = day else {
+                        continue;
+                    };
+                    let y = y0 + SVG_CELL * (row + 2) + SVG_CELL * 2 / 3;
+                    if day.today {
+                        writeln!(
+                            out,
+                            "  <rect x=\"{}\" y=\"{}\" width=\"{SVG_CELL}\" height=\"{SVG_CELL}\" \
+                             fill=\"none\" stroke=\"black\"/>",
+                            x0 + column * SVG_CELL,
+                            y0 + SVG_CELL * (row + 2)
+                        )?;
+                    }
+                    let fill = match day.date.weekday() {
+                        _ if day.day_off => "#d00",
+                        Weekday::Sun => "#d00",
+                        Weekday::Sat => "#06c",
+                        _ => "#000",
+                    };
+                    let title = if day.holidays.is_empty() {
+                        String::new()
+                    } else {
+                        format!("<title>{}</title>", escape_html(&day.holidays.join(", ")))
+                    };
+                    writeln!(
+                        out,
+                        "  <text x=\"{}\" y=\"{y}\" fill=\"{fill}\">{}{title}</text>",
+                        centre(column),
+                        day.date.day()
+                    )?;
+                }
+            }
+        }
+        writeln!(out, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{
+        filter::DayFilter,
+        holiday::{Category, Holiday, HolidayInfo},
+        render::{
+            Calendar, CsvRenderer, HtmlRenderer, JsonRenderer, MarkdownRenderer, Renderer,
+            SvgRenderer,
+        },
+    };
+
+    /// February 2025 seen on the 3rd, with the 11th a holiday.
+    fn golden_calendar() -> Calendar {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 2, d).unwrap();
+        let mut hi = HolidayInfo::new();
+        hi.add(
+            date(11),
+            Holiday {
+                name: "建国記念の日 & <祝>".to_string(),
+                category: Category::National,
+                time: None,
+                span: None,
+                source: String::new(),
+            },
+        );
+        let names = ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"].map(String::from);
+        Calendar::new(
+            &hi,
+            [(2025, 2, "February 2025".to_string())],
+            Weekday::Sun,
+            names,
+            date(3),
+            &DayFilter::default(),
+        )
+    }
+
+    #[test]
+    fn test_markdown_renderer() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 2, d).unwrap();
+        let calendar = golden_calendar();
+        assert_eq!(
+            calendar.months[0].weeks[0][6].as_ref().unwrap().date,
+            date(1)
+        );
+
+        let mut out = Vec::new();
+        MarkdownRenderer.render(&calendar, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "### February 2025");
+        assert_eq!(lines[2], "| Su | Mo | Tu | We | Th | Fr | Sa |");
+        assert_eq!(lines[4], "|  |  |  |  |  |  | 1 |");
+        assert_eq!(lines[5], "| 2 | _3_ | 4 | 5 | 6 | 7 | 8 |");
+        assert_eq!(lines[6], "| 9 | 10 | **11** | 12 | 13 | 14 | 15 |");
+        assert_eq!(lines.last(), Some(&"- 02/11: 建国記念の日 & <祝>"));
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 2, d).unwrap();
+        let names = ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"].map(String::from);
+        let mut calendar = Calendar::new(
+            &HolidayInfo::new(),
+            [(2025, 2, "February 2025".to_string())],
+            Weekday::Sun,
+            names,
+            date(3),
+            &DayFilter::default(),
+        );
+        let days = HashMap::from([(date(11), 1), (date(14), 2)]);
+        calendar.set_footnotes(&days, &["建国記念の日".to_string(), "a < b".to_string()]);
+
+        let mut out = Vec::new();
+        MarkdownRenderer.render(&calendar, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[6], "| 9 | 10 | 11[^1] | 12 | 13 | 14[^2] | 15 |");
+        assert_eq!(lines[9..], ["", "[^1]: 建国記念の日", "[^2]: a < b"]);
+
+        let mut out = Vec::new();
+        HtmlRenderer.render(&calendar, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("<td>11<sup>1</sup></td>"), "{out}");
+        assert!(out.ends_with(
+            "<ol class=\"calp-footnotes\">\n  <li>建国記念の日</li>\n  <li>a &lt; b</li>\n</ol>\n"
+        ));
+    }
+
+    #[test]
+    fn test_golden() {
+        let calendar = golden_calendar();
+        let golden: [(&dyn Renderer, &str); 3] = [
+            (&HtmlRenderer, include_str!("../tests/golden/february.html")),
+            (&JsonRenderer, include_str!("../tests/golden/february.json")),
+            (&SvgRenderer, include_str!("../tests/golden/february.svg")),
+        ];
+        for (renderer, expected) in golden {
+            let mut out = Vec::new();
+            renderer.render(&calendar, &mut out).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_csv_renderer() {
+        let calendar = golden_calendar();
+
+        let mut out = Vec::new();
+        CsvRenderer { delimiter: ',' }
+            .render(&calendar, &mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + 28);
+        assert_eq!(lines[0], "year,month,day,weekday,is_holiday,is_today");
+        assert_eq!(lines[1], "2025,2,1,Sat,false,false");
+        assert_eq!(lines[3], "2025,2,3,Mon,false,true");
+        assert_eq!(lines[11], "2025,2,11,Tue,true,false");
+
+        let mut out = Vec::new();
+        CsvRenderer { delimiter: '\t' }
+            .render(&calendar, &mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().nth(1), Some("2025\t2\t1\tSat\tfalse\tfalse"));
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::Write;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{business::is_business_day, filter::DayFilter, holiday::HolidayInfo, LibResult};
+
+/// Prints every date in `from..=to` matching the filters, formatted with `format`.
+#[allow(clippy::too_many_arguments)]
+pub fn print_seq(
+    out: &mut impl Write,
+    holiday_info: &HolidayInfo,
+    from: NaiveDate,
+    to: NaiveDate,
+    business_days: bool,
+    weekdays: &[Weekday],
+    day_filter: &DayFilter,
+    format: &str,
+) -> LibResult<()> {
+    for date in from.iter_days().take_while(|d| *d <= to) {
+        if business_days && !is_business_day(holiday_info, date) {
+            continue;
+        }
+        if !weekdays.is_empty() && !weekdays.contains(&date.weekday()) {
+            continue;
+        }
+        if !day_filter.matches(holiday_info, date) {
+            continue;
+        }
+        writeln!(out, "{}", date.format(format))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{filter::DayFilter, holiday::holidays_of, seq::print_seq};
+
+    #[test]
+    fn test_print_seq() {
+        let hi = holidays_of(&["2025/01/13,成人の日"]);
+        let seq = |business_days, weekdays: &[Weekday], format| {
+            let from = NaiveDate::from_ymd_opt(2025, 1, 9).unwrap();
+            let to = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+            let filter = DayFilter::default();
+            let mut out = vec![];
+            print_seq(
+                &mut out,
+                &hi,
+                from,
+                to,
+                business_days,
+                weekdays,
+                &filter,
+                format,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(seq(false, &[], "%d").lines().count(), 9);
+        assert_eq!(seq(true, &[], "%d"), "09\n10\n14\n15\n16\n17\n");
+        assert_eq!(
+            seq(true, &[Weekday::Mon, Weekday::Fri], "%Y/%m/%d"),
+            "2025/01/10\n2025/01/17\n"
+        );
+        assert_eq!(seq(false, &[Weekday::Mon], "%a %d"), "Mon 13\n");
+    }
+}
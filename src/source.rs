@@ -0,0 +1,437 @@
+use std::{
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+use crate::{
+    config::expand_home,
+    decorator::shell,
+    holiday::{
+        default_holiday_file, parse_holiday_text, read_holiday_file, Category, DateFormat,
+        HolidayInfo,
+    },
+    ics::parse_ics,
+    observance::{add_observances, Observance},
+    overrides::{apply_overrides, load_overrides},
+    trace::Tracer,
+    Encoding, FileConfig, Lang, LibResult,
+};
+
+/// Somewhere holidays/events come from. Sources are merged in pipeline
+/// order, so each one sees, and may derive from or remove, what the
+/// earlier ones added.
+pub trait DateSource {
+    /// Stage reported by `--trace-json`
+    fn stage(&self) -> &'static str;
+
+    /// Adds the source's entries, and every file it reads to `inputs`.
+    fn merge_into(
+        &self,
+        holiday_info: &mut HolidayInfo,
+        inputs: &mut Vec<PathBuf>,
+    ) -> LibResult<()>;
+
+    /// File to watch for changes, if the source is read from one
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Everything but the contents of its input files that the entries
+    /// depend on, for the holiday cache; `None` if they cannot be cached.
+    fn cache_key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A `date,name[,category]` holiday file, with its `#include`s.
+pub struct HolidayFile {
+    pub path: PathBuf,
+    pub encoding: Encoding,
+    pub date_format: DateFormat,
+    /// Category, and so style, of the lines that don't name one
+    pub category: Category,
+}
+
+impl DateSource for HolidayFile {
+    fn stage(&self) -> &'static str {
+        "load"
+    }
+
+    fn merge_into(
+        &self,
+        holiday_info: &mut HolidayInfo,
+        inputs: &mut Vec<PathBuf>,
+    ) -> LibResult<()> {
+        holiday_info.extend(read_holiday_file(
+            &self.path,
+            self.encoding,
+            self.date_format,
+            self.category,
+            &mut Vec::new(),
+            inputs,
+        )?);
+        Ok(())
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "file {} {:?} {:?} {:?}",
+            self.path.display(),
+            self.encoding,
+            self.date_format,
+            self.category
+        ))
+    }
+}
+
+/// An iCalendar `.ics` file given with `--file`.
+pub struct IcsFile {
+    pub path: PathBuf,
+    /// Category of the events without one of calp's `CATEGORIES`
+    pub category: Category,
+}
+
+impl DateSource for IcsFile {
+    fn stage(&self) -> &'static str {
+        "load"
+    }
+
+    fn merge_into(
+        &self,
+        holiday_info: &mut HolidayInfo,
+        inputs: &mut Vec<PathBuf>,
+    ) -> LibResult<()> {
+        let source = self.path.display().to_string();
+        let text = fs::read_to_string(&self.path).map_err(|e| format!("{source}: {e}"))?;
+        inputs.push(self.path.clone());
+        holiday_info.extend(parse_ics(&text, self.category, &source)?);
+        Ok(())
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("ics {} {:?}", self.path.display(), self.category))
+    }
+}
+
+/// A `--source-command`, whose output is read as a holiday file, or as
+/// iCalendar when it starts with `BEGIN:VCALENDAR`. Never cached, since
+/// its output may change at any time.
+pub struct CommandSource {
+    pub command: String,
+    pub date_format: DateFormat,
+}
+
+impl DateSource for CommandSource {
+    fn stage(&self) -> &'static str {
+        "load"
+    }
+
+    fn merge_into(&self, holiday_info: &mut HolidayInfo, _: &mut Vec<PathBuf>) -> LibResult<()> {
+        let command = &self.command;
+        let output = shell(command)
+            .output()
+            .map_err(|e| format!("{command}: {e}"))?;
+        if !output.status.success() {
+            return Err(format!("{command}: exited with {}", output.status).into());
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        // Lines that don't name a category are someone's calendar, not days off.
+        let entries = if text.trim_start().starts_with("BEGIN:VCALENDAR") {
+            parse_ics(&text, Category::Personal, command)?
+        } else {
+            parse_holiday_text(&text, self.date_format, Category::Personal, command)?
+        };
+        holiday_info.extend(entries);
+        Ok(())
+    }
+}
+
+/// Holidays computed for every year in `years`.
+pub struct Computed {
+    pub observances: Vec<Observance>,
+    pub years: RangeInclusive<i32>,
+    pub lang: Lang,
+}
+
+impl DateSource for Computed {
+    fn stage(&self) -> &'static str {
+        "observances"
+    }
+
+    fn merge_into(&self, holiday_info: &mut HolidayInfo, _: &mut Vec<PathBuf>) -> LibResult<()> {
+        add_observances(
+            holiday_info,
+            &self.observances,
+            self.years.clone(),
+            self.lang,
+        );
+        Ok(())
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!(
+            "computed {:?} {:?} {:?}",
+            self.observances, self.years, self.lang
+        ))
+    }
+}
+
+/// 振替休日 and 国民の休日, derived from the computed Japanese holidays merged so far.
+pub struct SubstituteHolidays {
+    pub lang: Lang,
+}
+
+impl DateSource for SubstituteHolidays {
+    fn stage(&self) -> &'static str {
+        "observances"
+    }
+
+    fn merge_into(&self, holiday_info: &mut HolidayInfo, _: &mut Vec<PathBuf>) -> LibResult<()> {
+        holiday_info.add_substitute_holidays(self.lang);
+        Ok(())
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("substitute {:?}", self.lang))
+    }
+}
+
+/// A `--override` file of `+date,name` / `-date[,name]` lines.
+pub struct OverrideFile {
+    pub path: String,
+}
+
+impl DateSource for OverrideFile {
+    fn stage(&self) -> &'static str {
+        "overrides"
+    }
+
+    fn merge_into(
+        &self,
+        holiday_info: &mut HolidayInfo,
+        inputs: &mut Vec<PathBuf>,
+    ) -> LibResult<()> {
+        apply_overrides(holiday_info, &load_overrides(&self.path)?, &self.path);
+        inputs.push(PathBuf::from(&self.path));
+        Ok(())
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(Path::new(&self.path))
+    }
+
+    fn cache_key(&self) -> Option<String> {
+        Some(format!("override {}", self.path))
+    }
+}
+
+/// Parses a `--file` argument: a path, optionally followed by `=CATEGORY` to
+/// give the category of its lines that don't name one, e.g. `work.csv=company`.
+pub fn parse_holiday_file(s: &str) -> Result<(PathBuf, Category), String> {
+    match s.rsplit_once('=') {
+        Some((path, category)) if !path.is_empty() && !Path::new(&expand_home(s)).exists() => {
+            let category = Category::from_str(category, true)
+                .map_err(|_| format!("unknown category: \"{category}\""))?;
+            Ok((PathBuf::from(expand_home(path)), category))
+        }
+        _ => Ok((PathBuf::from(expand_home(s)), Category::National)),
+    }
+}
+
+/// The configured sources in precedence order: holiday and iCalendar files
+/// (the [`default_holiday_file`] when none are given), source commands,
+/// computed Japanese holidays and the days derived from them, other
+/// observances, then overrides.
+pub fn configured_sources(
+    file_config: &FileConfig,
+    years: RangeInclusive<i32>,
+    lang: Lang,
+) -> Vec<Box<dyn DateSource>> {
+    let files = if file_config.file.is_empty() {
+        default_holiday_file()
+            .map(|path| (path, Category::National))
+            .into_iter()
+            .collect()
+    } else {
+        file_config.file.clone()
+    };
+    let mut observances = file_config.observances.clone();
+    if files.is_empty() && !observances.contains(&Observance::Japan) {
+        observances.insert(0, Observance::Japan);
+    }
+    // Substitute holidays follow Japanese holidays only, so the other
+    // observances are added after them.
+    let (japan, others): (Vec<_>, Vec<_>) = observances
+        .into_iter()
+        .partition(|o| *o == Observance::Japan);
+
+    let mut sources: Vec<Box<dyn DateSource>> = vec![];
+    for (path, category) in files {
+        let ics = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ics"));
+        sources.push(match ics {
+            true => Box::new(IcsFile { path, category }),
+            false => Box::new(HolidayFile {
+                path,
+                encoding: file_config.encoding,
+                date_format: file_config.date_format,
+                category,
+            }),
+        });
+    }
+    for command in &file_config.source_commands {
+        sources.push(Box::new(CommandSource {
+            command: command.clone(),
+            date_format: file_config.date_format,
+        }));
+    }
+    sources.push(Box::new(Computed {
+        observances: japan,
+        years: years.clone(),
+        lang,
+    }));
+    sources.push(Box::new(SubstituteHolidays { lang }));
+    sources.push(Box::new(Computed {
+        observances: others,
+        years,
+        lang,
+    }));
+    for path in &file_config.overrides {
+        sources.push(Box::new(OverrideFile { path: path.clone() }));
+    }
+    sources
+}
+
+/// Merges `sources` in order into a new [`HolidayInfo`].
+pub fn load_sources(sources: &[Box<dyn DateSource>], tracer: &Tracer) -> LibResult<HolidayInfo> {
+    merge_sources(sources, tracer).map(|(holiday_info, _)| holiday_info)
+}
+
+/// [`load_sources`], with the files they read.
+pub fn merge_sources(
+    sources: &[Box<dyn DateSource>],
+    tracer: &Tracer,
+) -> LibResult<(HolidayInfo, Vec<PathBuf>)> {
+    let mut holiday_info = HolidayInfo::new();
+    let mut inputs = vec![];
+    for source in sources {
+        let _span = tracer.span(source.stage());
+        source.merge_into(&mut holiday_info, &mut inputs)?;
+    }
+    Ok((holiday_info, inputs))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        holiday::DateFormat,
+        holiday::{Category, HolidayInfo},
+        observance::Observance,
+        source::{
+            load_sources, parse_holiday_file, CommandSource, Computed, DateSource,
+            SubstituteHolidays,
+        },
+        trace::Tracer,
+        Lang, LibResult,
+    };
+
+    struct Removal(NaiveDate);
+
+    impl DateSource for Removal {
+        fn stage(&self) -> &'static str {
+            "overrides"
+        }
+
+        fn merge_into(
+            &self,
+            holiday_info: &mut HolidayInfo,
+            _: &mut Vec<PathBuf>,
+        ) -> LibResult<()> {
+            holiday_info.remove(self.0, None);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_load_sources() {
+        // 2025-02-23 (Sunday) is the Emperor's Birthday, substituted on the 24th
+        let date = |d| NaiveDate::from_ymd_opt(2025, 2, d).unwrap();
+        let japan = || -> Box<dyn DateSource> {
+            Box::new(Computed {
+                observances: vec![Observance::Japan],
+                years: 2025..=2025,
+                lang: Lang::Japanese,
+            })
+        };
+        let substitute = || -> Box<dyn DateSource> {
+            Box::new(SubstituteHolidays {
+                lang: Lang::Japanese,
+            })
+        };
+        let tracer = Tracer::new(false);
+
+        let hi = load_sources(&[japan(), substitute()], &tracer).unwrap();
+        assert!(hi.is_day_off(date(24)));
+
+        // Later sources see what earlier ones merged.
+        let hi = load_sources(
+            &[japan(), Box::new(Removal(date(23))), substitute()],
+            &tracer,
+        )
+        .unwrap();
+        assert!(!hi.is_day_off(date(24)));
+        let hi = load_sources(
+            &[japan(), substitute(), Box::new(Removal(date(23)))],
+            &tracer,
+        )
+        .unwrap();
+        assert!(hi.is_day_off(date(24)));
+    }
+
+    #[test]
+    fn test_parse_holiday_file() {
+        assert_eq!(
+            parse_holiday_file("work.csv=Company"),
+            Ok((PathBuf::from("work.csv"), Category::Company))
+        );
+        assert_eq!(
+            parse_holiday_file("holidays.csv"),
+            Ok((PathBuf::from("holidays.csv"), Category::National))
+        );
+        assert!(parse_holiday_file("work.csv=team").is_err());
+    }
+
+    #[test]
+    fn test_command_source() {
+        let command = |command: &str| -> Box<dyn DateSource> {
+            Box::new(CommandSource {
+                command: command.to_string(),
+                date_format: DateFormat::Ymd,
+            })
+        };
+        let tracer = Tracer::new(false);
+        let hi = load_sources(&[command("echo 2025/05/09,release")], &tracer).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 5, 9).unwrap();
+        let holidays = hi.holidays_on(date);
+        assert_eq!(holidays[0].name, "release");
+        assert_eq!(holidays[0].category, Category::Personal);
+        assert!(!hi.is_day_off(date));
+        assert!(load_sources(&[command("exit 3")], &tracer).is_err());
+    }
+}
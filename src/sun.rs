@@ -0,0 +1,147 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use clap::Args;
+
+use crate::tz::Zone;
+
+#[derive(Debug, Args)]
+pub struct SunConfig {
+    /// Show sunrise/sunset and mark the shortest/longest days (requires --lat and --lon)
+    #[arg(long, requires_all = ["lat", "lon"], global = true)]
+    pub sun: bool,
+
+    /// Latitude in degrees (north positive)
+    #[arg(long, value_name = "DEG", allow_hyphen_values = true, global = true)]
+    lat: Option<f64>,
+
+    /// Longitude in degrees (east positive)
+    #[arg(long, value_name = "DEG", allow_hyphen_values = true, global = true)]
+    lon: Option<f64>,
+}
+
+impl SunConfig {
+    /// The configured location when `--sun` is enabled.
+    pub fn location(&self) -> Option<(f64, f64)> {
+        match (self.sun, self.lat, self.lon) {
+            (true, Some(lat), Some(lon)) => Some((lat, lon)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SunTimes {
+    Normal {
+        sunrise: DateTime<Utc>,
+        sunset: DateTime<Utc>,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+impl SunTimes {
+    pub fn day_length(&self) -> Duration {
+        match self {
+            SunTimes::Normal { sunrise, sunset } => *sunset - *sunrise,
+            SunTimes::PolarDay => Duration::days(1),
+            SunTimes::PolarNight => Duration::zero(),
+        }
+    }
+}
+
+/// Sunrise and sunset on `date` at the given location (NOAA solar equations).
+pub fn sun_times(date: NaiveDate, lat: f64, lon: f64) -> SunTimes {
+    let gamma = 2.0 * PI / 365.0 * (date.ordinal0() as f64);
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let decl = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let lat_rad = lat.to_radians();
+    let cos_ha =
+        90.833_f64.to_radians().cos() / (lat_rad.cos() * decl.cos()) - lat_rad.tan() * decl.tan();
+    if cos_ha < -1.0 {
+        return SunTimes::PolarDay;
+    }
+    if cos_ha > 1.0 {
+        return SunTimes::PolarNight;
+    }
+    let ha = cos_ha.acos().to_degrees();
+
+    let midnight = date.and_time(NaiveTime::MIN).and_utc();
+    let at = |minutes: f64| midnight + Duration::seconds((minutes * 60.0).round() as i64);
+    SunTimes::Normal {
+        sunrise: at(720.0 - 4.0 * (lon + ha) - eqtime),
+        sunset: at(720.0 - 4.0 * (lon - ha) - eqtime),
+    }
+}
+
+/// The shortest and longest days of `year` at the given location.
+pub fn extreme_days(year: i32, lat: f64, lon: f64) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let days = first
+        .iter_days()
+        .take_while(|d| d.year() == year)
+        .map(|d| (d, sun_times(d, lat, lon).day_length()))
+        .collect::<Vec<_>>();
+    let shortest = days.iter().min_by_key(|(_, l)| *l)?.0;
+    let longest = days.iter().max_by_key(|(_, l)| *l)?.0;
+    Some((shortest, longest))
+}
+
+/// Formats sunrise, sunset and day length in the given zone.
+pub fn format_sun_times(times: SunTimes, zone: Zone, japanese: bool) -> String {
+    match times {
+        SunTimes::Normal { sunrise, sunset } => {
+            let local = |t: DateTime<Utc>| zone.format_time(t, "%H:%M");
+            let length = times.day_length();
+            let length = format!("{}:{:02}", length.num_hours(), length.num_minutes() % 60);
+            if japanese {
+                format!(
+                    "日の出 {}  日の入 {}  (昼の長さ {length})",
+                    local(sunrise),
+                    local(sunset)
+                )
+            } else {
+                format!(
+                    "Sunrise {}  Sunset {}  (day length {length})",
+                    local(sunrise),
+                    local(sunset)
+                )
+            }
+        }
+        SunTimes::PolarDay if japanese => "白夜".to_string(),
+        SunTimes::PolarDay => "Polar day".to_string(),
+        SunTimes::PolarNight if japanese => "極夜".to_string(),
+        SunTimes::PolarNight => "Polar night".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Timelike};
+
+    use crate::sun::{sun_times, SunTimes};
+
+    #[test]
+    fn test_sun_times() {
+        // Tokyo, 2024-06-21: sunrise 04:25 JST (19:25 UTC), sunset 19:00 JST (10:00 UTC)
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let SunTimes::Normal { sunrise, sunset } = sun_times(date, 35.6895, 139.6917) else {
+            panic!("expected sunrise and sunset");
+        };
+        assert_eq!((sunrise.hour(), sunrise.minute() / 10), (19, 2));
+        assert_eq!((sunset.hour(), sunset.minute() / 10), (10, 0));
+
+        // Tromsø in December
+        let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+        assert_eq!(sun_times(date, 69.65, 18.96), SunTimes::PolarNight);
+    }
+}
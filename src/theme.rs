@@ -0,0 +1,314 @@
+use clap::{builder::PossibleValue, ValueEnum};
+use nu_ansi_term::{Color, Style};
+
+use crate::holiday::Category;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Theme {
+    Default,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    Solarized,
+    Gruvbox,
+    /// Attributes only, for monochrome terminals and printouts
+    Mono,
+    HighContrast,
+}
+
+impl ValueEnum for Theme {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Default,
+            Self::Deuteranopia,
+            Self::Protanopia,
+            Self::Tritanopia,
+            Self::Solarized,
+            Self::Gruvbox,
+            Self::Mono,
+            Self::HighContrast,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Theme::Default => PossibleValue::new("default"),
+            Theme::Deuteranopia => PossibleValue::new("deuteranopia"),
+            Theme::Protanopia => PossibleValue::new("protanopia"),
+            Theme::Tritanopia => PossibleValue::new("tritanopia"),
+            Theme::Solarized => PossibleValue::new("solarized"),
+            Theme::Gruvbox => PossibleValue::new("gruvbox"),
+            Theme::Mono => PossibleValue::new("mono"),
+            Theme::HighContrast => PossibleValue::new("high-contrast"),
+        })
+    }
+}
+
+/// Every style the grid draws with: base styles for day cells, and those of
+/// the cells around them. Today and span days are [`layer`]ed on top with
+/// `today` and `span`, marks and transitions with underline and bold, so they
+/// combine with every theme.
+/// Without color support [`Caps::degrade`](crate::caps::Caps::degrade)
+/// removes them all.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub sunday: Style,
+    pub saturday: Style,
+    pub national: Style,
+    pub overrides: Style,
+    pub company: Style,
+    pub personal: Style,
+    /// Days of no category on other weekdays
+    pub weekday: Style,
+    /// Neighboring months' days filling blank cells with `--spillover`
+    pub spillover: Style,
+    /// Layered over the days of a multi-day entry
+    pub span: Style,
+    /// Layered over today's cell
+    pub today: Style,
+}
+
+impl Palette {
+    pub fn category(&self, category: Category) -> Style {
+        match category {
+            Category::National => self.national,
+            Category::Override => self.overrides,
+            Category::Company => self.company,
+            Category::Personal => self.personal,
+        }
+    }
+}
+
+impl Theme {
+    /// The colorblind presets draw from the Okabe-Ito palette and also italicize
+    /// holidays, so a holiday on a weekend never depends on hue alone.
+    pub fn palette(self) -> Palette {
+        let fg = |n| Style::new().fg(Color::Fixed(n));
+        let weekday = Style::new();
+        let spillover = Style::new().dimmed();
+        let span = Style::new().underline();
+        let today = Style::new().reverse();
+        match self {
+            Theme::Default => Palette {
+                sunday: Style::new().fg(Color::Red),
+                saturday: Style::new().fg(Color::Blue),
+                national: Style::new().fg(Color::Red),
+                overrides: fg(202),
+                company: Style::new().fg(Color::Purple),
+                personal: Style::new().fg(Color::Cyan),
+                weekday,
+                spillover,
+                span,
+                today,
+            },
+            // Red-green deficiencies: separate along the blue-orange axis.
+            // Protanopes see long wavelengths darker, so their orange is lighter.
+            Theme::Deuteranopia | Theme::Protanopia => {
+                let orange = if self == Theme::Protanopia { 221 } else { 214 };
+                Palette {
+                    sunday: fg(orange),
+                    saturday: fg(32),
+                    national: fg(orange).italic(),
+                    overrides: fg(orange).italic(),
+                    company: fg(175).italic(),
+                    personal: fg(117).italic(),
+                    weekday,
+                    spillover,
+                    span,
+                    today,
+                }
+            }
+            // Blue-yellow deficiency: separate along the red-cyan axis.
+            Theme::Tritanopia => Palette {
+                sunday: fg(160),
+                saturday: fg(37),
+                national: fg(160).italic(),
+                overrides: fg(160).italic(),
+                company: fg(211).italic(),
+                personal: fg(250).italic(),
+                weekday,
+                spillover,
+                span,
+                today,
+            },
+            // The accent colors nearest in the 256-color palette, with
+            // neighboring months' days in base01.
+            Theme::Solarized => Palette {
+                sunday: fg(160),
+                saturday: fg(33),
+                national: fg(160),
+                overrides: fg(166),
+                company: fg(125),
+                personal: fg(37),
+                weekday,
+                spillover: fg(240),
+                span,
+                today,
+            },
+            Theme::Gruvbox => Palette {
+                sunday: fg(167),
+                saturday: fg(109),
+                national: fg(167),
+                overrides: fg(208),
+                company: fg(175),
+                personal: fg(108),
+                weekday,
+                spillover: fg(245),
+                span,
+                today: Style::new().fg(Color::Fixed(235)).on(Color::Fixed(214)),
+            },
+            Theme::Mono => Palette {
+                sunday: Style::new().bold(),
+                saturday: Style::new().italic(),
+                national: Style::new().bold(),
+                overrides: Style::new().bold(),
+                company: Style::new().bold().italic(),
+                personal: Style::new().italic(),
+                weekday,
+                spillover,
+                span,
+                today,
+            },
+            // Saturated colors in bold, and today black on yellow.
+            Theme::HighContrast => Palette {
+                sunday: fg(196).bold(),
+                saturday: fg(39).bold(),
+                national: fg(196).bold(),
+                overrides: fg(202).bold(),
+                company: fg(201).bold(),
+                personal: fg(51).bold(),
+                weekday,
+                spillover,
+                span,
+                today: Style::new()
+                    .fg(Color::Fixed(16))
+                    .on(Color::Fixed(226))
+                    .bold(),
+            },
+        }
+    }
+}
+
+/// `top` drawn over `base`: its colors where it sets them, and the
+/// attributes of both.
+pub fn layer(base: Style, top: Style) -> Style {
+    Style {
+        foreground: top.foreground.or(base.foreground),
+        background: top.background.or(base.background),
+        is_bold: base.is_bold || top.is_bold,
+        is_dimmed: base.is_dimmed || top.is_dimmed,
+        is_italic: base.is_italic || top.is_italic,
+        is_underline: base.is_underline || top.is_underline,
+        is_blink: base.is_blink || top.is_blink,
+        is_reverse: base.is_reverse || top.is_reverse,
+        is_hidden: base.is_hidden || top.is_hidden,
+        is_strikethrough: base.is_strikethrough || top.is_strikethrough,
+        prefix_with_reset: base.prefix_with_reset || top.prefix_with_reset,
+    }
+}
+
+/// Parses a 256-color index e.g. `208`, or `#RRGGBB` e.g. `#ff8700`.
+pub fn parse_colour(s: &str) -> Result<Color, String> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+            _ => Err(format!("expected #RRGGBB: \"{s}\"")),
+        };
+    }
+    s.parse()
+        .map(Color::Fixed)
+        .map_err(|_| format!("expected a color 0-255 or #RRGGBB: \"{s}\""))
+}
+
+/// Parses a comma-separated list of bold, dimmed, italic, underline and
+/// reverse, a foreground COLOR and `bg=COLOR`, e.g. `bold,underline` or
+/// `bg=#303030`.
+pub fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    for item in s.split(',').map(str::trim) {
+        style = match item {
+            "bold" => style.bold(),
+            "dimmed" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            "reverse" => style.reverse(),
+            _ => match item.strip_prefix("bg=") {
+                Some(colour) => style.on(parse_colour(colour)?),
+                None => style.fg(parse_colour(item).map_err(|_| {
+                    format!("expected bold, dimmed, italic, underline, reverse, a color or bg=COLOR: \"{item}\"")
+                })?),
+            },
+        };
+    }
+    Ok(style)
+}
+
+#[cfg(test)]
+mod test {
+    use nu_ansi_term::{Color, Style};
+
+    use crate::theme::{layer, parse_colour, parse_style, Theme};
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(parse_colour("208"), Ok(Color::Fixed(208)));
+        assert_eq!(parse_colour("#ff8700"), Ok(Color::Rgb(255, 135, 0)));
+        assert!(parse_colour("#ff87").is_err());
+        assert!(parse_colour("256").is_err());
+        assert_eq!(
+            parse_style("bold, bg=#303030"),
+            Ok(Style::new().bold().on(Color::Rgb(48, 48, 48)))
+        );
+        assert!(parse_style("blinking").is_err());
+
+        let sunday = Style::new().fg(Color::Red).italic();
+        assert_eq!(
+            layer(sunday, Style::new().on(Color::Fixed(236))),
+            sunday.on(Color::Fixed(236))
+        );
+    }
+
+    #[test]
+    fn test_colorblind_palettes() {
+        for theme in [Theme::Deuteranopia, Theme::Protanopia, Theme::Tritanopia] {
+            let p = theme.palette();
+            let fgs = [p.sunday, p.saturday, p.company, p.personal].map(|s| s.foreground);
+            assert!(fgs
+                .iter()
+                .all(|c| !matches!(c, Some(Color::Red | Color::Blue))));
+            assert_ne!(p.sunday.foreground, p.saturday.foreground);
+            // A holiday on a Saturday differs from the Saturday by more than hue.
+            let holidays = [p.national, p.overrides, p.company, p.personal];
+            assert!(holidays.iter().all(|s| s.is_italic), "{theme:?}");
+            assert!(!p.sunday.is_italic && !p.saturday.is_italic);
+            // Today keeps the holiday's hue and italics.
+            let today = layer(p.national, p.today);
+            assert_eq!(today.foreground, p.national.foreground);
+            assert!(today.is_italic && today.is_reverse);
+        }
+        assert_ne!(
+            Theme::Deuteranopia.palette().sunday,
+            Theme::Protanopia.palette().sunday
+        );
+    }
+
+    #[test]
+    fn test_mono_palette() {
+        let mono = Theme::Mono.palette();
+        let styles = [
+            mono.sunday,
+            mono.saturday,
+            mono.company,
+            mono.personal,
+            mono.today,
+        ];
+        assert!(styles
+            .iter()
+            .all(|s| s.foreground.is_none() && s.background.is_none()));
+    }
+}
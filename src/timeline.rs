@@ -0,0 +1,115 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{consts::ENGLISH_MONTH_NAMES, holiday::HolidayInfo, Lang};
+
+/// Ranges longer than this are drawn one column per week instead of per day.
+const MAX_DAY_COLUMNS: i64 = 62;
+
+/// Renders holidays/events in `start..=end` as horizontal bands, one row per name.
+pub fn format_timeline(
+    holiday_info: &HolidayInfo,
+    start: NaiveDate,
+    end: NaiveDate,
+    first_weekday: Weekday,
+    lang: Lang,
+) -> Vec<String> {
+    let by_week = (end - start).num_days() >= MAX_DAY_COLUMNS;
+    // The first day of each column.
+    let columns = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| !by_week || *d == start || d.weekday() == first_weekday)
+        .collect::<Vec<_>>();
+    let column_of = |date: NaiveDate| columns.partition_point(|c| *c <= date) - 1;
+
+    let mut rows: Vec<(&str, Vec<bool>)> = vec![];
+    for (date, holiday) in holiday_info.iter_from(start).take_while(|(d, _)| *d <= end) {
+        let i = match rows.iter().position(|(name, _)| *name == holiday.name) {
+            Some(i) => i,
+            None => {
+                rows.push((&holiday.name, vec![false; columns.len()]));
+                rows.len() - 1
+            }
+        };
+        rows[i].1[column_of(date)] = true;
+    }
+
+    let label_width = rows.iter().map(|(name, _)| name.width()).max().unwrap_or(0);
+    let pad = |s: &str| format!("{s}{}", " ".repeat(label_width - s.width()));
+
+    // One cell per column; a month label occupies as many cells as its display width.
+    let mut month_cells = vec![" ".to_string(); columns.len()];
+    let mut next_free = 0;
+    for (i, c) in columns.iter().enumerate() {
+        let starts_month = i == 0 || columns[i - 1].month() != c.month();
+        if !starts_month || i < next_free {
+            continue;
+        }
+        let label = match lang {
+            Lang::Japanese => format!("{}月", c.month()),
+            Lang::English => ENGLISH_MONTH_NAMES[c.month0() as usize][..3].to_string(),
+        };
+        let end = (i + label.width()).min(columns.len());
+        month_cells[i..end].fill(String::new());
+        month_cells[i] = label;
+        next_free = end + 1;
+    }
+    let month_line = month_cells.concat();
+
+    let mut lines = vec![format!("{} {}", pad(""), month_line.trim_end())];
+    if !by_week {
+        let digits = columns
+            .iter()
+            .map(|d| char::from_digit(d.day() % 10, 10).unwrap())
+            .collect::<String>();
+        lines.push(format!("{} {digits}", pad("")));
+    }
+    lines.extend(rows.iter().map(|(name, cells)| {
+        let band = cells
+            .iter()
+            .map(|on| if *on { '█' } else { '·' })
+            .collect::<String>();
+        format!("{} {band}", pad(name))
+    }));
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{holiday::holidays_of, timeline::format_timeline, Lang};
+
+    #[test]
+    fn test_format_timeline() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let hi = holidays_of(&[
+            "2025/04/29,昭和の日",
+            "2025/04/30..2025/05/02,leave,personal",
+            "2025/05/05,こどもの日",
+        ]);
+        let lines = format_timeline(&hi, date(4, 27), date(5, 6), Weekday::Mon, Lang::English);
+        assert_eq!(
+            lines,
+            [
+                "           Apr May",
+                "           7890123456",
+                "昭和の日   ··█·······",
+                "leave      ···███····",
+                "こどもの日 ········█·",
+            ]
+        );
+        // A month label that would run into the one before it is left out.
+        let lines = format_timeline(&hi, date(4, 28), date(5, 6), Weekday::Mon, Lang::English);
+        assert_eq!(lines[0], "           Apr");
+
+        // A quarter gets a column per week from Monday, after the partial first one.
+        let lines = format_timeline(&hi, date(4, 1), date(6, 30), Weekday::Mon, Lang::Japanese);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "           4月  5月 6月");
+        assert_eq!(lines[1], "昭和の日   ····█·········");
+        assert_eq!(lines[2], "leave      ····█·········");
+        assert_eq!(lines[3], "こどもの日 ·····█········");
+    }
+}
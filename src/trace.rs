@@ -0,0 +1,153 @@
+use std::{cell::RefCell, io::Write, time::Instant};
+
+use serde::Serialize;
+
+/// Writes one JSON line per finished pipeline stage to stderr, e.g.
+/// `{"span":"load","start_us":12,"duration_us":2180}`, when enabled.
+pub struct Tracer {
+    enabled: bool,
+    epoch: Instant,
+    /// Lines kept instead of written, for tests
+    captured: Option<RefCell<Vec<String>>>,
+}
+
+/// A running stage; reported when dropped, so early returns are timed too.
+pub struct Span<'a> {
+    tracer: &'a Tracer,
+    name: &'static str,
+    start: Instant,
+}
+
+#[derive(Serialize)]
+struct Event {
+    span: &'static str,
+    start_us: u128,
+    duration_us: u128,
+}
+
+impl Tracer {
+    pub fn new(enabled: bool) -> Tracer {
+        Tracer {
+            enabled,
+            epoch: Instant::now(),
+            captured: None,
+        }
+    }
+
+    #[cfg(test)]
+    fn capturing() -> Tracer {
+        Tracer {
+            captured: Some(RefCell::default()),
+            ..Tracer::new(true)
+        }
+    }
+
+    pub fn span(&self, name: &'static str) -> Span<'_> {
+        Span {
+            tracer: self,
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Span<'_> {
+    fn drop(&mut self) {
+        if !self.tracer.enabled {
+            return;
+        }
+        let event = Event {
+            span: self.name,
+            start_us: (self.start - self.tracer.epoch).as_micros(),
+            duration_us: self.start.elapsed().as_micros(),
+        };
+        if let Ok(line) = serde_json::to_string(&event) {
+            match &self.tracer.captured {
+                Some(lines) => lines.borrow_mut().push(line),
+                // Tracing must never fail the run.
+                None => {
+                    let _ = writeln!(std::io::stderr(), "{line}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use clap::Parser;
+    use serde_json::Value;
+
+    use crate::{source::configured_sources, source::load_sources, trace::Tracer, Config, Lang};
+
+    fn spans(tracer: &Tracer) -> Vec<Value> {
+        tracer
+            .captured
+            .as_ref()
+            .unwrap()
+            .borrow()
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_span() {
+        let tracer = Tracer::capturing();
+        {
+            let _outer = tracer.span("render");
+            let _inner = tracer.span("load");
+        }
+        // Reported as they finish, inner first, each with its own start
+        let spans = spans(&tracer);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(
+            (&spans[0]["span"], &spans[1]["span"]),
+            (&"load".into(), &"render".into())
+        );
+        assert!(spans[0]["start_us"].as_u64() >= spans[1]["start_us"].as_u64());
+        assert!(spans[0]["duration_us"].as_u64() <= spans[1]["duration_us"].as_u64());
+    }
+
+    #[test]
+    fn test_traced_sources() {
+        let dir = std::env::temp_dir().join(format!("calp-trace-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("holidays.csv");
+        fs::write(&file, "2025/01/01,元日\n").unwrap();
+        let overrides = dir.join("overrides.csv");
+        fs::write(&overrides, "-2025/01/01\n").unwrap();
+        let config = Config::try_parse_from([
+            "calp",
+            "-e=utf8",
+            "-f",
+            file.to_str().unwrap(),
+            "--observances=christian",
+            "--override",
+            overrides.to_str().unwrap(),
+        ])
+        .unwrap();
+        let sources = configured_sources(&config.file_config, 2025..=2025, Lang::English);
+        let tracer = Tracer::capturing();
+        load_sources(&sources, &tracer).unwrap();
+        // Files, computed Japanese holidays, their substitutes, the other
+        // observances, then overrides
+        let stages = spans(&tracer)
+            .iter()
+            .map(|span| span["span"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            [
+                "load",
+                "observances",
+                "observances",
+                "observances",
+                "overrides"
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
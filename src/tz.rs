@@ -0,0 +1,171 @@
+use chrono::{
+    DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+/// The timezone dates are interpreted in: the system zone or an IANA zone from `--tz`.
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    pub fn new(tz: Option<Tz>) -> Zone {
+        tz.map_or(Zone::Local, Zone::Named)
+    }
+
+    /// Current date in this zone, so "today" does not depend on the system zone.
+    pub fn today(&self) -> NaiveDate {
+        self.date_at(Utc::now())
+    }
+
+    /// When the day after the one `now` falls on starts in this zone, for
+    /// redrawing when "today" moves on.
+    pub fn next_midnight(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // Where a transition skips midnight, the day starts at the first
+        // local time that exists.
+        fn start<T: TimeZone>(tz: &T, date: NaiveDate) -> Option<DateTime<Utc>> {
+            let midnight = date.and_time(NaiveTime::MIN);
+            (0..24)
+                .find_map(|h| {
+                    tz.from_local_datetime(&(midnight + Duration::hours(h)))
+                        .earliest()
+                })
+                .map(|d| d.with_timezone(&Utc))
+        }
+        let tomorrow = self.date_at(now).succ_opt()?;
+        match self {
+            Zone::Local => start(&chrono::Local, tomorrow),
+            Zone::Named(tz) => start(tz, tomorrow),
+        }
+    }
+
+    fn date_at(&self, now: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Zone::Local => now.with_timezone(&chrono::Local).date_naive(),
+            Zone::Named(tz) => now.with_timezone(tz).date_naive(),
+        }
+    }
+
+    /// Whether `--tz` named a zone other than Japan's, the only case in which
+    /// DST transitions are marked.
+    pub fn is_foreign(&self) -> bool {
+        matches!(self, Zone::Named(tz) if *tz != Tz::Asia__Tokyo)
+    }
+
+    /// UTC offset at the start of `date` in this zone.
+    pub fn offset_on(&self, date: NaiveDate) -> Option<FixedOffset> {
+        self.offset_at(date.and_time(NaiveTime::MIN), 1)
+    }
+
+    /// The offsets at the end of the day before and at the end of `date`,
+    /// when the UTC offset changes during it. The last second rather than
+    /// midnight is sampled, since a transition at midnight skips or repeats
+    /// it. Only for a [foreign](Zone::is_foreign) zone.
+    pub fn transition_on(&self, date: NaiveDate) -> Option<(FixedOffset, FixedOffset)> {
+        if !self.is_foreign() {
+            return None;
+        }
+        let end = |d: NaiveDate| self.offset_at(d.and_hms_opt(23, 59, 59)?, -1);
+        let before = end(date.pred_opt()?)?;
+        let after = end(date)?;
+        (before != after).then_some((before, after))
+    }
+
+    /// Offset at `local`, or, when a transition skips it, at the nearest hour
+    /// `step` hours away that exists.
+    fn offset_at(&self, local: NaiveDateTime, step: i64) -> Option<FixedOffset> {
+        fn resolve<T: TimeZone>(tz: &T, local: NaiveDateTime, step: i64) -> Option<FixedOffset> {
+            (0..24).find_map(|h| {
+                let result = tz.from_local_datetime(&(local + Duration::hours(h * step)));
+                if step > 0 {
+                    result.earliest()
+                } else {
+                    result.latest()
+                }
+                .map(|d| d.offset().fix())
+            })
+        }
+        match self {
+            Zone::Local => resolve(&chrono::Local, local, step),
+            Zone::Named(tz) => resolve(tz, local, step),
+        }
+    }
+
+    pub fn format_time(&self, t: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            Zone::Local => t.with_timezone(&chrono::Local).format(fmt).to_string(),
+            Zone::Named(tz) => t.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+}
+
+/// Parses an IANA timezone name such as `Europe/Berlin`.
+pub fn parse_tz(s: &str) -> Result<Tz, String> {
+    s.parse().map_err(|_| format!("unknown timezone: \"{s}\""))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use crate::tz::{parse_tz, Zone};
+
+    #[test]
+    fn test_date_at() {
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 2025-01-01 00:30 in Tokyo is still New Year's Eve in UTC and Berlin.
+        let now = Utc.with_ymd_and_hms(2024, 12, 31, 15, 30, 0).unwrap();
+        let tokyo = Zone::new(Some(parse_tz("Asia/Tokyo").unwrap()));
+        let berlin = Zone::new(Some(parse_tz("Europe/Berlin").unwrap()));
+        assert_eq!(tokyo.date_at(now), ymd(2025, 1, 1));
+        assert_eq!(berlin.date_at(now), ymd(2024, 12, 31));
+        let later = Utc.with_ymd_and_hms(2024, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(berlin.date_at(later), ymd(2025, 1, 1));
+    }
+
+    #[test]
+    fn test_next_midnight() {
+        let utc = |d, h| Utc.with_ymd_and_hms(2024, 9, d, h, 0, 0).unwrap();
+        let tokyo = Zone::new(Some(parse_tz("Asia/Tokyo").unwrap()));
+        let berlin = Zone::new(Some(parse_tz("Europe/Berlin").unwrap()));
+        // 2024-09-06 20:00 UTC is already the 7th in Tokyo.
+        assert_eq!(tokyo.next_midnight(utc(6, 20)), Some(utc(7, 15)));
+        assert_eq!(berlin.next_midnight(utc(6, 20)), Some(utc(6, 22)));
+        // Santiago skips from 00:00 to 01:00 on 2024-09-08, at 04:00 UTC.
+        let santiago = Zone::new(Some(parse_tz("America/Santiago").unwrap()));
+        assert_eq!(santiago.next_midnight(utc(7, 12)), Some(utc(8, 4)));
+        assert_eq!(santiago.next_midnight(utc(8, 4)), Some(utc(9, 3)));
+    }
+
+    #[test]
+    fn test_transition_on() {
+        let zone = Zone::new(Some(parse_tz("Europe/Berlin").unwrap()));
+        let ymd = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let (before, after) = zone.transition_on(ymd(2024, 3, 31)).unwrap();
+        assert_eq!(
+            (before.local_minus_utc(), after.local_minus_utc()),
+            (3600, 7200)
+        );
+        assert!(zone.transition_on(ymd(2024, 10, 27)).is_some());
+        assert!(zone.transition_on(ymd(2024, 10, 26)).is_none());
+
+        // Santiago skips midnight when DST starts and repeats the hour
+        // before it when DST ends.
+        let santiago = Zone::new(Some(parse_tz("America/Santiago").unwrap()));
+        let (before, after) = santiago.transition_on(ymd(2024, 9, 8)).unwrap();
+        assert_eq!(
+            (before.local_minus_utc(), after.local_minus_utc()),
+            (-4 * 3600, -3 * 3600)
+        );
+        assert!(santiago.transition_on(ymd(2024, 9, 7)).is_none());
+        assert!(santiago.transition_on(ymd(2024, 4, 6)).is_some());
+        assert!(santiago.transition_on(ymd(2024, 4, 7)).is_none());
+
+        let tokyo = Zone::new(Some(parse_tz("Asia/Tokyo").unwrap()));
+        assert!(tokyo.transition_on(ymd(1951, 5, 5)).is_none());
+        assert!(Zone::Local.transition_on(ymd(2024, 3, 31)).is_none());
+    }
+}
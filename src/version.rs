@@ -0,0 +1,60 @@
+use clap::ValueEnum;
+
+use crate::{
+    jp_holidays::{EQUINOX_YEARS, RULES_AS_OF},
+    observance::Observance,
+};
+
+/// `calp 0.1.0`, followed with `verbose` by what the build can do, for bug reports.
+pub fn format_version(verbose: bool) -> Vec<String> {
+    let mut lines = vec![format!("calp {}", env!("CARGO_PKG_VERSION"))];
+    if !verbose {
+        return lines;
+    }
+    let features = [cfg!(feature = "xlsx").then_some("xlsx")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let observances = Observance::value_variants()
+        .iter()
+        .filter_map(|o| o.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect::<Vec<_>>();
+    lines.extend([
+        format!("target: {}", env!("CALP_TARGET")),
+        format!(
+            "features: {}",
+            if features.is_empty() {
+                "none".to_string()
+            } else {
+                features.join(", ")
+            }
+        ),
+        format!(
+            "holidays: Japanese rules as of {RULES_AS_OF}, equinoxes {}-{}",
+            EQUINOX_YEARS.0, EQUINOX_YEARS.1
+        ),
+        format!("observances: {}", observances.join(", ")),
+    ]);
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use crate::version::format_version;
+
+    #[test]
+    fn test_format_version() {
+        let version = format!("calp {}", env!("CARGO_PKG_VERSION"));
+        let lines = format_version(true);
+        assert_eq!(lines[0], version);
+        assert_eq!(format_version(false), [version]);
+        assert!(lines[1].starts_with("target: "));
+        assert!(
+            lines.contains(&"observances: japan, christian, lunar-new-year, seollal".to_string())
+        );
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("holidays: ") && l.ends_with("equinoxes 1900-2150")));
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{
+    center,
+    consts::{
+        ENGLISH_MONTH_NAMES, ENGLISH_WEEK_NAMES, JAPANESE_LUNAR_MONTH_NAMES, JAPANESE_WEEK_NAMES,
+    },
+    holiday::HolidayInfo,
+    preformat_days,
+    weekplan::fit,
+    Lang,
+};
+
+/// Display width of a day cell on a month page.
+const CELL_WIDTH: usize = 11;
+/// Lines per day cell on a month page: the day number, then holiday names.
+const CELL_HEIGHT: usize = 4;
+
+/// Renders a plain-text wall calendar for printing: one framed page per
+/// month separated by form feeds, or all twelve months on a single page.
+pub fn format_wall(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    first_weekday: Weekday,
+    one_page: bool,
+    lang: Lang,
+) -> String {
+    if one_page {
+        format_one_page(holiday_info, year, first_weekday, lang).join("\n") + "\n"
+    } else {
+        (1..=12)
+            .map(|month| {
+                format_page(holiday_info, year, month, first_weekday, lang).join("\n") + "\n"
+            })
+            .collect::<Vec<_>>()
+            .join("\x0c")
+    }
+}
+
+fn week_names(first_weekday: Weekday, lang: Lang) -> Vec<&'static str> {
+    let names = match lang {
+        Lang::Japanese => JAPANESE_WEEK_NAMES,
+        Lang::English => ENGLISH_WEEK_NAMES,
+    };
+    let offset = first_weekday.num_days_from_sunday() as usize;
+    (0..7).map(|i| names[(offset + i) % 7]).collect()
+}
+
+fn month_title(year: i32, month: u32, lang: Lang) -> String {
+    match lang {
+        Lang::Japanese => format!(
+            "{year}年 {month}月 {}",
+            JAPANESE_LUNAR_MONTH_NAMES[month as usize - 1]
+        ),
+        Lang::English => format!("{} {year}", ENGLISH_MONTH_NAMES[month as usize - 1]),
+    }
+}
+
+/// Horizontal frame line for `columns` cells of `width`, e.g. `├──┼──┤`.
+fn rule(left: char, mid: char, right: char, columns: usize, width: usize) -> String {
+    let cells = vec!["─".repeat(width); columns];
+    format!("{left}{}{right}", cells.join(&mid.to_string()))
+}
+
+fn format_page(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    month: u32,
+    first_weekday: Weekday,
+    lang: Lang,
+) -> Vec<String> {
+    let inner = CELL_WIDTH * 7 + 6;
+    let mut ret = vec![
+        rule('┌', '─', '┐', 1, inner),
+        format!("│{}│", center(&month_title(year, month, lang), inner)),
+        rule('├', '┬', '┤', 7, CELL_WIDTH),
+    ];
+    let names = week_names(first_weekday, lang)
+        .iter()
+        .map(|n| center(n, CELL_WIDTH))
+        .collect::<Vec<_>>();
+    ret.push(format!("│{}│", names.join("│")));
+
+    let days = preformat_days(year, month, first_weekday);
+    let weeks = days
+        .chunks(7)
+        .filter(|w| w.iter().any(|d| *d != 0))
+        .collect::<Vec<_>>();
+    for week in weeks {
+        ret.push(rule('├', '┼', '┤', 7, CELL_WIDTH));
+        for line in 0..CELL_HEIGHT {
+            let cells = week
+                .iter()
+                .map(|d| {
+                    let date = NaiveDate::from_ymd_opt(year, month, *d);
+                    match (line, date) {
+                        (_, None) => " ".repeat(CELL_WIDTH),
+                        (0, Some(date)) => {
+                            let mark = if holiday_info.is_day_off(date) {
+                                "*"
+                            } else {
+                                ""
+                            };
+                            fit(&format!("{:>2}{mark}", date.day()), CELL_WIDTH)
+                        }
+                        (n, Some(date)) => {
+                            let holidays = holiday_info.holidays_on(date);
+                            fit(
+                                holidays.get(n - 1).map_or("", |h| h.name.as_str()),
+                                CELL_WIDTH,
+                            )
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            ret.push(format!("│{}│", cells.join("│")));
+        }
+    }
+    ret.push(rule('└', '┴', '┘', 7, CELL_WIDTH));
+    ret
+}
+
+fn format_one_page(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    first_weekday: Weekday,
+    lang: Lang,
+) -> Vec<String> {
+    // Each mini month is 7 three-column cells wide.
+    let width = 7 * 3;
+    let mini = |month: u32| {
+        let mut lines = vec![
+            center(&month_title(year, month, lang), width),
+            week_names(first_weekday, lang)
+                .iter()
+                .map(|n| fit(n, 2))
+                .collect::<Vec<_>>()
+                .join(" ")
+                + " ",
+        ];
+        for week in preformat_days(year, month, first_weekday).chunks(7) {
+            lines.push(
+                week.iter()
+                    .map(|d| match NaiveDate::from_ymd_opt(year, month, *d) {
+                        Some(date) if holiday_info.is_day_off(date) => format!("{d:>2}*"),
+                        Some(_) => format!("{d:>2} "),
+                        None => "   ".to_string(),
+                    })
+                    .collect::<String>(),
+            );
+        }
+        lines
+    };
+
+    let mut ret = vec![
+        rule('┌', '─', '┐', 1, width * 3 + 4),
+        format!("│{}│", center(&year.to_string(), width * 3 + 4)),
+        rule('├', '─', '┤', 1, width * 3 + 4),
+    ];
+    for row in 0..4 {
+        let months = (1..=3).map(|i| mini(row * 3 + i)).collect::<Vec<_>>();
+        for line in 0..months[0].len() {
+            let cells = months
+                .iter()
+                .map(|m| fit(&m[line], width))
+                .collect::<Vec<_>>();
+            ret.push(format!("│ {} │", cells.join(" ")));
+        }
+    }
+    ret.push(rule('└', '─', '┘', 1, width * 3 + 4));
+
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+    for (date, holiday) in holiday_info
+        .iter_from(start)
+        .take_while(|(d, _)| d.year() == year)
+        .filter(|(_, holiday)| holiday.is_day_off())
+    {
+        ret.push(format!(
+            "{:>2}/{:<2} {}",
+            date.month(),
+            date.day(),
+            holiday.name
+        ));
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Weekday;
+    use unicode_width::UnicodeWidthStr;
+
+    use crate::{holiday::holidays_of, wall::format_wall, Lang};
+
+    #[test]
+    fn test_format_wall() {
+        let hi = holidays_of(&["2025/05/05,こどもの日", "2025/05/05,端午,personal"]);
+        let wall = format_wall(&hi, 2025, Weekday::Sun, false, Lang::English);
+        let pages = wall.split('\x0c').collect::<Vec<_>>();
+        assert_eq!(pages.len(), 12);
+        let may = pages[4].lines().collect::<Vec<_>>();
+        assert!(may[1].contains("May 2025"));
+        assert!(may.iter().all(|line| line.width() == may[0].width()));
+        // 2025-05-05 is a Monday: a day off, named under its number
+        let cell = |n: usize| may[n].split('│').nth(2).unwrap().to_string();
+        assert_eq!(cell(10), " 5*        ");
+        assert_eq!(cell(11), "こどもの日 ");
+        assert_eq!(cell(12), "端午       ");
+
+        let page = format_wall(&hi, 2025, Weekday::Mon, true, Lang::Japanese);
+        assert!(!page.contains('\x0c'));
+        assert!(page.contains(" 5*"));
+        // Holidays are listed below the months, personal events are not.
+        assert!(page.ends_with("┘\n 5/5  こどもの日\n"), "{page}");
+    }
+}
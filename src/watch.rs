@@ -0,0 +1,86 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often [`FileWatcher::wait`] polls.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Notices when any of a set of files is modified, created or deleted, by
+/// comparing modification times. Polling a handful of small files costs
+/// next to nothing, and unlike filesystem notifications it follows editors
+/// that save by renaming a new file over the old one, files that don't
+/// exist yet, and network mounts, the same way on every platform.
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> FileWatcher {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+        FileWatcher { files }
+    }
+
+    /// Whether any file changed since the last call, or since the watcher was created.
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, seen) in &mut self.files {
+            let modified = modified(path);
+            if modified != *seen {
+                *seen = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Blocks until a file changes or, if given, `deadline` passes.
+    pub fn wait(&mut self, deadline: Option<SystemTime>) {
+        while !self.changed() {
+            if deadline.is_some_and(|d| SystemTime::now() >= d) {
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        env, fs,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::watch::FileWatcher;
+
+    #[test]
+    fn test_file_watcher() {
+        let path = env::temp_dir().join(format!("calp-watch-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = FileWatcher::new([path.clone()]);
+        assert!(!watcher.changed());
+        fs::write(&path, "2025/01/01,元日").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+
+        // Nothing changes, so only the deadline ends the wait.
+        let deadline = SystemTime::now() + Duration::from_millis(100);
+        watcher.wait(Some(deadline));
+        assert!(SystemTime::now() >= deadline);
+    }
+}
@@ -0,0 +1,114 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use clap::{builder::PossibleValue, ValueEnum};
+
+use crate::{weekplan::week_start, Lang};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WeekNumbers {
+    Iso,
+    Us,
+}
+
+impl ValueEnum for WeekNumbers {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Iso, Self::Us]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            WeekNumbers::Iso => PossibleValue::new("iso"),
+            WeekNumbers::Us => PossibleValue::new("us"),
+        })
+    }
+}
+
+/// Label printed before each week row of the grid.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WeekLabel {
+    /// 第1週… / W1…
+    OfMonth,
+    /// ISO 8601 week number, weeks starting Monday
+    Iso,
+    /// US week number: week 1 contains January 1st, weeks starting Sunday
+    Us,
+    /// `PW1` is the week containing the anchor date
+    Project(NaiveDate),
+}
+
+impl WeekLabel {
+    /// Label for row `week` (0-based) whose first cell is `first_day`, or blank
+    /// padding when `empty`.
+    pub fn format(
+        &self,
+        week: usize,
+        first_day: NaiveDate,
+        first_weekday: Weekday,
+        empty: bool,
+        lang: Lang,
+    ) -> String {
+        if empty {
+            return self.padding(lang);
+        }
+        // The middle of the row decides which numbered week it belongs to.
+        let mid = first_day + Days::new(3);
+        match self {
+            WeekLabel::OfMonth => match lang {
+                Lang::Japanese => format!("第{}週 ", week + 1),
+                Lang::English => format!("W{} ", week + 1),
+            },
+            WeekLabel::Iso => format!("W{:02} ", mid.iso_week().week()),
+            WeekLabel::Us => format!("W{:02} ", us_week(mid)),
+            WeekLabel::Project(anchor) => {
+                let weeks = (first_day - week_start(*anchor, first_weekday))
+                    .num_days()
+                    .div_euclid(7)
+                    + 1;
+                if weeks < 1 {
+                    self.padding(lang)
+                } else {
+                    format!("{:<6}", format!("PW{weeks}"))
+                }
+            }
+        }
+    }
+
+    /// Blank label as wide as `format`.
+    pub fn padding(&self, lang: Lang) -> String {
+        let width = match (self, lang) {
+            (WeekLabel::OfMonth, Lang::Japanese) => 6,
+            (WeekLabel::OfMonth, Lang::English) => 3,
+            (WeekLabel::Iso | WeekLabel::Us, _) => 4,
+            (WeekLabel::Project(_), _) => 6,
+        };
+        " ".repeat(width)
+    }
+}
+
+fn us_week(date: NaiveDate) -> u32 {
+    let jan1 = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap();
+    (date.ordinal0() + jan1.weekday().num_days_from_sunday()) / 7 + 1
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{weeknum::WeekLabel, Lang};
+
+    #[test]
+    fn test_week_label() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let label =
+            |l: WeekLabel, first_day| l.format(0, first_day, Weekday::Mon, false, Lang::English);
+
+        // 2024-12-30 (Mon) starts ISO week 1 of 2025.
+        assert_eq!(label(WeekLabel::Iso, date(2024, 12, 30)), "W01 ");
+        assert_eq!(label(WeekLabel::Iso, date(2024, 4, 1)), "W14 ");
+        assert_eq!(label(WeekLabel::Us, date(2024, 1, 7)), "W02 ");
+
+        let project = WeekLabel::Project(date(2024, 4, 3));
+        assert_eq!(label(project, date(2024, 4, 1)), "PW1   ");
+        assert_eq!(label(project, date(2024, 6, 17)), "PW12  ");
+        assert_eq!(label(project, date(2024, 3, 25)), "      ");
+    }
+}
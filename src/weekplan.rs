@@ -0,0 +1,162 @@
+use std::ops::RangeInclusive;
+
+use chrono::{Datelike, Days, NaiveDate, Timelike};
+use nu_ansi_term::Style;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    consts::{ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    holiday::HolidayInfo,
+    Lang,
+};
+
+/// Display width of each day column.
+const COLUMN_WIDTH: usize = 12;
+
+/// Parses an hour range e.g. `8-18`.
+pub fn parse_hours(s: &str) -> Result<RangeInclusive<u32>, String> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| format!("expected START-END: \"{s}\""))?;
+    let hour = |h: &str| match h.trim().parse::<u32>() {
+        Ok(h) if h < 24 => Ok(h),
+        _ => Err(format!("invalid hour: \"{h}\"")),
+    };
+    let (start, end) = (hour(start)?, hour(end)?);
+    if start > end {
+        return Err(format!("empty hour range: \"{s}\""));
+    }
+    Ok(start..=end)
+}
+
+/// Renders the week starting on `start` as one column per day, with an
+/// all-day row followed by one row per hour in `hours`. Timed events are
+/// placed in the row of their start hour.
+pub fn format_week_planner(
+    holiday_info: &HolidayInfo,
+    start: NaiveDate,
+    hours: RangeInclusive<u32>,
+    today: NaiveDate,
+    lang: Lang,
+) -> Vec<String> {
+    let days = start.iter_days().take(7).collect::<Vec<_>>();
+    let row = |label: &str, cells: Vec<String>| format!("{}│{}", fit(label, 7), cells.join("│"));
+
+    let header = days
+        .iter()
+        .map(|d| {
+            let weekday = d.weekday().num_days_from_sunday() as usize;
+            let s = match lang {
+                Lang::Japanese => format!(
+                    "{}/{} ({})",
+                    d.month(),
+                    d.day(),
+                    JAPANESE_WEEK_NAMES[weekday]
+                ),
+                Lang::English => {
+                    format!("{}/{} {}", d.month(), d.day(), ENGLISH_WEEK_NAMES[weekday])
+                }
+            };
+            let s = fit(&s, COLUMN_WIDTH);
+            if *d == today {
+                Style::new().reverse().paint(s).to_string()
+            } else {
+                s
+            }
+        })
+        .collect();
+    let mut ret = vec![row("", header)];
+    ret.push(format!(
+        "{}┼{}",
+        "─".repeat(7),
+        vec!["─".repeat(COLUMN_WIDTH); 7].join("┼")
+    ));
+
+    let entries = days
+        .iter()
+        .map(|d| holiday_info.holidays_on(*d))
+        .collect::<Vec<_>>();
+    let all_day = entries
+        .iter()
+        .map(|h| h.iter().filter(|h| h.time.is_none()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let lines = all_day.iter().map(|h| h.len()).max().unwrap_or(0).max(1);
+    for i in 0..lines {
+        let label = match (i, lang) {
+            (0, Lang::Japanese) => "終日",
+            (0, Lang::English) => "all day",
+            _ => "",
+        };
+        let cells = all_day
+            .iter()
+            .map(|h| fit(h.get(i).map_or("", |h| h.name.as_str()), COLUMN_WIDTH))
+            .collect();
+        ret.push(row(label, cells));
+    }
+    for hour in hours {
+        let cells = entries
+            .iter()
+            .map(|h| {
+                // Events starting in this hour show their name; events still
+                // running from an earlier hour show a continuation mark.
+                let starting = h
+                    .iter()
+                    .filter(|h| h.time.is_some_and(|t| t.start.hour() == hour))
+                    .map(|h| format!("{} {}", h.time.unwrap().start.format("%H:%M"), h.name))
+                    .collect::<Vec<_>>();
+                let running = h.iter().any(|h| {
+                    h.time.is_some_and(|t| {
+                        t.start.hour() < hour
+                            && t.end.is_some_and(|e| (e.hour(), e.minute()) > (hour, 0))
+                    })
+                });
+                match (starting.is_empty(), running) {
+                    (false, _) => fit(&starting.join(" "), COLUMN_WIDTH),
+                    (true, true) => fit("┆", COLUMN_WIDTH),
+                    (true, false) => " ".repeat(COLUMN_WIDTH),
+                }
+            })
+            .collect();
+        ret.push(row(&format!("{hour:>2}:00"), cells));
+    }
+    ret
+}
+
+/// First day of the week containing `date`.
+pub fn week_start(date: NaiveDate, first_weekday: chrono::Weekday) -> NaiveDate {
+    date - Days::new(date.weekday().days_since(first_weekday).into())
+}
+
+/// Pads or truncates `s` to exactly `width` display columns.
+pub fn fit(s: &str, width: usize) -> String {
+    let mut ret = String::new();
+    let mut w = 0;
+    for c in s.chars() {
+        let cw = c.width().unwrap_or(0);
+        if w + cw > width {
+            break;
+        }
+        ret.push(c);
+        w += cw;
+    }
+    ret + &" ".repeat(width - w)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::weekplan::{fit, parse_hours};
+
+    #[test]
+    fn test_parse_hours() {
+        assert_eq!(parse_hours("8-18"), Ok(8..=18));
+        assert!(parse_hours("18-8").is_err());
+        assert!(parse_hours("9-24").is_err());
+        assert!(parse_hours("9").is_err());
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("憲法記念日", 5), "憲法 ");
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use rust_xlsxwriter::{Format, FormatAlign, Workbook};
+
+use crate::{
+    consts::{ENGLISH_MONTH_NAMES, ENGLISH_WEEK_NAMES, JAPANESE_WEEK_NAMES},
+    holiday::{Holiday, HolidayInfo},
+    last_day_in_month, Lang, LibResult,
+};
+
+/// Column where the agenda table starts, right of the month grids.
+const AGENDA_COLUMN: u16 = 8;
+
+/// Writes a one-sheet workbook: the selected months as shaded grids, with a
+/// row of holiday names under each week, and an agenda table on the right.
+pub fn write_xlsx(
+    path: &str,
+    holiday_info: &HolidayInfo,
+    months: &[(i32, u32)],
+    first_weekday: Weekday,
+    lang: Lang,
+) -> LibResult<()> {
+    let title = Format::new().set_bold().set_font_size(14);
+    let bold = Format::new().set_bold().set_align(FormatAlign::Center);
+    let day = Format::new().set_align(FormatAlign::Center);
+    let holiday = day
+        .clone()
+        .set_background_color(0xFFC7CE)
+        .set_font_color(0x9C0006);
+    let sunday = day.clone().set_background_color(0xFCE4D6);
+    let saturday = day.clone().set_background_color(0xDDEBF7);
+    let note = Format::new().set_font_size(8).set_text_wrap();
+    let week_names = match lang {
+        Lang::Japanese => JAPANESE_WEEK_NAMES,
+        Lang::English => ENGLISH_WEEK_NAMES,
+    };
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let (Some(&(first_year, _)), Some(&(last_year, _))) = (months.first(), months.last()) else {
+        return Err("no months to write".into());
+    };
+    let sheet_name = if first_year == last_year {
+        first_year.to_string()
+    } else {
+        format!("{first_year}-{last_year}")
+    };
+    sheet.set_name(sheet_name)?;
+    for col in 0..7 {
+        sheet.set_column_width(col, 12)?;
+    }
+
+    let mut row = 0;
+    for &(year, month) in months {
+        let month_title = match lang {
+            Lang::Japanese => format!("{year}年{month}月"),
+            Lang::English => format!("{} {year}", ENGLISH_MONTH_NAMES[month as usize - 1]),
+        };
+        sheet.merge_range(row, 0, row, 6, &month_title, &title)?;
+        row += 1;
+        for col in 0..7 {
+            let weekday = (first_weekday.num_days_from_sunday() as usize + col) % 7;
+            sheet.write_string_with_format(row, col as u16, week_names[weekday], &bold)?;
+        }
+        row += 1;
+
+        let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        let last = last_day_in_month(year, month);
+        let mut date = first - Days::new(first.weekday().days_since(first_weekday).into());
+        while date <= last {
+            for col in 0..7 {
+                if date.month() == month {
+                    let format = if holiday_info.is_day_off(date) {
+                        &holiday
+                    } else if date.weekday() == Weekday::Sun {
+                        &sunday
+                    } else if date.weekday() == Weekday::Sat {
+                        &saturday
+                    } else {
+                        &day
+                    };
+                    sheet.write_number_with_format(row, col, date.day(), format)?;
+                    let names = holiday_info
+                        .holidays_on(date)
+                        .iter()
+                        .map(|h| h.name.as_str())
+                        .collect::<Vec<_>>();
+                    if !names.is_empty() {
+                        sheet.write_string_with_format(row + 1, col, names.join("\n"), &note)?;
+                    }
+                }
+                date = date + Days::new(1);
+            }
+            row += 2;
+        }
+        row += 1;
+    }
+
+    let headers = match lang {
+        Lang::Japanese => ["日付", "曜日", "名称", "種別"],
+        Lang::English => ["Date", "Weekday", "Name", "Category"],
+    };
+    for (i, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, AGENDA_COLUMN + i as u16, *header, &bold)?;
+    }
+    sheet.set_column_width(AGENDA_COLUMN, 12)?;
+    sheet.set_column_width(AGENDA_COLUMN + 2, 24)?;
+    let entries = agenda_entries(holiday_info, months);
+    for (i, (date, entry)) in entries.into_iter().enumerate() {
+        let row = i as u32 + 1;
+        let format = if holiday_info.is_day_off(date) {
+            &holiday
+        } else {
+            &day
+        };
+        let weekday = week_names[date.weekday().num_days_from_sunday() as usize];
+        let category = format!("{:?}", entry.category).to_lowercase();
+        sheet.write_string_with_format(
+            row,
+            AGENDA_COLUMN,
+            date.format("%Y-%m-%d").to_string(),
+            format,
+        )?;
+        sheet.write_string_with_format(row, AGENDA_COLUMN + 1, weekday, format)?;
+        sheet.write_string(row, AGENDA_COLUMN + 2, &entry.name)?;
+        sheet.write_string(row, AGENDA_COLUMN + 3, category)?;
+    }
+
+    workbook.save(path)?;
+    Ok(())
+}
+
+/// Entries on the days of `months`, with a span listed once: on its first
+/// day, or on the first displayed day if that is later.
+fn agenda_entries<'a>(
+    holiday_info: &'a HolidayInfo,
+    months: &[(i32, u32)],
+) -> Vec<(NaiveDate, &'a Holiday)> {
+    let (Some(&first), Some(&last)) = (months.iter().min(), months.iter().max()) else {
+        return vec![];
+    };
+    let shown = |d: NaiveDate| months.contains(&(d.year(), d.month()));
+    let end = last_day_in_month(last.0, last.1);
+    holiday_info
+        .iter_from(NaiveDate::from_ymd_opt(first.0, first.1, 1).unwrap())
+        .take_while(|(d, _)| *d <= end)
+        .filter(|(d, _)| shown(*d))
+        .filter(|(d, h)| {
+            h.span.is_none_or(|(first, _)| {
+                *d == first || d.pred_opt().is_none_or(|prev| !shown(prev))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::{
+        holiday::holidays_of,
+        xlsx::{agenda_entries, write_xlsx},
+        Lang,
+    };
+
+    #[test]
+    fn test_write_xlsx() {
+        let hi = holidays_of(&[
+            "2025/01/01,元日",
+            "2025/01/31..2025/03/02,walk,personal",
+            "2025/02/11,建国記念の日",
+            "2025/03/20,春分の日",
+        ]);
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let agenda = |months: &[(i32, u32)]| {
+            agenda_entries(&hi, months)
+                .into_iter()
+                .map(|(d, h)| (d, h.name.as_str()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            agenda(&[(2025, 1), (2025, 3)]),
+            [
+                (date(1, 1), "元日"),
+                (date(1, 31), "walk"),
+                (date(3, 1), "walk"),
+                (date(3, 20), "春分の日"),
+            ]
+        );
+        assert_eq!(
+            agenda(&[(2025, 2)]),
+            [(date(2, 1), "walk"), (date(2, 11), "建国記念の日")]
+        );
+
+        let path = env::temp_dir().join(format!("calp-xlsx-{}.xlsx", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_xlsx(
+            path,
+            &hi,
+            &[(2025, 1), (2025, 3)],
+            Weekday::Sun,
+            Lang::English,
+        )
+        .unwrap();
+        assert!(fs::read(path).unwrap().starts_with(b"PK"));
+        fs::remove_file(path).unwrap();
+    }
+}
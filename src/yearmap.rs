@@ -0,0 +1,142 @@
+use chrono::NaiveDate;
+use nu_ansi_term::{Color, Style};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    business::is_business_day, consts::ENGLISH_MONTH_NAMES, holiday::HolidayInfo, theme::Palette,
+    Lang,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum DayKind {
+    Workday,
+    Weekend,
+    Holiday,
+    /// A lone business day between two days off
+    Bridge,
+}
+
+fn day_kind(holiday_info: &HolidayInfo, date: NaiveDate) -> DayKind {
+    let off = |d: Option<NaiveDate>| d.is_some_and(|d| !is_business_day(holiday_info, d));
+    if holiday_info.is_day_off(date) {
+        DayKind::Holiday
+    } else if !is_business_day(holiday_info, date) {
+        DayKind::Weekend
+    } else if off(date.pred_opt()) && off(date.succ_opt()) {
+        DayKind::Bridge
+    } else {
+        DayKind::Workday
+    }
+}
+
+/// Renders `year` as 12 rows of 31 one-character cells, followed by a legend.
+pub fn format_year_heatmap(
+    holiday_info: &HolidayInfo,
+    year: i32,
+    palette: &Palette,
+    lang: Lang,
+) -> Vec<String> {
+    let cell = |kind| match kind {
+        DayKind::Workday => Style::new().dimmed().paint("·"),
+        DayKind::Weekend => palette.saturday.paint("□"),
+        DayKind::Holiday => palette.national.paint("■"),
+        DayKind::Bridge => Style::new().fg(Color::Yellow).paint("▲"),
+    };
+    let label = |month: u32| match lang {
+        Lang::Japanese => format!("{month:>2}月 "),
+        Lang::English => format!("{} ", &ENGLISH_MONTH_NAMES[month as usize - 1][..3]),
+    };
+    let mut ruler = [' '; 31];
+    for d in [1, 5, 10, 15, 20, 25, 30] {
+        for (i, c) in d.to_string().chars().enumerate() {
+            ruler[d - 1 + i] = c;
+        }
+    }
+
+    let mut ret = vec![format!(
+        "{}{}",
+        " ".repeat(label(1).width()),
+        ruler.iter().collect::<String>()
+    )];
+    for month in 1..=12 {
+        let row = (1..=31)
+            .map(|d| match NaiveDate::from_ymd_opt(year, month, d) {
+                Some(date) => cell(day_kind(holiday_info, date)).to_string(),
+                None => " ".to_string(),
+            })
+            .collect::<String>();
+        ret.push(format!("{}{row}", label(month)));
+    }
+    ret.push(String::new());
+    ret.push(match lang {
+        Lang::Japanese => format!(
+            "{} 祝日  {} 週末  {} 飛び石  {} 平日",
+            cell(DayKind::Holiday),
+            cell(DayKind::Weekend),
+            cell(DayKind::Bridge),
+            cell(DayKind::Workday)
+        ),
+        Lang::English => format!(
+            "{} holiday  {} weekend  {} bridge day  {} workday",
+            cell(DayKind::Holiday),
+            cell(DayKind::Weekend),
+            cell(DayKind::Bridge),
+            cell(DayKind::Workday)
+        ),
+    });
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        holiday::{Category, Holiday, HolidayInfo},
+        yearmap::{day_kind, DayKind},
+    };
+
+    #[test]
+    fn test_day_kind() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let mut hi = HolidayInfo::new();
+        for (m, d) in [(4, 29), (5, 3), (5, 6)] {
+            hi.add(
+                date(m, d),
+                Holiday {
+                    name: String::new(),
+                    category: Category::National,
+                    time: None,
+                    span: None,
+                    source: String::new(),
+                },
+            );
+        }
+        assert_eq!(day_kind(&hi, date(4, 29)), DayKind::Holiday);
+        assert_eq!(day_kind(&hi, date(4, 30)), DayKind::Workday);
+        assert_eq!(day_kind(&hi, date(5, 4)), DayKind::Weekend);
+        // Friday between Thursday off and the weekend
+        hi.add(
+            date(5, 2),
+            Holiday {
+                name: String::new(),
+                category: Category::Company,
+                time: None,
+                span: None,
+                source: String::new(),
+            },
+        );
+        assert_eq!(day_kind(&hi, date(5, 1)), DayKind::Workday);
+        hi.add(
+            date(4, 30),
+            Holiday {
+                name: String::new(),
+                category: Category::Company,
+                time: None,
+                span: None,
+                source: String::new(),
+            },
+        );
+        assert_eq!(day_kind(&hi, date(5, 1)), DayKind::Bridge);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+/// An empty home directory for one test, so no config, holiday file or
+/// cache of the user's is read.
+fn home(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("calp-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn calp(home: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_calp"))
+        .args(["--caps", "color=none", "--lang", "en"])
+        .args(args)
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_STATE_HOME")
+        .env_remove("XDG_CACHE_HOME")
+        .env_remove("CALP_CONFIG")
+        .env_remove("CALP_PROFILE")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_show_unsorted_months() {
+    let home = home("show");
+    let output = calp(&home, &["show", "2025-03", "2025-01", "2025-03"]);
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    let (jan, mar) = (out.find("January").unwrap(), out.find("March").unwrap());
+    assert!(jan < mar);
+    assert_eq!(out.matches("March").count(), 1);
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_repeated_months() {
+    let home = home("months");
+    let output = calp(&home, &["-m", "1-2", "-m", "11", "2025"]);
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    for month in ["January", "February", "November"] {
+        assert!(out.contains(month), "{out}");
+    }
+    assert!(!out.contains("March"));
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_export_ignores_output_encoding() {
+    let home = home("export");
+    let file = home.join("holidays.csv");
+    fs::write(&file, "2025/01/01,元日\n").unwrap();
+    let file = file.to_str().unwrap();
+    let args = [
+        "-e",
+        "utf8",
+        "--output-encoding",
+        "sjis",
+        "-f",
+        file,
+        "export",
+        "--ics",
+        "2025",
+    ];
+    let output = calp(&home, &args);
+    assert!(output.status.success(), "{output:?}");
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(out.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(out.contains("SUMMARY:元日\r\n"), "{out}");
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_week_anchor_conflicts() {
+    let home = home("anchor");
+    let anchored = |other: &str| calp(&home, &["--week-anchor", "2025-01-06", other, "2025"]);
+    assert!(!anchored("--week-of-month").status.success());
+    assert!(!anchored("--week-numbers=iso").status.success());
+    let output = calp(&home, &["--week-anchor", "2025-01-06", "-m", "1", "2025"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(stdout(&output).contains("PW1"));
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_diagnostics_on_stderr() {
+    let home = home("stderr");
+    let missing = home.join("missing.csv");
+    let output = calp(&home, &["-f", missing.to_str().unwrap(), "2025"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.csv"));
+
+    let file = home.join("holidays.csv");
+    fs::write(&file, "not a holiday\n").unwrap();
+    let output = calp(&home, &["-e", "utf8", "-f", file.to_str().unwrap(), "2025"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("warning"));
+    let out = stdout(&output);
+    assert!(out.contains("January") && !out.contains("warning"), "{out}");
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_query_stdin() {
+    let home = home("query");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_calp"))
+        .args(["--caps", "color=none", "--lang", "en", "query", "--stdin"])
+        .env("HOME", &home)
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("CALP_CONFIG")
+        .env_remove("CALP_PROFILE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(b"2025-01-03\n\n  2025-01-04  \nnot a date\n")
+        .unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{out}");
+    assert_eq!(lines[0], "2025-01-03\tFr\t-\ttrue");
+    assert_eq!(lines[1], "2025-01-04\tSa\t-\tfalse");
+    assert!(lines[2].starts_with("not a date\terror: "));
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_sjis_output() {
+    let home = home("sjis");
+    let file = home.join("holidays.csv");
+    fs::write(&file, "2025/01/01,🎍元日\n").unwrap();
+    let file = file.to_str().unwrap();
+    let args = [
+        "-e",
+        "utf8",
+        "--output-encoding",
+        "sjis",
+        "-f",
+        file,
+        "query",
+        "2025-01-01",
+    ];
+    let output = calp(&home, &args);
+    assert!(output.status.success(), "{output:?}");
+    // Emoji have no Shift_JIS mapping.
+    let (expected, _, _) = encoding_rs::SHIFT_JIS.encode("2025-01-01\tWe\t?元日\tfalse\n");
+    assert_eq!(output.stdout, expected.into_owned());
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_warn_uncovered() {
+    let home = home("uncovered");
+    let file = home.join("holidays.csv");
+    fs::write(&file, "2025/01/01,元日\n").unwrap();
+    let file = file.to_str().unwrap();
+    let stderr = |args: &[&str]| {
+        let output = calp(&home, &[&["-e", "utf8", "-f", file], args].concat());
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+    assert_eq!(stderr(&["-m", "1", "2025"]), "");
+    assert_eq!(
+        stderr(&["-m", "1", "2080"]),
+        "warning: no holiday data for 2080\n"
+    );
+    assert_eq!(stderr(&["-q", "-m", "1", "2080"]), "");
+    assert_eq!(
+        stderr(&["holidays", "2080"]),
+        "warning: no holiday data for 2080\n"
+    );
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_monday() {
+    let home = home("monday");
+    let output = calp(&home, &["--monday", "-m", "6", "2025"]);
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    assert!(out.contains("Mo Tu We Th Fr Sa Su"), "{out}");
+    let output = calp(
+        &home,
+        &["--monday", "--first-weekday", "sat", "-m", "6", "2025"],
+    );
+    assert!(!output.status.success());
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_month_range() {
+    let home = home("range");
+    let file = home.join("holidays.csv");
+    fs::write(&file, "2024/11/03,文化の日\n2025/01/01,元日\n").unwrap();
+    let file = file.to_str().unwrap();
+    let args = [
+        "-e", "utf8", "-f", file, "--from", "2024-11", "--to", "2025-03",
+    ];
+    let output = calp(&home, &args);
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    let months = ["November 2024", "January 2025", "March 2025"];
+    assert!(months.iter().all(|m| out.contains(m)), "{out}");
+    assert!(!out.contains("October") && !out.contains("April"), "{out}");
+    // Both years have holiday data, so neither is warned about.
+    assert!(output.stderr.is_empty(), "{output:?}");
+
+    let output = calp(&home, &["--from", "2025-03", "--to", "2024-11"]);
+    assert!(!output.status.success());
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_year_ranges() {
+    let home = home("years");
+    let output = calp(&home, &["-q", "2025-2026", "2024"]);
+    assert!(output.status.success(), "{output:?}");
+    let out = stdout(&output);
+    let banners = out
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.len() == 4 && line.parse::<i32>().is_ok())
+        .collect::<Vec<_>>();
+    // In the order given, each with a banner of its own
+    assert_eq!(banners, ["2025", "2026", "2024"]);
+    assert_eq!(out.matches("December").count(), 3);
+    assert!(!calp(&home, &["2026-2024"]).status.success());
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn test_filtered_structured_output() {
+    let home = home("filtered");
+    let file = home.join("holidays.csv");
+    fs::write(&file, "2025/05/03,憲法記念日\n2025/05/05,こどもの日\n").unwrap();
+    let file = file.to_str().unwrap();
+    let run = |format: &str, only: &[&str]| {
+        let args = [
+            &["-e", "utf8", "-f", file, "--format", format],
+            only,
+            &["-m", "5", "2025"],
+        ];
+        let output = calp(&home, &args.concat());
+        assert!(output.status.success(), "{output:?}");
+        stdout(&output)
+    };
+    assert_eq!(run("csv", &[]).lines().count(), 1 + 31);
+    assert_eq!(
+        run("csv", &["--only", "holidays"]),
+        "year,month,day,weekday,is_holiday,is_today\n\
+         2025,5,3,Sat,true,false\n\
+         2025,5,5,Mon,true,false\n"
+    );
+    let tsv = run("tsv", &["--only", "workdays", "--only-weekdays", "mon"]);
+    assert_eq!(
+        tsv.lines().skip(1).collect::<Vec<_>>(),
+        [
+            "2025\t5\t12\tMon\tfalse\tfalse",
+            "2025\t5\t19\tMon\tfalse\tfalse",
+            "2025\t5\t26\tMon\tfalse\tfalse"
+        ]
+    );
+
+    // Days filtered out are null, as are those outside the month.
+    let json_days = |only: &[&str]| {
+        let months: serde_json::Value = serde_json::from_str(&run("json", only)).unwrap();
+        months[0]["weeks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|week| week.as_array().unwrap())
+            .filter_map(|day| Some(day["date"].as_str()?.to_string()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(json_days(&[]).len(), 31);
+    assert_eq!(
+        json_days(&["--only", "holidays"]),
+        ["2025-05-03", "2025-05-05"]
+    );
+    assert_eq!(
+        json_days(&["--only-weekdays", "mon"]),
+        ["2025-05-05", "2025-05-12", "2025-05-19", "2025-05-26"]
+    );
+    fs::remove_dir_all(home).unwrap();
+}